@@ -6,33 +6,86 @@ type PmAmmPool = {
   x: number;
   y: number;
   l0: number;
-  dynamic_on: number;
+  schedule: number;
   fee_bps: number;
   expiry_ts: number;
+  fees: number;
+  l_floor: number;
+  l_ceiling: number;
+  handoff_ts: number;
+  collateral: number;
+  shares_outstanding: number;
   bump: number;
 };
 
 function decodePmAmmPool(data: Buffer): PmAmmPool {
-  if (data.length < 8 + 32 + 8 + 8 + 8 + 1 + 2 + 8 + 1) throw new Error("account data too short");
+  if (data.length < 8 + 32 + 8 + 8 + 8 + 1 + 2 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 1) throw new Error("account data too short");
   let o = 8; // skip discriminator
   const market = new PublicKey(data.subarray(o, o + 32)); o += 32;
   const x = Number(data.readBigUInt64LE(o)); o += 8;
   const y = Number(data.readBigUInt64LE(o)); o += 8;
   const l0 = Number(data.readBigUInt64LE(o)); o += 8;
-  const dynamic_on = data.readUInt8(o); o += 1;
+  const schedule = data.readUInt8(o); o += 1;
   const fee_bps = data.readUInt16LE(o); o += 2;
   const expiry_ts = Number(data.readBigInt64LE(o)); o += 8;
+  const fees = Number(data.readBigUInt64LE(o)); o += 8;
+  const l_floor = Number(data.readBigUInt64LE(o)); o += 8;
+  const l_ceiling = Number(data.readBigUInt64LE(o)); o += 8;
+  const handoff_ts = Number(data.readBigInt64LE(o)); o += 8;
+  const collateral = Number(data.readBigUInt64LE(o)); o += 8;
+  const shares_outstanding = Number(data.readBigUInt64LE(o)); o += 8;
   const bump = data.readUInt8(o); o += 1;
-  return { market, x, y, l0, dynamic_on, fee_bps, expiry_ts, bump };
+  return { market, x, y, l0, schedule, fee_bps, expiry_ts, fees, l_floor, l_ceiling, handoff_ts, collateral, shares_outstanding, bump };
 }
 
-function effectiveLiquidity(l0: number, dynamic_on: number, expiry_ts: number, now_ts: number): number {
-  if (dynamic_on === 0) return l0;
-  const dt = Math.max(expiry_ts - now_ts, 1);
-  let L = l0 * Math.sqrt(dt);
-  if (!Number.isFinite(L) || L <= 0) L = l0;
-  const cap = l0 * 1_000_000;
-  return Math.min(L, cap);
+// Market.fee_bps_platform and fee_bps_creator, past the discriminator and the fields before them
+const MARKET_FEE_BPS_OFFSET = 208;
+
+function decodeMarketFeeBps(data: Buffer): { platform: number; creator: number } {
+  if (data.length < MARKET_FEE_BPS_OFFSET + 4) throw new Error("account data too short");
+  return { platform: data.readUInt16LE(MARKET_FEE_BPS_OFFSET), creator: data.readUInt16LE(MARKET_FEE_BPS_OFFSET + 2) };
+}
+
+// Liquidity schedule: 0 static, 1 sqrt decay, 2 linear decay, clamped to [floor, ceiling]
+function effectiveLiquidity(pool: PmAmmPool, now_ts: number): number {
+  const dt = Math.max(pool.expiry_ts - now_ts, 1);
+  const L = pool.schedule === 1 ? pool.l0 * Math.sqrt(dt) : pool.schedule === 2 ? pool.l0 * dt : pool.l0;
+  return Math.min(Math.max(L, pool.l_floor), pool.l_ceiling);
+}
+
+// Standard normal pdf and CDF (Abramowitz–Stegun 7.1.26), as in the program's math.rs
+function normPdf(z: number): number {
+  return Math.exp(-0.5 * z * z) / Math.sqrt(2 * Math.PI);
+}
+
+function normCdf(z: number): number {
+  if (z < 0) return 1 - normCdf(-z);
+  const t = 1 / (1 + 0.2316419 * z);
+  const poly = t * (0.31938153 + t * (-0.356563782 + t * (1.781477937 + t * (-1.821255978 + t * 1.330274429))));
+  return 1 - normPdf(z) * poly;
+}
+
+// pm-AMM reserve per unit of liquidity: y = L·ŷ(z), x = L·ŷ(-z)
+function reserve(z: number): number {
+  return Math.max(z * normCdf(z) + normPdf(z), 0);
+}
+
+const MAX_Z = 6;
+
+// Where (x, y) sit on the curve, by the same bisection as the program's pmamm_z_fixed
+function solveZ(x: number, y: number): number {
+  const h = (z: number) => (x - y) * reserve(z) + y * z;
+  let lo = -MAX_Z;
+  let hi = MAX_Z;
+  if (h(lo) >= 0) return lo;
+  if (h(hi) < 0) return hi;
+  for (let i = 0; i < 48; i++) {
+    const mid = (lo + hi) / 2;
+    const at = h(mid);
+    if (at === 0) return mid;
+    if (at < 0) lo = mid; else hi = mid;
+  }
+  return (lo + hi) / 2;
 }
 
 export async function GET(req: NextRequest) {
@@ -64,39 +117,35 @@ export async function GET(req: NextRequest) {
     // Derive pmamm pool PDA
     const [pmammPda] = PublicKey.findProgramAddressSync([Buffer.from("pmamm"), market.toBuffer()], new PublicKey(programIdStr));
 
-    const acct = await connection.getAccountInfo(pmammPda);
-    if (!acct)
+    const [acct, marketAcct] = await connection.getMultipleAccountsInfo([pmammPda, market]);
+    if (!acct || !marketAcct)
       return new Response(JSON.stringify({ error: "PmAmmPool not found for market" }), { status: 404 });
 
     const pool = decodePmAmmPool(Buffer.from(acct.data));
+    const feeBps = decodeMarketFeeBps(Buffer.from(marketAcct.data));
     const now = Math.floor(Date.now() / 1000);
     if (now >= pool.expiry_ts)
       return new Response(JSON.stringify({ error: "market expired" }), { status: 400 });
-
-    const L = effectiveLiquidity(pool.l0, pool.dynamic_on, pool.expiry_ts, now);
-    let vx = pool.x + L / 2;
-    let vy = pool.y + L / 2;
-    const k = vx * vy;
-
-    const s = size;
-    let deltaY = 0;
-    if (side === 0) {
-      const maxBuy = Math.max(vx - 1, 0);
-      const buy = Math.min(s, maxBuy);
-      const newVx = Math.max(vx - buy, 1);
-      const newVy = k / newVx;
-      deltaY = Math.max(newVy - vy, 0);
-      vx = newVx; vy = newVy;
-    } else {
-      const newVx = vx + s;
-      const newVy = Math.max(k / newVx, 0);
-      deltaY = Math.max(vy - newVy, 0);
-      vx = newVx; vy = newVy;
-    }
-
-    const fee = Math.max(deltaY * (pool.fee_bps / 10_000), 0);
-    const notional = side === 0 ? (deltaY + fee) : Math.max(deltaY - fee, 0); // mirror on-chain
-    const price = s > 0 ? notional / s : 0;
+    if (pool.handoff_ts !== 0 && now >= pool.handoff_ts)
+      return new Response(JSON.stringify({ error: "pm-AMM handed off to the order book" }), { status: 400 });
+
+    const L = effectiveLiquidity(pool, now);
+    const z = solveZ(pool.x, pool.y);
+    const target = side === 0 ? z + size / L : z - size / L;
+    const to = Math.min(Math.max(target, -MAX_Z), MAX_Z);
+    // the program refuses a trade the price bound would cut short rather than partly fill it
+    if (to !== target)
+      return new Response(JSON.stringify({ error: "trade would be partially filled at the price bound" }), { status: 400 });
+    const moved = Math.abs(L * (reserve(to) - reserve(z)));
+    const deltaY = side === 0 ? Math.ceil(moved) : Math.floor(moved);
+
+    const fee = Math.ceil(deltaY * (pool.fee_bps / 10_000));
+    // the market's platform and creator fees, on what the curve moved, come on top of the pool's
+    const platform_fee = Math.ceil(deltaY * (feeBps.platform / 10_000));
+    const creator_fee = Math.ceil(deltaY * (feeBps.creator / 10_000));
+    const charged = fee + platform_fee + creator_fee;
+    const notional = side === 0 ? (deltaY + charged) : Math.max(deltaY - charged, 0); // mirror on-chain
+    const price = notional / size;
     const price_bps = Math.round(price * 10_000);
 
     return new Response(JSON.stringify({
@@ -107,16 +156,25 @@ export async function GET(req: NextRequest) {
       side,
       size,
       price,
+      marginal_price: normCdf(z),
       price_bps,
       fee,
+      platform_fee,
+      creator_fee,
       notional,
       pool: {
         x: pool.x,
         y: pool.y,
         l0: pool.l0,
-        dynamic_on: pool.dynamic_on,
+        schedule: pool.schedule,
+        l_floor: pool.l_floor,
+        l_ceiling: pool.l_ceiling,
         fee_bps: pool.fee_bps,
         expiry_ts: pool.expiry_ts,
+        handoff_ts: pool.handoff_ts,
+        fees: pool.fees,
+        collateral: pool.collateral,
+        shares_outstanding: pool.shares_outstanding,
       },
       cluster,
     }), {
@@ -1,31 +1,51 @@
 import { NextRequest } from "next/server";
-import { Connection, PublicKey, Transaction, TransactionInstruction } from "@solana/web3.js";
+import { Connection, PublicKey, SystemProgram, Transaction, TransactionInstruction } from "@solana/web3.js";
 import { createHash } from "crypto";
 
+const ASSOCIATED_TOKEN_PROGRAM_ID = new PublicKey("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+
 function sighash(name: string) {
   const preimage = Buffer.from(`global:${name}`);
   const hash = createHash("sha256").update(preimage).digest();
   return hash.subarray(0, 8);
 }
 
-function u64le(n: number) {
+function u64le(n: number | bigint) {
   const b = Buffer.alloc(8);
-  b.writeBigUInt64LE(BigInt(Math.floor(n)));
+  b.writeBigUInt64LE(typeof n === "bigint" ? n : BigInt(Math.floor(n)));
   return b;
 }
 
+const U64_MAX = (1n << 64n) - 1n;
+
 function decodePmAmmPool(data: Buffer) {
-  if (data.length < 8 + 32 + 8 + 8 + 8 + 1 + 2 + 8 + 1) throw new Error("account data too short");
+  if (data.length < 8 + 32 + 8 + 8 + 8 + 1 + 2 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 1) throw new Error("account data too short");
   let o = 8; // skip discriminator
   const market = new PublicKey(data.subarray(o, o + 32)); o += 32;
   const x = Number(data.readBigUInt64LE(o)); o += 8;
   const y = Number(data.readBigUInt64LE(o)); o += 8;
   const l0 = Number(data.readBigUInt64LE(o)); o += 8;
-  const dynamic_on = data.readUInt8(o); o += 1;
+  const schedule = data.readUInt8(o); o += 1;
   const fee_bps = data.readUInt16LE(o); o += 2;
   const expiry_ts = Number(data.readBigInt64LE(o)); o += 8;
+  const fees = Number(data.readBigUInt64LE(o)); o += 8;
+  const l_floor = Number(data.readBigUInt64LE(o)); o += 8;
+  const l_ceiling = Number(data.readBigUInt64LE(o)); o += 8;
+  const handoff_ts = Number(data.readBigInt64LE(o)); o += 8;
+  const collateral = Number(data.readBigUInt64LE(o)); o += 8;
+  const shares_outstanding = Number(data.readBigUInt64LE(o)); o += 8;
   const bump = data.readUInt8(o); o += 1;
-  return { market, x, y, l0, dynamic_on, fee_bps, expiry_ts, bump };
+  return { market, x, y, l0, schedule, fee_bps, expiry_ts, fees, l_floor, l_ceiling, handoff_ts, collateral, shares_outstanding, bump };
+}
+
+function decodeCollateralVault(data: Buffer) {
+  if (data.length < 8 + 32 + 32 + 32 + 8 + 1) throw new Error("account data too short");
+  let o = 8; // skip discriminator
+  const market = new PublicKey(data.subarray(o, o + 32)); o += 32;
+  const mint = new PublicKey(data.subarray(o, o + 32)); o += 32;
+  const token_account = new PublicKey(data.subarray(o, o + 32)); o += 32;
+  const balance = Number(data.readBigUInt64LE(o)); o += 8;
+  return { market, mint, token_account, balance };
 }
 
 export async function POST(req: NextRequest) {
@@ -35,6 +55,9 @@ export async function POST(req: NextRequest) {
     const side: number = body.side;
     const size: number = body.size;
     const takerStr: string = body.taker;
+    // slippage bounds, in collateral or shares depending on the side; unbounded unless given
+    const minAmountOut = BigInt(body.minAmountOut ?? 0);
+    const maxAmountIn = body.maxAmountIn === undefined ? U64_MAX : BigInt(body.maxAmountIn);
     const cluster: "devnet" | "mainnet-beta" = body.cluster || "devnet";
 
     if (!marketStr || !(side === 0 || side === 1) || !(size > 0) || !takerStr) {
@@ -62,15 +85,43 @@ export async function POST(req: NextRequest) {
     const pool = decodePmAmmPool(Buffer.from(acct.data));
     const now = Math.floor(Date.now() / 1000);
     if (now >= pool.expiry_ts) return new Response(JSON.stringify({ error: "market expired" }), { status: 400 });
-
-    // Build Anchor instruction data: 8-byte discriminator + u8 side + u64 size
+    if (pool.handoff_ts !== 0 && now >= pool.handoff_ts)
+      return new Response(JSON.stringify({ error: "pm-AMM handed off to the order book" }), { status: 400 });
+
+    // The trade settles against the market's collateral vault: its mint and token program
+    // decide the taker's token account, their ATA unless one is passed
+    const [positionPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("pmamm_position"), market.toBuffer(), taker.toBuffer()],
+      programId
+    );
+    const [vaultPda] = PublicKey.findProgramAddressSync([Buffer.from("collateral"), market.toBuffer()], programId);
+    const [feesPda] = PublicKey.findProgramAddressSync([Buffer.from("fees"), market.toBuffer()], programId);
+    const vaultAcct = await connection.getAccountInfo(vaultPda);
+    if (!vaultAcct) return new Response(JSON.stringify({ error: "CollateralVault not found for market" }), { status: 404 });
+    const vault = decodeCollateralVault(Buffer.from(vaultAcct.data));
+    const mintAcct = await connection.getAccountInfo(vault.mint);
+    if (!mintAcct) return new Response(JSON.stringify({ error: "collateral mint not found" }), { status: 404 });
+    const tokenProgram = mintAcct.owner;
+    const takerTokens = body.takerTokens
+      ? new PublicKey(body.takerTokens)
+      : PublicKey.findProgramAddressSync([taker.toBuffer(), tokenProgram.toBuffer(), vault.mint.toBuffer()], ASSOCIATED_TOKEN_PROGRAM_ID)[0];
+
+    // Build Anchor instruction data: 8-byte discriminator + u8 side + u64 size + u64 min_amount_out + u64 max_amount_in
     const disc = sighash("trade_pmamm");
-    const data = Buffer.concat([disc, Buffer.from([side & 0xff]), u64le(size)]);
+    const data = Buffer.concat([disc, Buffer.from([side & 0xff]), u64le(size), u64le(minAmountOut), u64le(maxAmountIn)]);
 
     const keys = [
       { pubkey: market, isSigner: false, isWritable: false },
       { pubkey: pmammPda, isSigner: false, isWritable: true },
-      { pubkey: taker, isSigner: true, isWritable: false },
+      { pubkey: positionPda, isSigner: false, isWritable: true },
+      { pubkey: vaultPda, isSigner: false, isWritable: true },
+      { pubkey: vault.token_account, isSigner: false, isWritable: true },
+      { pubkey: vault.mint, isSigner: false, isWritable: false },
+      { pubkey: takerTokens, isSigner: false, isWritable: true },
+      { pubkey: feesPda, isSigner: false, isWritable: true },
+      { pubkey: tokenProgram, isSigner: false, isWritable: false },
+      { pubkey: taker, isSigner: true, isWritable: true },
+      { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
     ];
 
     const ix = new TransactionInstruction({ programId, keys, data });
@@ -13,16 +13,25 @@ export type QuoteResponse = {
   side: number;
   size: number;
   price: number;
+  marginal_price: number;
   price_bps: number;
   fee: number;
+  platform_fee: number;
+  creator_fee: number;
   notional: number;
   pool: {
     x: number;
     y: number;
     l0: number;
-    dynamic_on: number;
+    schedule: number;
+    l_floor: number;
+    l_ceiling: number;
     fee_bps: number;
     expiry_ts: number;
+    handoff_ts: number;
+    fees: number;
+    collateral: number;
+    shares_outstanding: number;
   };
   cluster: "devnet" | "mainnet-beta";
 };
@@ -32,6 +41,9 @@ export type TradeRequest = {
   side: 0 | 1;
   size: number;
   taker: string;
+  takerTokens?: string;
+  minAmountOut?: number;
+  maxAmountIn?: number;
   cluster?: "devnet" | "mainnet-beta";
 };
 
@@ -44,9 +44,17 @@ function decodeEvent(log: string, txSig: string, slot: number, ixIndex: number):
         "size",
         "ts",
         "l0",
-        "dynamic_on",
+        "schedule",
+        "l_floor",
+        "l_ceiling",
         "fee_bps",
         "expiry_ts",
+        "handoff_ts",
+        "platform_fee",
+        "creator_fee",
+        "lp_fee",
+        "recipient",
+        "amount",
       ]);
       data[key] = numKeys.has(key) ? Number(valRaw) : valRaw;
     }
@@ -121,6 +129,16 @@ async function upsertEvent(event: EventLog) {
         console.log(`[indexer] TradeExecuted: ${t.id}`);
         break;
       }
+      case "FeesCharged": {
+        const total = event.data.platform_fee + event.data.creator_fee + event.data.lp_fee;
+        console.log(`[indexer] FeesCharged: ${event.data.market} taker=${event.data.taker} total=${total}`);
+        break;
+      }
+      case "FeesClaimed": {
+        const recipient = event.data.recipient === 0 ? "Platform" : "Creator";
+        console.log(`[indexer] FeesClaimed: ${event.data.market} ${recipient} amount=${event.data.amount}`);
+        break;
+      }
     }
   } catch (e: any) {
     if (e?.code === "ConditionalCheckFailedException") {
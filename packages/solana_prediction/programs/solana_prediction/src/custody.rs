@@ -0,0 +1,119 @@
+// Collateral movements between a trader's token account and the market's vault. The vault's
+// token account is owned by the `CollateralVault` PDA, so deposits are signed by the trader
+// and withdrawals by the vault's seeds. Zero amounts are skipped rather than sent as no-op CPIs.
+//
+// Collateral can be a legacy SPL mint or a Token-2022 one. Token-2022 transfer fees are paid by
+// whoever sends: a deposit is grossed up so the vault receives the full amount, and a withdrawal
+// reaches the recipient net of the fee. `CollateralVault.balance` only ever moves by what the
+// vault's token account actually gained or lost, so it can't drift from the token balance.
+
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{transfer_fee::TransferFeeConfig, BaseStateWithExtensions, ExtensionType, StateWithExtensions},
+};
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+
+use crate::{CollateralVault, ErrorCode};
+
+/// Token-2022 mint extensions a collateral mint may carry. The rest can move, freeze or gate
+/// the vault's tokens outside this program (permanent delegate, transfer hook, pausable, ...).
+pub const SUPPORTED_MINT_EXTENSIONS: [ExtensionType; 4] = [
+    ExtensionType::TransferFeeConfig,
+    ExtensionType::MintCloseAuthority,
+    ExtensionType::MetadataPointer,
+    ExtensionType::TokenMetadata,
+];
+
+/// Rejects collateral mints with extensions outside `SUPPORTED_MINT_EXTENSIONS`.
+pub fn check_mint(mint: &InterfaceAccount<Mint>) -> Result<()> {
+    let info = mint.to_account_info();
+    if *info.owner != spl_token_2022::ID {
+        return Ok(());
+    }
+    let data = info.try_borrow_data()?;
+    let state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+    for extension in state.get_extension_types()? {
+        require!(SUPPORTED_MINT_EXTENSIONS.contains(&extension), ErrorCode::UnsupportedMint);
+    }
+    Ok(())
+}
+
+/// The fee that must be sent on top of `amount` for `amount` to arrive, under the current
+/// epoch's transfer fee. Zero for legacy mints and Token-2022 mints without the extension.
+pub fn inverse_transfer_fee(mint: &InterfaceAccount<Mint>, amount: u64) -> Result<u64> {
+    let info = mint.to_account_info();
+    if *info.owner != spl_token_2022::ID {
+        return Ok(0);
+    }
+    let data = info.try_borrow_data()?;
+    let state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+    let Ok(config) = state.get_extension::<TransferFeeConfig>() else {
+        return Ok(0);
+    };
+    let fee = config.calculate_inverse_epoch_fee(Clock::get()?.epoch, amount).ok_or(ErrorCode::MathOverflow)?;
+    Ok(fee)
+}
+
+/// Moves `amount`, plus any transfer fee, from `from`, signed by `authority`, into the vault,
+/// and credits the vault with what arrived.
+pub fn deposit<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    mint: &InterfaceAccount<'info, Mint>,
+    from: &InterfaceAccount<'info, TokenAccount>,
+    vault: &mut Account<'info, CollateralVault>,
+    vault_tokens: &mut InterfaceAccount<'info, TokenAccount>,
+    authority: &Signer<'info>,
+    amount: u64,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    let gross = amount.checked_add(inverse_transfer_fee(mint, amount)?).ok_or(ErrorCode::MathOverflow)?;
+    let accounts = TransferChecked {
+        from: from.to_account_info(),
+        mint: mint.to_account_info(),
+        to: vault_tokens.to_account_info(),
+        authority: authority.to_account_info(),
+    };
+    let before = vault_tokens.amount;
+    token_interface::transfer_checked(CpiContext::new(token_program.to_account_info(), accounts), gross, mint.decimals)?;
+    vault_tokens.reload()?;
+    let received = vault_tokens.amount.checked_sub(before).ok_or(ErrorCode::MathOverflow)?;
+    require!(received >= amount, ErrorCode::CollateralShortfall);
+    vault.balance = vault.balance.checked_add(received).ok_or(ErrorCode::MathOverflow)?;
+    Ok(())
+}
+
+/// Moves `amount` out of the vault into `to`, signed by the vault PDA. Any transfer fee comes
+/// out of what `to` receives.
+pub fn withdraw<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    mint: &InterfaceAccount<'info, Mint>,
+    vault: &mut Account<'info, CollateralVault>,
+    vault_tokens: &mut InterfaceAccount<'info, TokenAccount>,
+    to: &InterfaceAccount<'info, TokenAccount>,
+    amount: u64,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    vault.balance = vault.balance.checked_sub(amount).ok_or(ErrorCode::InsufficientVaultBalance)?;
+    let (market, bump) = (vault.market, vault.bump);
+    let seeds: &[&[u8]] = &[b"collateral", market.as_ref(), &[bump]];
+    let accounts = TransferChecked {
+        from: vault_tokens.to_account_info(),
+        mint: mint.to_account_info(),
+        to: to.to_account_info(),
+        authority: vault.to_account_info(),
+    };
+    let before = vault_tokens.amount;
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(token_program.to_account_info(), accounts, &[seeds]),
+        amount,
+        mint.decimals,
+    )?;
+    vault_tokens.reload()?;
+    require!(before.checked_sub(vault_tokens.amount) == Some(amount), ErrorCode::CollateralShortfall);
+    Ok(())
+}
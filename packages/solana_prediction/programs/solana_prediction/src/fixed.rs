@@ -0,0 +1,274 @@
+// Deterministic Q64.64 fixed-point arithmetic.
+// NOTE: Everything here is integer-only so results are bit-identical on every validator,
+// independent of how BPF soft-float rounds. Products/quotients truncate toward zero.
+
+use std::cmp::Ordering;
+
+const FRAC_BITS: u32 = 64;
+
+/// Signed Q64.64 number stored in an `i128` (64 integer bits incl. sign, 64 fractional bits).
+/// Range is roughly ±9.2e18 with a resolution of 2^-64 (~5.4e-20).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Fixed(i128);
+
+impl Fixed {
+    pub const ZERO: Fixed = Fixed(0);
+    pub const ONE: Fixed = Fixed(1 << FRAC_BITS);
+    pub const TWO: Fixed = Fixed(2 << FRAC_BITS);
+    pub const HALF: Fixed = Fixed(1 << (FRAC_BITS - 1));
+    pub const MAX: Fixed = Fixed(i128::MAX);
+    pub const MIN: Fixed = Fixed(i128::MIN);
+    /// Smallest positive value (2^-64).
+    pub const EPSILON: Fixed = Fixed(1);
+    pub const LN_2: Fixed = Fixed(12_786_308_645_202_655_660);
+    pub const PI: Fixed = Fixed(57_952_155_664_616_982_739);
+    pub const SQRT_2: Fixed = Fixed(26_087_635_650_665_564_425);
+    pub const SQRT_2PI: Fixed = Fixed(46_239_130_270_042_206_923);
+    pub const FRAC_1_SQRT_2PI: Fixed = Fixed(7_359_186_146_747_302_452);
+    pub const FRAC_1_SQRT_PI: Fixed = Fixed(10_407_460_856_758_233_229);
+
+    pub const fn from_raw(raw: i128) -> Fixed { Fixed(raw) }
+
+    pub const fn raw(self) -> i128 { self.0 }
+
+    pub const fn from_int(v: i64) -> Fixed { Fixed((v as i128) << FRAC_BITS) }
+
+    /// Exact `num / den` (truncated toward zero). `None` on zero denominator or overflow.
+    pub fn from_ratio(num: i128, den: i128) -> Option<Fixed> {
+        if den == 0 { return None; }
+        let neg = (num < 0) != (den < 0);
+        let q = div_wide(num.unsigned_abs(), den.unsigned_abs())?;
+        with_sign(q, neg)
+    }
+
+    /// Interprets `value` as a decimal with `decimals` fractional digits (e.g. 12345, 2 -> 123.45).
+    pub fn from_scaled(value: i64, decimals: u8) -> Option<Fixed> {
+        Fixed::from_ratio(value as i128, pow10(decimals)?)
+    }
+
+    /// Converts back to a decimal with `decimals` fractional digits, rounding toward -inf.
+    pub fn to_scaled_floor(self, decimals: u8) -> Option<i64> {
+        Fixed(self.0.checked_mul(pow10(decimals)?)?).floor_to_i64()
+    }
+
+    /// Converts back to a decimal with `decimals` fractional digits, rounding half away from zero.
+    pub fn to_scaled_round(self, decimals: u8) -> Option<i64> {
+        let scaled = Fixed(self.0.checked_mul(pow10(decimals)?)?);
+        let half = if scaled.is_negative() { Fixed::HALF.checked_neg()? } else { Fixed::HALF };
+        scaled.checked_add(half)?.conservative_to_i64()
+    }
+
+    /// Converts back to a decimal with `decimals` fractional digits, rounding toward +inf.
+    pub fn to_scaled_ceil(self, decimals: u8) -> Option<i64> {
+        Fixed(self.0.checked_mul(pow10(decimals)?)?).ceil_to_i64()
+    }
+
+    /// Lossy conversion for off-chain tooling and tests. Never feed the result back on-chain.
+    pub fn to_f64(self) -> f64 { self.0 as f64 / (1u128 << FRAC_BITS) as f64 }
+
+    /// Lossy conversion for off-chain tooling and tests.
+    pub fn from_f64(v: f64) -> Fixed { Fixed((v * (1u128 << FRAC_BITS) as f64) as i128) }
+
+    pub fn is_negative(self) -> bool { self.0 < 0 }
+
+    pub fn is_positive(self) -> bool { self.0 > 0 }
+
+    pub fn abs(self) -> Fixed { Fixed(self.0.abs()) }
+
+    pub fn min(self, other: Fixed) -> Fixed { if self <= other { self } else { other } }
+
+    pub fn max(self, other: Fixed) -> Fixed { if self >= other { self } else { other } }
+
+    pub fn floor(self) -> Fixed { Fixed(self.0 & !((1i128 << FRAC_BITS) - 1)) }
+
+    pub fn ceil(self) -> Fixed {
+        let f = self.floor();
+        if f == self { f } else { Fixed(f.0 + (1i128 << FRAC_BITS)) }
+    }
+
+    pub fn floor_to_i64(self) -> Option<i64> { i64::try_from(self.0 >> FRAC_BITS).ok() }
+
+    pub fn ceil_to_i64(self) -> Option<i64> { i64::try_from(self.ceil().0 >> FRAC_BITS).ok() }
+
+    /// Rounds against the trader: floors positive values and ceilings negative values.
+    pub fn conservative_to_i64(self) -> Option<i64> {
+        if self.0 >= 0 { self.floor_to_i64() } else { self.ceil_to_i64() }
+    }
+
+    pub fn checked_add(self, rhs: Fixed) -> Option<Fixed> { self.0.checked_add(rhs.0).map(Fixed) }
+
+    pub fn checked_sub(self, rhs: Fixed) -> Option<Fixed> { self.0.checked_sub(rhs.0).map(Fixed) }
+
+    pub fn checked_neg(self) -> Option<Fixed> { self.0.checked_neg().map(Fixed) }
+
+    pub fn checked_mul(self, rhs: Fixed) -> Option<Fixed> {
+        let neg = (self.0 < 0) != (rhs.0 < 0);
+        let (hi, lo) = mul_wide(self.0.unsigned_abs(), rhs.0.unsigned_abs());
+        if hi >> FRAC_BITS != 0 { return None; }
+        with_sign((hi << FRAC_BITS) | (lo >> FRAC_BITS), neg)
+    }
+
+    pub fn checked_div(self, rhs: Fixed) -> Option<Fixed> {
+        if rhs.0 == 0 { return None; }
+        let neg = (self.0 < 0) != (rhs.0 < 0);
+        with_sign(div_wide(self.0.unsigned_abs(), rhs.0.unsigned_abs())?, neg)
+    }
+
+    /// Multiplies by a plain integer without a wide product.
+    pub fn checked_mul_int(self, rhs: i64) -> Option<Fixed> { self.0.checked_mul(rhs as i128).map(Fixed) }
+
+    /// Divides by a plain integer; much cheaper than `checked_div` on BPF.
+    pub fn checked_div_int(self, rhs: i64) -> Option<Fixed> { self.0.checked_div(rhs as i128).map(Fixed) }
+
+    /// Integer power by repeated squaring.
+    pub fn checked_powi(self, mut n: u32) -> Option<Fixed> {
+        let mut base = self;
+        let mut acc = Fixed::ONE;
+        while n > 0 {
+            if n & 1 == 1 { acc = acc.checked_mul(base)?; }
+            n >>= 1;
+            if n > 0 { base = base.checked_mul(base)?; }
+        }
+        Some(acc)
+    }
+
+    /// Square root via integer Newton iteration from above. `None` for negative inputs.
+    pub fn sqrt(self) -> Option<Fixed> {
+        match self.0.cmp(&0) {
+            Ordering::Less => return None,
+            Ordering::Equal => return Some(Fixed::ZERO),
+            Ordering::Greater => {}
+        }
+        // sqrt(raw / 2^64) * 2^64 = sqrt(raw * 2^64); start at a power of two above it.
+        let bits = 128 - self.0.leading_zeros();
+        let mut y = Fixed(1i128 << ((bits + FRAC_BITS) / 2 + 1).min(126));
+        loop {
+            let next = Fixed((y.0 + self.checked_div(y)?.0) >> 1);
+            if next >= y { return Some(y); }
+            y = next;
+        }
+    }
+
+    /// e^x. Underflows to zero below about -44.3 and returns `None` above about 43.6.
+    pub fn exp(self) -> Option<Fixed> {
+        if self < Fixed::from_int(-45) { return Some(Fixed::ZERO); }
+        if self > Fixed::from_int(44) { return None; }
+        // x = (32k + j) * ln2/32 + r with |r| <= ln2/64, then e^x = 2^k * 2^(j/32) * e^r
+        let n = self.checked_mul(INV_LN2_32)?.checked_add(Fixed::HALF)?.floor_to_i64()?;
+        let r = self.checked_sub(LN2_32.checked_mul_int(n)?)?;
+        let (k, j) = (n.div_euclid(32), n.rem_euclid(32) as usize);
+        // Horner over 1/m! for m = 8..0; r^9/9! is below 2^-64 for |r| <= ln2/64
+        let mut poly = INV_FACTORIALS[8];
+        for c in INV_FACTORIALS[..8].iter().rev() {
+            poly = poly.checked_mul(r)?.checked_add(*c)?;
+        }
+        let m = poly.checked_mul(Fixed(EXP2_32THS[j]))?;
+        if k >= 0 {
+            if k >= 64 { return None; }
+            m.0.checked_mul(1i128 << k).map(Fixed)
+        } else if k <= -127 {
+            Some(Fixed::ZERO)
+        } else {
+            Some(Fixed(m.0 >> (-k)))
+        }
+    }
+
+    /// Natural logarithm. `None` for non-positive inputs.
+    pub fn ln(self) -> Option<Fixed> {
+        if self.0 <= 0 { return None; }
+        // x = 2^k * m with m in [1, 2)
+        let k = (127 - self.0.leading_zeros()) as i64 - FRAC_BITS as i64;
+        let m = if k >= 0 { Fixed(self.0 >> k) } else { Fixed(self.0 << (-k)) };
+        // ln(m) = 2 * atanh(s), s = (m - 1) / (m + 1) in [0, 1/3]
+        let s = m.checked_sub(Fixed::ONE)?.checked_div(m.checked_add(Fixed::ONE)?)?;
+        let s2 = s.checked_mul(s)?;
+        let mut pow = s;
+        let mut sum = s;
+        for n in 1..=40 {
+            pow = pow.checked_mul(s2)?;
+            let term = pow.checked_div_int(2 * n + 1)?;
+            if term == Fixed::ZERO { break; }
+            sum = sum.checked_add(term)?;
+        }
+        Fixed::LN_2.checked_mul_int(k)?.checked_add(sum.checked_mul_int(2)?)
+    }
+}
+
+const INV_LN2_32: Fixed = Fixed(851_616_838_262_036_639_472);
+const LN2_32: Fixed = Fixed(399_572_145_162_582_989);
+
+/// 1/m! for m = 0..=8.
+const INV_FACTORIALS: [Fixed; 9] = [
+    Fixed(18_446_744_073_709_551_616),
+    Fixed(18_446_744_073_709_551_616),
+    Fixed(9_223_372_036_854_775_808),
+    Fixed(3_074_457_345_618_258_603),
+    Fixed(768_614_336_404_564_651),
+    Fixed(153_722_867_280_912_930),
+    Fixed(25_620_477_880_152_155),
+    Fixed(3_660_068_268_593_165),
+    Fixed(457_508_533_574_146),
+];
+
+/// 2^(j/32) for j = 0..32, raw Q64.64.
+const EXP2_32THS: [i128; 32] = [
+    18_446_744_073_709_551_616, 18_850_675_170_876_015_534, 19_263_451_207_323_153_962, 19_685_265_862_001_232_463,
+    20_116_317_054_877_281_742, 20_556_807_039_801_272_192, 21_006_942_499_405_792_876, 21_466_934_642_083_762_882,
+    21_936_999_301_089_678_047, 22_417_357_035_810_892_401, 22_908_233_235_256_451_931, 23_409_858_223_812_038_705,
+    23_922_467_369_310_646_740, 24_446_301_193_469_697_521, 24_981_605_484_746_413_453, 25_528_631_413_664_402_207,
+    26_087_635_650_665_564_425, 26_658_880_486_542_622_162, 27_242_633_955_508_776_317, 27_839_169_960_962_238_637,
+    28_448_768_404_004_648_378, 29_071_715_314_773_675_845, 29_708_302_986_651_435_464, 30_358_830_113_411_680_436,
+    31_023_601_929_370_129_896, 31_702_930_352_603_688_618, 32_397_134_131_305_759_266, 33_106_538_993_346_318_672,
+    33_831_477_799_106_933_342, 34_572_290_697_662_426_007, 35_329_325_286_382_475_353, 36_102_936_774_028_035_701,
+];
+
+fn pow10(decimals: u8) -> Option<i128> { 10i128.checked_pow(decimals as u32) }
+
+fn with_sign(mag: u128, neg: bool) -> Option<Fixed> {
+    if neg {
+        if mag > i128::MAX as u128 + 1 { return None; }
+        Some(Fixed((mag as i128).wrapping_neg()))
+    } else {
+        i128::try_from(mag).ok().map(Fixed)
+    }
+}
+
+/// Full 256-bit product of two u128 values as (hi, lo).
+fn mul_wide(a: u128, b: u128) -> (u128, u128) {
+    const MASK: u128 = u64::MAX as u128;
+    let (a1, a0) = (a >> 64, a & MASK);
+    let (b1, b0) = (b >> 64, b & MASK);
+    let p00 = a0 * b0;
+    let p01 = a0 * b1;
+    let p10 = a1 * b0;
+    let p11 = a1 * b1;
+    let mid = (p00 >> 64) + (p01 & MASK) + (p10 & MASK);
+    let lo = (p00 & MASK) | (mid << 64);
+    let hi = p11 + (p01 >> 64) + (p10 >> 64) + (mid >> 64);
+    (hi, lo)
+}
+
+/// floor(a * 2^64 / b) for b != 0, or `None` if the quotient does not fit in 128 bits.
+fn div_wide(a: u128, b: u128) -> Option<u128> {
+    let int = a / b;
+    if int >> FRAC_BITS != 0 { return None; }
+    let mut rem = a % b;
+    let frac = if rem >> FRAC_BITS == 0 {
+        (rem << FRAC_BITS) / b
+    } else {
+        // Schoolbook long division for the fractional bits. rem < b, so when doubling carries
+        // out of 128 bits the true value still exceeds b and the wrapping subtraction is exact.
+        let mut q: u128 = 0;
+        for _ in 0..FRAC_BITS {
+            let carry = rem >> 127;
+            rem <<= 1;
+            q <<= 1;
+            if carry == 1 || rem >= b {
+                rem = rem.wrapping_sub(b);
+                q |= 1;
+            }
+        }
+        q
+    };
+    Some((int << FRAC_BITS) | frac)
+}
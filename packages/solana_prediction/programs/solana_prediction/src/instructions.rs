@@ -0,0 +1,1636 @@
+use super::*;
+
+#[program]
+pub mod solana_prediction {
+    use super::*;
+
+    // Initialize the platform's registry, a single PDA, controlled by an authority
+    pub fn init_registry(ctx: Context<InitRegistry>) -> Result<()> {
+        let reg = &mut ctx.accounts.registry;
+        reg.authority = ctx.accounts.authority.key();
+        reg.bump = ctx.bumps.registry;
+        Ok(())
+    }
+
+    // Add (or update) a strategy mapping: key -> target program id
+    pub fn upsert_strategy(ctx: Context<UpsertStrategy>, strategy_key: [u8; 32], target_program: Pubkey) -> Result<()> {
+        require_keys_eq!(ctx.accounts.registry.authority, ctx.accounts.authority.key(), ErrorCode::Unauthorized);
+        let strat = &mut ctx.accounts.strategy;
+        strat.registry = ctx.accounts.registry.key();
+        strat.strategy_key = strategy_key;
+        strat.target_program = target_program;
+        strat.bump = ctx.bumps.strategy;
+        Ok(())
+    }
+
+    // Minimal gated execution stub. For a safe template, we only emit an event after checks.
+    // Extending to CPI requires passing account metas + instruction data and doing invoke_signed.
+    pub fn execute_strategy(
+        ctx: Context<ExecuteStrategy>,
+        strategy_key: [u8; 32],
+        approved: bool,
+        _ix_data: Vec<u8>, // placeholder for future CPI payload
+    ) -> Result<()> {
+        require!(approved, ErrorCode::NotApproved);
+        let strat = &ctx.accounts.strategy;
+        require!(strat.strategy_key == strategy_key, ErrorCode::StrategyKeyMismatch);
+        // Safety: For the starter template, we do not perform CPI. We only emit an event.
+        emit!(StrategyExecutionRequested {
+            user: ctx.accounts.user.key(),
+            strategy: strategy_key,
+            target_program: strat.target_program,
+            ts: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    // ========== Distributional Markets (Scaffold) ==========
+
+    pub fn create_market(
+        ctx: Context<CreateMarket>,
+        params: MarketParams,
+    ) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        market.version = MARKET_VERSION;
+        market.authority = ctx.accounts.authority.key();
+        market.slug = params.slug;
+        market.market_type = params.market_type;
+        market.decimals = params.decimals;
+        market.outcome_min = params.outcome_min;
+        market.outcome_max = params.outcome_max;
+        market.unit = params.unit;
+        market.dist_type = params.dist_type;
+        market.mu = params.mu;
+        market.sigma = params.sigma;
+        market.sigma_min = params.sigma_min;
+        market.step = params.step;
+        market.resolution_time = params.resolution_time;
+        market.oracle_config = params.oracle_config;
+        market.fee_bps_platform = params.fee_bps_platform;
+        market.fee_bps_creator = params.fee_bps_creator;
+        market.l2_norm = params.l2_norm;
+        market.dist_shape = params.dist_shape;
+        market.scoring_rule = params.scoring_rule;
+        market.strike = params.strike;
+        market.fee_bps_lp = params.fee_bps_lp;
+        market.validate()?;
+        market.resolved_outcome = 0;
+        market.status = MarketStatus::Active;
+        market.bump = ctx.bumps.market;
+
+        emit!(MarketCreated {
+            market: market.key(),
+            authority: market.authority,
+            slug: market.slug,
+            outcome_min: market.outcome_min,
+            outcome_max: market.outcome_max,
+            unit: market.unit,
+            ts: Clock::get()?.unix_timestamp,
+        });
+        // Lightweight indexer-friendly log
+        msg!(
+            "IDX:MarketCreated|market={}|ts={}",
+            market.key(),
+            Clock::get()?.unix_timestamp
+        );
+        Ok(())
+    }
+
+    pub fn initialize_market_infrastructure(
+        ctx: Context<InitializeMarketInfrastructure>,
+    ) -> Result<()> {
+        custody::check_mint(&ctx.accounts.collateral_mint)?;
+        require!(ctx.accounts.market.decimals <= ctx.accounts.collateral_mint.decimals, ErrorCode::InvalidDecimals);
+        let subsidy = ctx.accounts.market.subsidy()?;
+        let market = &mut ctx.accounts.market;
+        market.distribution_balance = subsidy;
+        market.liquidity_pool = ctx.accounts.liquidity_pool.key();
+        market.order_book = ctx.accounts.order_book.key();
+        market.collateral_vault = ctx.accounts.collateral_vault.key();
+
+        let lp = &mut ctx.accounts.liquidity_pool;
+        lp.market = market.key();
+        lp.vault = ctx.accounts.collateral_vault.key();
+        lp.lp_mint = ctx.accounts.lp_mint.key();
+        lp.total_liquidity = 0;
+        lp.fees_accrued = 0;
+        lp.bump = ctx.bumps.liquidity_pool;
+
+        let ob = &mut ctx.accounts.order_book;
+        ob.market = market.key();
+        ob.best_bid_bps = 0;
+        ob.best_ask_bps = 0;
+        ob.event_counter = 0;
+        ob.bump = ctx.bumps.order_book;
+
+        let cv = &mut ctx.accounts.collateral_vault;
+        cv.market = market.key();
+        cv.mint = ctx.accounts.collateral_mint.key();
+        cv.token_account = ctx.accounts.vault_tokens.key();
+        cv.balance = 0;
+        cv.bump = ctx.bumps.collateral_vault;
+
+        let fees = &mut ctx.accounts.market_fees;
+        fees.market = market.key();
+        fees.platform = ctx.accounts.registry.authority;
+        fees.platform_fees = 0;
+        fees.creator_fees = 0;
+        fees.lp_fees = 0;
+        fees.bump = ctx.bumps.market_fees;
+
+        custody::deposit(
+            &ctx.accounts.token_program,
+            &ctx.accounts.collateral_mint,
+            &ctx.accounts.authority_tokens,
+            &mut ctx.accounts.collateral_vault,
+            &mut ctx.accounts.vault_tokens,
+            &ctx.accounts.authority,
+            subsidy,
+        )?;
+
+        emit!(MarketInfrastructureInitialized {
+            market: market.key(),
+            subsidy,
+            ts: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Creates the bid and ask slabs that hold resting limit orders.
+    pub fn init_order_book(ctx: Context<InitOrderBook>) -> Result<()> {
+        let market = ctx.accounts.market.key();
+        ctx.accounts.bids.load_init()?.init(market, Side::Buy, ctx.bumps.bids);
+        ctx.accounts.asks.load_init()?.init(market, Side::Sell, ctx.bumps.asks);
+        emit!(OrderBookInitialized { market, ts: Clock::get()?.unix_timestamp });
+        Ok(())
+    }
+
+    /// Limit order. Unless it is post-only it first matches the opposite side of the book up
+    /// to `price_bps`, then GTC, GTT and post-only orders rest what is left at the back of
+    /// their price level's FIFO queue, locking its collateral; IOC drops it and FOK fails
+    /// unless it fills in full. Only GTT uses `expiry`. `order_id` comes from
+    /// `OrderBook.event_counter`, so it is unique within the market and never reused.
+    ///
+    /// Fills settle against their makers' `OpenOrders` (see `OpenOrders`), so every maker the
+    /// order may meet has to be passed as a remaining account; the signer's own always is.
+    /// Expired orders met while matching are pruned if their owner's `OpenOrders` is passed
+    /// and stepped over otherwise.
+    pub fn place_limit_order<'info>(
+        ctx: Context<'_, '_, 'info, 'info, PlaceLimitOrder<'info>>,
+        side: Side,
+        price_bps: u64,
+        size: u64,
+        expiry: i64,
+        client_order_id: u64,
+        time_in_force: TimeInForce,
+    ) -> Result<()> {
+        validation::price(price_bps)?;
+        validation::size(size)?;
+        let now = Clock::get()?.unix_timestamp;
+        validation::market_open(&ctx.accounts.market, now)?;
+        let expiry_ts = if time_in_force == TimeInForce::Gtt {
+            require!(expiry > now, ErrorCode::InvalidExpiry);
+            expiry
+        } else {
+            0
+        };
+        let market = ctx.accounts.market.key();
+        let owner = ctx.accounts.owner.key();
+        let ob = &mut ctx.accounts.order_book;
+        let order_id = ob.event_counter;
+        ob.event_counter = ob.event_counter.checked_add(1).ok_or(ErrorCode::MathOverflow)?;
+
+        let oo = &mut ctx.accounts.open_orders;
+        if oo.owner == Pubkey::default() {
+            oo.market = market;
+            oo.owner = owner;
+            oo.bump = ctx.bumps.open_orders;
+        }
+        let mut makers = remaining_open_orders(market, ctx.remaining_accounts, Some(oo.key()))?;
+
+        let (opposite, resting) = if side == Side::Buy {
+            (&ctx.accounts.asks, &ctx.accounts.bids)
+        } else {
+            (&ctx.accounts.bids, &ctx.accounts.asks)
+        };
+        let take = {
+            let mut book = opposite.load_mut()?;
+            let prunable = |o: &OrderNode| o.owner == owner || makers.iter().any(|m| m.owner == o.owner);
+            let take = match time_in_force {
+                TimeInForce::PostOnly => {
+                    require!(!book.crosses(price_bps), ErrorCode::OrderWouldCross);
+                    Take::default()
+                }
+                TimeInForce::Fok => {
+                    require!(book.depth(price_bps, now) >= size, ErrorCode::FillOrKillUnfilled);
+                    book.take(size, price_bps, now, prunable)
+                }
+                _ => book.take(size, price_bps, now, prunable),
+            };
+            ob.set_best(side.opposite(), book.best_price());
+            take
+        };
+        for order in &take.expired {
+            let oo = if order.owner == owner {
+                &mut ctx.accounts.open_orders
+            } else {
+                makers.iter_mut().find(|m| m.owner == order.owner).ok_or(ErrorCode::InvalidOpenOrders)?
+            };
+            refund_expired(oo, market, side.opposite(), order, now)?;
+        }
+        let (filled, notional) = emit_fills(market, owner, side, &take.fills, now);
+        if filled > 0 {
+            let (owed, lp) = settle_fills(&ctx.accounts.market, &mut ctx.accounts.open_orders, &mut makers, side, &take.fills)?;
+            let (platform, creator) = ctx.accounts.market.trade_fees(notional, u64::MAX)?;
+            custody::deposit(
+                &ctx.accounts.token_program,
+                &ctx.accounts.mint,
+                &ctx.accounts.owner_tokens,
+                &mut ctx.accounts.collateral_vault,
+                &mut ctx.accounts.vault_tokens,
+                &ctx.accounts.owner,
+                owed + platform + creator + lp,
+            )?;
+            let fees = &mut ctx.accounts.market_fees;
+            fees.accrue(platform, creator, lp)?;
+            emit_fees(market, owner, (platform, creator, lp), now);
+        }
+
+        let rest = size - filled;
+        if rest > 0 && time_in_force != TimeInForce::Ioc {
+            let locked = ctx.accounts.open_orders.lock(side, price_bps, rest)?;
+            custody::deposit(
+                &ctx.accounts.token_program,
+                &ctx.accounts.mint,
+                &ctx.accounts.owner_tokens,
+                &mut ctx.accounts.collateral_vault,
+                &mut ctx.accounts.vault_tokens,
+                &ctx.accounts.owner,
+                locked,
+            )?;
+            let order = OrderNode {
+                owner,
+                order_id,
+                client_order_id,
+                price_bps,
+                size: rest,
+                placed_ts: now,
+                expiry_ts,
+                next: BOOK_NIL,
+                _padding: [0; 6],
+            };
+            let mut book = resting.load_mut()?;
+            book.insert(order)?;
+            ob.set_best(side, book.best_price());
+
+            emit!(OrderPlaced { order_id, market, owner, side, price_bps, size: rest, ts: now });
+            msg!(
+                "IDX:OrderPlaced|market={}|order_id={}|side={}|price_bps={}|size={}|ts={}",
+                market,
+                order_id,
+                side as u8,
+                price_bps,
+                rest,
+                now
+            );
+        }
+        for maker in &makers {
+            maker.exit(&crate::ID)?;
+        }
+        Ok(())
+    }
+
+    /// Routed taker order. Fills against the opposite side of the book, best price first and
+    /// oldest first within a price, while resting orders are at least as good as the pm-AMM's
+    /// marginal price, then takes the remainder from `PmAmmPool` if one is passed and still
+    /// quoting. The AMM leg settles like `trade_pmamm`, against the taker's `PmAmmPosition`.
+    /// `limit_price_bps` is the most a buyer pays (the least a seller accepts) on any book fill
+    /// and on the AMM leg's average, before platform and creator fees; whatever can't be filled
+    /// within it is dropped. Returns the split and the blended average price.
+    ///
+    /// Book fills settle like in `place_limit_order`, crediting the taker's shares to its own
+    /// `OpenOrders`, and expired orders are pruned the same way, for the owners whose
+    /// `OpenOrders` are passed as remaining accounts.
+    pub fn execute_market_order<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ExecuteMarketOrder<'info>>,
+        side: Side,
+        size: u64,
+        limit_price_bps: u64,
+    ) -> Result<MarketOrderFill> {
+        validation::price(limit_price_bps)?;
+        validation::size(size)?;
+        let now = Clock::get()?.unix_timestamp;
+        validation::market_open(&ctx.accounts.market, now)?;
+        let amm = ctx.accounts.pmamm_pool.as_mut().filter(|pool| pool.quoting(now));
+        // The book only keeps priority down to the AMM's marginal price
+        let book_limit = match &amm {
+            Some(pool) if side == Side::Buy => limit_price_bps.min(pool.marginal_price_bps(side)?),
+            Some(pool) => limit_price_bps.max(pool.marginal_price_bps(side)?),
+            None => limit_price_bps,
+        };
+
+        let market = ctx.accounts.market.key();
+        let taker = ctx.accounts.taker.key();
+        let oo = &mut ctx.accounts.open_orders;
+        if oo.owner == Pubkey::default() {
+            oo.market = market;
+            oo.owner = taker;
+            oo.bump = ctx.bumps.open_orders;
+        }
+        let mut makers = remaining_open_orders(market, ctx.remaining_accounts, Some(oo.key()))?;
+        let take = {
+            let mut book = if side == Side::Buy { ctx.accounts.asks.load_mut()? } else { ctx.accounts.bids.load_mut()? };
+            let take = book.take(size, book_limit, now, |o| o.owner == taker || makers.iter().any(|m| m.owner == o.owner));
+            ctx.accounts.order_book.set_best(side.opposite(), book.best_price());
+            take
+        };
+        for order in &take.expired {
+            let oo = if order.owner == taker {
+                &mut ctx.accounts.open_orders
+            } else {
+                makers.iter_mut().find(|m| m.owner == order.owner).ok_or(ErrorCode::InvalidOpenOrders)?
+            };
+            refund_expired(oo, market, side.opposite(), order, now)?;
+        }
+        let (owed, lp) = settle_fills(&ctx.accounts.market, &mut ctx.accounts.open_orders, &mut makers, side, &take.fills)?;
+        for maker in &makers {
+            maker.exit(&crate::ID)?;
+        }
+        // Sum of price_bps * size over every fill, AMM leg included
+        let (book_size, mut notional) = emit_fills(market, taker, side, &take.fills, now);
+        // (platform, creator, LP) fees over both legs
+        let mut charged = (0u64, 0u64, 0u64);
+        if book_size > 0 {
+            let (platform, creator) = ctx.accounts.market.trade_fees(notional, u64::MAX)?;
+            custody::deposit(
+                &ctx.accounts.token_program,
+                &ctx.accounts.mint,
+                &ctx.accounts.taker_tokens,
+                &mut ctx.accounts.collateral_vault,
+                &mut ctx.accounts.vault_tokens,
+                &ctx.accounts.taker,
+                owed + platform + creator + lp,
+            )?;
+            ctx.accounts.market_fees.accrue(platform, creator, lp)?;
+            charged = (platform, creator, lp);
+        }
+
+        let mut amm_size = 0u64;
+        if let Some(pool) = amm {
+            if book_size < size {
+                let before = (ctx.accounts.collateral_vault.balance, pmamm_owed(pool, &ctx.accounts.market_fees));
+                let lp_fees = pool.fees;
+                let (traded, total) = pool.swap(side, size - book_size, now)?;
+                let quote_bps = total as u128 * 10_000;
+                let limit = limit_price_bps as u128 * traded as u128;
+                require!(if side == Side::Buy { quote_bps <= limit } else { quote_bps >= limit }, ErrorCode::SlippageExceeded);
+                let quote = PmAmmQuote::new(side, traded, total, pool.fees - lp_fees).charge(side, &ctx.accounts.market)?;
+                let position = ctx.accounts.pmamm_position.as_mut().ok_or(ErrorCode::MissingMarketState)?;
+                if position.owner == Pubkey::default() {
+                    position.market = market;
+                    position.owner = taker;
+                    position.bump = ctx.bumps.pmamm_position.ok_or(ErrorCode::MissingMarketState)?;
+                }
+                settle_pmamm(
+                    side,
+                    traded,
+                    quote.collateral(side),
+                    position,
+                    &ctx.accounts.token_program,
+                    &ctx.accounts.mint,
+                    &ctx.accounts.taker_tokens,
+                    &mut ctx.accounts.collateral_vault,
+                    &mut ctx.accounts.vault_tokens,
+                    &ctx.accounts.taker,
+                )?;
+                let fees = &mut ctx.accounts.market_fees;
+                fees.accrue(quote.platform_fee, quote.creator_fee, quote.lp_fee)?;
+                reconcile_pmamm(pool, fees, &ctx.accounts.collateral_vault, before)?;
+                charged = (charged.0 + quote.platform_fee, charged.1 + quote.creator_fee, charged.2 + quote.lp_fee);
+                amm_size = traded;
+                notional += quote_bps;
+            }
+        }
+        let filled = book_size + amm_size;
+        require!(filled > 0, ErrorCode::NoLiquidityWithinLimit);
+        emit_fees(market, taker, charged, now);
+        // Blended average price, rounded against the taker
+        let avg = if side == Side::Buy { notional.div_ceil(filled as u128) } else { notional / filled as u128 };
+        let price_bps = u64::try_from(avg).map_err(|_| ErrorCode::MathOverflow)?;
+
+        emit!(TradeExecuted { market, taker, side, price_bps, size: filled, book_size, amm_size, ts: now });
+        msg!(
+            "IDX:TradeExecuted|market={}|taker={}|side={}|price_bps={}|size={}|book_size={}|amm_size={}|ts={}",
+            market,
+            taker,
+            side as u8,
+            price_bps,
+            filled,
+            book_size,
+            amm_size,
+            now
+        );
+        Ok(MarketOrderFill { book_size, amm_size, price_bps })
+    }
+
+    /// Takes one of the signer's orders off the book and frees its remaining collateral.
+    pub fn cancel_order(ctx: Context<CancelOrder>, order_id: u64) -> Result<()> {
+        let owner = ctx.accounts.owner.key();
+        let side = {
+            let bids = ctx.accounts.bids.load()?;
+            let asks = ctx.accounts.asks.load()?;
+            let (side, order) = match bids.find(|o| o.order_id == order_id) {
+                Some(order) => (Side::Buy, order),
+                None => (Side::Sell, asks.find(|o| o.order_id == order_id).ok_or(ErrorCode::OrderNotFound)?),
+            };
+            require_keys_eq!(order.owner, owner, ErrorCode::NotOrderOwner);
+            side
+        };
+        cancel_resting(ctx.accounts, side, order_id)
+    }
+
+    /// Cancels the signer's order placed with `client_order_id`.
+    pub fn cancel_order_by_client_id(ctx: Context<CancelOrder>, client_order_id: u64) -> Result<()> {
+        let owner = ctx.accounts.owner.key();
+        let mine = |o: &OrderNode| o.owner == owner && o.client_order_id == client_order_id;
+        let (side, order_id) = {
+            let bids = ctx.accounts.bids.load()?;
+            let asks = ctx.accounts.asks.load()?;
+            match (bids.find(mine), asks.find(mine)) {
+                (Some(order), _) => (Side::Buy, order.order_id),
+                (None, Some(order)) => (Side::Sell, order.order_id),
+                (None, None) => return err!(ErrorCode::OrderNotFound),
+            }
+        };
+        cancel_resting(ctx.accounts, side, order_id)
+    }
+
+    /// Cancels every order the signer has resting on this market's book.
+    pub fn cancel_all_orders(ctx: Context<CancelOrder>) -> Result<()> {
+        let owner = ctx.accounts.owner.key();
+        for side in [Side::Buy, Side::Sell] {
+            loop {
+                let order_id = {
+                    let book = if side == Side::Buy { ctx.accounts.bids.load()? } else { ctx.accounts.asks.load()? };
+                    match book.find(|o| o.owner == owner) {
+                        Some(order) => order.order_id,
+                        None => break,
+                    }
+                };
+                cancel_resting(ctx.accounts, side, order_id)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Pays the owner's free book collateral (released by cancels and expiries, or paid for
+    /// filled asks) out of the vault, with a unit per YES and NO share pair and, once the market
+    /// resolved, per winning share.
+    pub fn settle_funds(ctx: Context<SettleFunds>) -> Result<()> {
+        let market = &ctx.accounts.market;
+        let yes_wins = (market.status == MarketStatus::Resolved).then(|| market.yes_wins());
+        let oo = &mut ctx.accounts.open_orders;
+        let amount = oo.settle()?.checked_add(oo.redeem(yes_wins)?).ok_or(ErrorCode::MathOverflow)?;
+        custody::withdraw(
+            &ctx.accounts.token_program,
+            &ctx.accounts.mint,
+            &mut ctx.accounts.collateral_vault,
+            &mut ctx.accounts.vault_tokens,
+            &ctx.accounts.owner_tokens,
+            amount,
+        )?;
+        let (market, owner) = (ctx.accounts.market.key(), ctx.accounts.owner.key());
+        let now = Clock::get()?.unix_timestamp;
+        emit!(FundsSettled { market, owner, amount, ts: now });
+        msg!("IDX:FundsSettled|market={}|owner={}|amount={}|ts={}", market, owner, amount, now);
+        Ok(())
+    }
+
+    /// Permissionless crank: takes every expired order off both sides of the book for the
+    /// owners whose `OpenOrders` are passed as remaining accounts, and frees their collateral.
+    pub fn prune_expired_orders<'info>(ctx: Context<'_, '_, 'info, 'info, PruneExpiredOrders<'info>>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let market = ctx.accounts.market.key();
+        let mut owners = remaining_open_orders(market, ctx.remaining_accounts, None)?;
+        for side in [Side::Buy, Side::Sell] {
+            let expired = {
+                let mut book = if side == Side::Buy { ctx.accounts.bids.load_mut()? } else { ctx.accounts.asks.load_mut()? };
+                let expired = book.prune_expired(now, |o| owners.iter().any(|oo| oo.owner == o.owner));
+                ctx.accounts.order_book.set_best(side, book.best_price());
+                expired
+            };
+            for order in &expired {
+                let oo = owners.iter_mut().find(|oo| oo.owner == order.owner).ok_or(ErrorCode::InvalidOpenOrders)?;
+                refund_expired(oo, market, side, order, now)?;
+            }
+        }
+        for oo in &owners {
+            oo.exit(&crate::ID)?;
+        }
+        Ok(())
+    }
+
+    /// Moves an `Active` market to `Closed` once its resolution time has passed, so readers see
+    /// that trading is over before the outcome is in. Anyone can call it.
+    pub fn close_market(ctx: Context<CloseMarket>) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        let now = Clock::get()?.unix_timestamp;
+        require!(market.status == MarketStatus::Active, ErrorCode::MarketNotActive);
+        require!(now >= market.resolution_time, ErrorCode::ResolutionTooEarly);
+        market.status = MarketStatus::Closed;
+
+        emit!(MarketClosed { market: market.key(), ts: now });
+        msg!("IDX:MarketClosed|market={}|ts={}", market.key(), now);
+        Ok(())
+    }
+
+    pub fn resolve_market(
+        ctx: Context<ResolveMarket>,
+        outcome_value: i64, // scaled by market.decimals
+        _proof: Vec<u8>,
+    ) -> Result<()> {
+        // Pyth oracle validation per ORACLE_RESOLUTION_FLOW.md:
+        // - Check status == Trading
+        // - staleness: now - publish_time <= STALENESS_LIMIT
+        // - confidence <= MAX_CONFIDENCE
+        // - normalize: price * 10^exponent
+        // For now, accept outcome_value directly (Pyth account parsing to be added)
+        
+        let market = &mut ctx.accounts.market;
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            matches!(market.status, MarketStatus::Active | MarketStatus::Closed),
+            ErrorCode::MarketNotActive
+        );
+        require!(now >= market.resolution_time, ErrorCode::ResolutionTooEarly);
+        require!((market.outcome_min..=market.outcome_max).contains(&outcome_value), ErrorCode::OutcomeOutOfRange);
+        market.status = MarketStatus::Resolved;
+        market.resolved_outcome = outcome_value;
+        
+        emit!(MarketResolved { 
+            market: market.key(), 
+            outcome_value, 
+            ts: now 
+        });
+        Ok(())
+    }
+
+    /// Adds collateral to a market's vault that no position owns, on top of the opening subsidy,
+    /// towards paying scoring-rule winners beyond what the other side posted. It is credited to
+    /// `Market::distribution_balance`.
+    pub fn fund_vault(ctx: Context<FundVault>, amount: u64) -> Result<()> {
+        validation::size(amount)?;
+        custody::deposit(
+            &ctx.accounts.token_program,
+            &ctx.accounts.mint,
+            &ctx.accounts.funder_tokens,
+            &mut ctx.accounts.collateral_vault,
+            &mut ctx.accounts.vault_tokens,
+            &ctx.accounts.funder,
+            amount,
+        )?;
+        let market = &mut ctx.accounts.market;
+        market.distribution_balance = market.distribution_balance.checked_add(amount).ok_or(ErrorCode::MathOverflow)?;
+        let (market, funder) = (ctx.accounts.market.key(), ctx.accounts.funder.key());
+        let now = Clock::get()?.unix_timestamp;
+        emit!(VaultFunded { market, funder, amount, ts: now });
+        msg!("IDX:VaultFunded|market={}|funder={}|amount={}|ts={}", market, funder, amount, now);
+        Ok(())
+    }
+
+    /// Settles a distribution position on a resolved market: collateral plus
+    /// k * (S(to, y) - S(from, y)) under the market's scoring rule, floored at zero.
+    pub fn claim_payout(ctx: Context<ClaimPayout>) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        require!(market.status == MarketStatus::Resolved, ErrorCode::MarketNotResolved);
+        let pos = &mut ctx.accounts.position;
+        require!(!pos.settled, ErrorCode::AlreadySettled);
+
+        let from = market.distribution(pos.from_mu, pos.from_sigma)?;
+        let to = market.distribution(pos.to_mu, pos.to_sigma)?;
+        let (lo, hi) = (market.fixed(market.outcome_min)?, market.fixed(market.outcome_max)?);
+        let y = market.fixed(market.resolved_outcome)?;
+        let k = Fixed::from_int(i64::try_from(pos.l2_norm).map_err(|_| ErrorCode::MathOverflow)?);
+        let delta = scoring::payout_delta(market.scoring_rule, k, &from, &to, y, lo, hi).ok_or(ErrorCode::MathOverflow)?;
+        let amount = Fixed::from_int(i64::try_from(pos.collateral_locked).map_err(|_| ErrorCode::MathOverflow)?)
+            .checked_add(delta)
+            .and_then(|v| v.max(Fixed::ZERO).floor_to_i64())
+            .ok_or(ErrorCode::MathOverflow)? as u64;
+        market.distribution_balance = market.distribution_balance.checked_sub(amount).ok_or(ErrorCode::InsufficientVaultBalance)?;
+        pos.settled = true;
+        custody::withdraw(
+            &ctx.accounts.token_program,
+            &ctx.accounts.mint,
+            &mut ctx.accounts.collateral_vault,
+            &mut ctx.accounts.vault_tokens,
+            &ctx.accounts.owner_tokens,
+            amount,
+        )?;
+
+        let now = Clock::get()?.unix_timestamp;
+        emit!(PayoutClaimed { owner: pos.owner, market: market.key(), amount, ts: now });
+        msg!(
+            "IDX:PayoutClaimed|market={}|owner={}|rule={}|amount={}|ts={}",
+            market.key(),
+            pos.owner,
+            market.scoring_rule,
+            amount,
+            now
+        );
+        Ok(())
+    }
+
+    // ====== Distribution trades ======
+
+    /// Moves the market's distribution from (mu, sigma) to (new_mu, new_sigma) within its family.
+    /// The trader posts the worst-case loss of the L2-normalised move as collateral and receives
+    /// a position paying g(x) - f(x) at resolution.
+    pub fn trade_distribution(
+        ctx: Context<TradeDistribution>,
+        position_id: u64,
+        new_mu: i64,
+        new_sigma: i64,
+        max_collateral: u64,
+    ) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        let now = Clock::get()?.unix_timestamp;
+        validation::market_open(market, now)?;
+        require!(market.market_type == MarketType::Distributional, ErrorCode::WrongMarketType);
+        require!(market.l2_norm > 0, ErrorCode::InvalidMarketParams);
+        require!(new_sigma >= market.sigma_min && new_sigma > 0, ErrorCode::SigmaBelowMin);
+
+        let from = market.distribution(market.mu, market.sigma)?;
+        let to = market.distribution(new_mu, new_sigma)?;
+        let (lo, hi) = (market.fixed(market.outcome_min)?, market.fixed(market.outcome_max)?);
+        let collateral = scoring::collateral_required(market.scoring_rule, market.l2_norm, &from, &to, lo, hi)
+            .ok_or(ErrorCode::MathOverflow)?;
+        require!(collateral <= max_collateral, ErrorCode::SlippageExceeded);
+        let k = Fixed::from_int(i64::try_from(market.l2_norm).map_err(|_| ErrorCode::MathOverflow)?);
+        let l2_distance = scaled_l2_distance_fixed(k, &from, &to, lo, hi)
+            .and_then(Fixed::ceil_to_i64)
+            .ok_or(ErrorCode::MathOverflow)? as u64;
+
+        let pos = &mut ctx.accounts.position;
+        pos.owner = ctx.accounts.trader.key();
+        pos.market = market.key();
+        pos.position_id = position_id;
+        pos.from_mu = market.mu;
+        pos.from_sigma = market.sigma;
+        pos.to_mu = new_mu;
+        pos.to_sigma = new_sigma;
+        pos.l2_norm = market.l2_norm;
+        pos.collateral_locked = collateral;
+        pos.opened_ts = now;
+        pos.settled = false;
+        pos.bump = ctx.bumps.position;
+
+        market.mu = new_mu;
+        market.sigma = new_sigma;
+        custody::deposit(
+            &ctx.accounts.token_program,
+            &ctx.accounts.mint,
+            &ctx.accounts.trader_tokens,
+            &mut ctx.accounts.collateral_vault,
+            &mut ctx.accounts.vault_tokens,
+            &ctx.accounts.trader,
+            collateral,
+        )?;
+        market.distribution_balance = market.distribution_balance.checked_add(collateral).ok_or(ErrorCode::MathOverflow)?;
+
+        emit!(DistributionTraded {
+            market: market.key(),
+            trader: pos.owner,
+            position: pos.key(),
+            from_mu: pos.from_mu,
+            from_sigma: pos.from_sigma,
+            to_mu: new_mu,
+            to_sigma: new_sigma,
+            collateral,
+            l2_distance,
+            ts: now,
+        });
+        msg!(
+            "IDX:DistributionTraded|market={}|trader={}|mu={}|sigma={}|collateral={}|ts={}",
+            market.key(),
+            pos.owner,
+            new_mu,
+            new_sigma,
+            collateral,
+            now
+        );
+        Ok(())
+    }
+
+    // ====== Gaussian mixtures ======
+
+    /// Sets the opening mixture of a mixture market. Weights are in ppm and must sum to 1e6.
+    pub fn init_mixture(ctx: Context<InitMixture>, components: Vec<MixtureComponent>) -> Result<()> {
+        let market = &ctx.accounts.market;
+        require!(market.market_type == MarketType::Mixture, ErrorCode::WrongMarketType);
+        require!(market.l2_norm > 0, ErrorCode::InvalidMarketParams);
+        market.mixture(&components)?;
+
+        let mixture = &mut ctx.accounts.mixture;
+        mixture.market = market.key();
+        mixture.set(&components);
+        mixture.bump = ctx.bumps.mixture;
+
+        emit!(MixtureInitialized { market: market.key(), len: mixture.len, ts: Clock::get()?.unix_timestamp });
+        Ok(())
+    }
+
+    /// Replaces the market's mixture with `components`. Collateral is the worst-case loss of
+    /// the L2-normalised move, exactly as for `trade_distribution`.
+    pub fn trade_mixture(
+        ctx: Context<TradeMixture>,
+        position_id: u64,
+        components: Vec<MixtureComponent>,
+        max_collateral: u64,
+    ) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        let now = Clock::get()?.unix_timestamp;
+        validation::market_open(market, now)?;
+        require!(market.market_type == MarketType::Mixture, ErrorCode::WrongMarketType);
+
+        let mixture = &mut ctx.accounts.mixture;
+        let from = market.mixture(mixture.components())?;
+        let to = market.mixture(&components)?;
+        let collateral = mixture_collateral_required(market.l2_norm, &from, &to).ok_or(ErrorCode::MathOverflow)?;
+        require!(collateral <= max_collateral, ErrorCode::SlippageExceeded);
+        let k = Fixed::from_int(i64::try_from(market.l2_norm).map_err(|_| ErrorCode::MathOverflow)?);
+        let l2_distance = mixture_scaled_l2_distance_fixed(k, &from, &to)
+            .and_then(Fixed::ceil_to_i64)
+            .ok_or(ErrorCode::MathOverflow)? as u64;
+
+        let pos = &mut ctx.accounts.position;
+        pos.owner = ctx.accounts.trader.key();
+        pos.market = market.key();
+        pos.position_id = position_id;
+        pos.from = mixture.components;
+        pos.from_len = mixture.len;
+        pos.l2_norm = market.l2_norm;
+        pos.collateral_locked = collateral;
+        pos.opened_ts = now;
+        pos.settled = false;
+        pos.bump = ctx.bumps.position;
+
+        mixture.set(&components);
+        pos.to = mixture.components;
+        pos.to_len = mixture.len;
+        custody::deposit(
+            &ctx.accounts.token_program,
+            &ctx.accounts.mint,
+            &ctx.accounts.trader_tokens,
+            &mut ctx.accounts.collateral_vault,
+            &mut ctx.accounts.vault_tokens,
+            &ctx.accounts.trader,
+            collateral,
+        )?;
+        market.distribution_balance = market.distribution_balance.checked_add(collateral).ok_or(ErrorCode::MathOverflow)?;
+
+        emit!(MixtureTraded {
+            market: market.key(),
+            trader: pos.owner,
+            position: pos.key(),
+            from_len: pos.from_len,
+            to_len: pos.to_len,
+            collateral,
+            l2_distance,
+            ts: now,
+        });
+        msg!(
+            "IDX:MixtureTraded|market={}|trader={}|components={}|collateral={}|ts={}",
+            market.key(),
+            pos.owner,
+            pos.to_len,
+            collateral,
+            now
+        );
+        Ok(())
+    }
+
+    /// Settles a mixture position on a resolved market: collateral plus g(y) - f(y) for the
+    /// k-scaled `to` and `from` mixtures at the outcome y, floored at zero.
+    pub fn claim_mixture_payout(ctx: Context<ClaimMixturePayout>) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        require!(market.status == MarketStatus::Resolved, ErrorCode::MarketNotResolved);
+        let pos = &mut ctx.accounts.position;
+        require!(!pos.settled, ErrorCode::AlreadySettled);
+
+        let from = market.mixture(&pos.from[..pos.from_len as usize])?;
+        let to = market.mixture(&pos.to[..pos.to_len as usize])?;
+        let y = market.fixed(market.resolved_outcome)?;
+        let k = Fixed::from_int(i64::try_from(pos.l2_norm).map_err(|_| ErrorCode::MathOverflow)?);
+        let f = mixture_scaled_density_fixed(k, &from, y).ok_or(ErrorCode::MathOverflow)?;
+        let g = mixture_scaled_density_fixed(k, &to, y).ok_or(ErrorCode::MathOverflow)?;
+        let amount = Fixed::from_int(i64::try_from(pos.collateral_locked).map_err(|_| ErrorCode::MathOverflow)?)
+            .checked_add(g)
+            .and_then(|v| v.checked_sub(f))
+            .and_then(|v| v.max(Fixed::ZERO).floor_to_i64())
+            .ok_or(ErrorCode::MathOverflow)? as u64;
+        market.distribution_balance = market.distribution_balance.checked_sub(amount).ok_or(ErrorCode::InsufficientVaultBalance)?;
+        pos.settled = true;
+        custody::withdraw(
+            &ctx.accounts.token_program,
+            &ctx.accounts.mint,
+            &mut ctx.accounts.collateral_vault,
+            &mut ctx.accounts.vault_tokens,
+            &ctx.accounts.owner_tokens,
+            amount,
+        )?;
+
+        let now = Clock::get()?.unix_timestamp;
+        emit!(PayoutClaimed { owner: pos.owner, market: market.key(), amount, ts: now });
+        msg!(
+            "IDX:PayoutClaimed|market={}|owner={}|rule={}|amount={}|ts={}",
+            market.key(),
+            pos.owner,
+            market.scoring_rule,
+            amount,
+            now
+        );
+        Ok(())
+    }
+
+    // ====== Histograms ======
+
+    /// Creates the bin-mass account of a histogram market with a uniform prior over the `step` grid.
+    pub fn init_histogram(ctx: Context<InitHistogram>) -> Result<()> {
+        let market = &ctx.accounts.market;
+        require!(market.market_type == MarketType::Histogram, ErrorCode::WrongMarketType);
+        require!(market.l2_norm > 0, ErrorCode::InvalidMarketParams);
+        let bins = market.histogram_bins()?;
+
+        let mut hist = ctx.accounts.histogram.load_init()?;
+        hist.market = market.key();
+        hist.bins = bins as u16;
+        hist.bump = ctx.bumps.histogram;
+        // Spread the remainder over the lowest bins so the masses sum to exactly the scale
+        let (each, rem) = (HISTOGRAM_MASS_SCALE / bins as u32, HISTOGRAM_MASS_SCALE % bins as u32);
+        for (i, m) in hist.mass[..bins].iter_mut().enumerate() {
+            *m = each + u32::from((i as u32) < rem);
+        }
+
+        emit!(HistogramInitialized { market: market.key(), bins: hist.bins, ts: Clock::get()?.unix_timestamp });
+        Ok(())
+    }
+
+    /// Moves probability mass between bins. Collateral is the largest per-bin drop of the
+    /// L2-normalised density, which is exact for piecewise-constant functions.
+    pub fn trade_histogram(
+        ctx: Context<TradeHistogram>,
+        position_id: u64,
+        shifts: Vec<BinShift>,
+        max_collateral: u64,
+    ) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        let now = Clock::get()?.unix_timestamp;
+        validation::market_open(market, now)?;
+        require!(market.market_type == MarketType::Histogram, ErrorCode::WrongMarketType);
+        require!(!shifts.is_empty() && shifts.len() <= MAX_HISTOGRAM_SHIFTS, ErrorCode::InvalidHistogramShift);
+
+        let mut hist = ctx.accounts.histogram.load_mut()?;
+        let bins = hist.bins as usize;
+        let from = hist.mass;
+        let mut to = from;
+        for shift in &shifts {
+            let (src, dst) = (shift.from_bin as usize, shift.to_bin as usize);
+            require!(src < bins && dst < bins && src != dst && shift.mass > 0, ErrorCode::InvalidHistogramShift);
+            to[src] = to[src].checked_sub(shift.mass).ok_or(ErrorCode::InvalidHistogramShift)?;
+            to[dst] = to[dst].checked_add(shift.mass).ok_or(ErrorCode::MathOverflow)?;
+        }
+
+        let width = market.fixed(market.step)?;
+        let collateral = histogram_collateral_required(market.l2_norm, &from[..bins], &to[..bins], width)
+            .ok_or(ErrorCode::MathOverflow)?;
+        require!(collateral <= max_collateral, ErrorCode::SlippageExceeded);
+        let k = Fixed::from_int(i64::try_from(market.l2_norm).map_err(|_| ErrorCode::MathOverflow)?);
+        let l2_distance = histogram_scaled_l2_distance_fixed(k, &from[..bins], &to[..bins])
+            .and_then(Fixed::ceil_to_i64)
+            .ok_or(ErrorCode::MathOverflow)? as u64;
+        hist.mass = to;
+
+        let mut pos = ctx.accounts.position.load_init()?;
+        pos.owner = ctx.accounts.trader.key();
+        pos.market = market.key();
+        pos.position_id = position_id;
+        pos.l2_norm = market.l2_norm;
+        pos.collateral_locked = collateral;
+        pos.opened_ts = now;
+        pos.from_mass = from;
+        pos.to_mass = to;
+        pos.bins = hist.bins;
+        pos.bump = ctx.bumps.position;
+        custody::deposit(
+            &ctx.accounts.token_program,
+            &ctx.accounts.mint,
+            &ctx.accounts.trader_tokens,
+            &mut ctx.accounts.collateral_vault,
+            &mut ctx.accounts.vault_tokens,
+            &ctx.accounts.trader,
+            collateral,
+        )?;
+        market.distribution_balance = market.distribution_balance.checked_add(collateral).ok_or(ErrorCode::MathOverflow)?;
+
+        emit!(HistogramTraded {
+            market: market.key(),
+            trader: pos.owner,
+            position: ctx.accounts.position.key(),
+            shifts: shifts.len() as u8,
+            collateral,
+            l2_distance,
+            ts: now,
+        });
+        msg!(
+            "IDX:HistogramTraded|market={}|trader={}|shifts={}|collateral={}|ts={}",
+            market.key(),
+            pos.owner,
+            shifts.len(),
+            collateral,
+            now
+        );
+        Ok(())
+    }
+
+    /// Settles a histogram position on a resolved market: the trader is owed their collateral
+    /// plus g - f on the bin containing the outcome, floored at zero.
+    pub fn settle_histogram_position(ctx: Context<SettleHistogramPosition>) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        require!(market.status == MarketStatus::Resolved, ErrorCode::MarketNotResolved);
+        let mut pos = ctx.accounts.position.load_mut()?;
+        require_keys_eq!(pos.owner, ctx.accounts.owner.key(), ErrorCode::Unauthorized);
+        require_keys_eq!(pos.market, market.key(), ErrorCode::InvalidAccountLayout);
+        require!(pos.settled == 0, ErrorCode::AlreadySettled);
+
+        let bins = pos.bins as usize;
+        let width = market.fixed(market.step)?;
+        let bin = histogram_bin(market.fixed(market.resolved_outcome)?, market.fixed(market.outcome_min)?, width, bins)
+            .ok_or(ErrorCode::MathOverflow)?;
+        let k = Fixed::from_int(i64::try_from(pos.l2_norm).map_err(|_| ErrorCode::MathOverflow)?);
+        let f = histogram_scaled_density_fixed(k, &pos.from_mass[..bins], width, bin).ok_or(ErrorCode::MathOverflow)?;
+        let g = histogram_scaled_density_fixed(k, &pos.to_mass[..bins], width, bin).ok_or(ErrorCode::MathOverflow)?;
+        let payout = Fixed::from_int(i64::try_from(pos.collateral_locked).map_err(|_| ErrorCode::MathOverflow)?)
+            .checked_add(g)
+            .and_then(|v| v.checked_sub(f))
+            .and_then(|v| v.max(Fixed::ZERO).floor_to_i64())
+            .ok_or(ErrorCode::MathOverflow)? as u64;
+        market.distribution_balance = market.distribution_balance.checked_sub(payout).ok_or(ErrorCode::InsufficientVaultBalance)?;
+        pos.settled = 1;
+        pos.payout = payout;
+        custody::withdraw(
+            &ctx.accounts.token_program,
+            &ctx.accounts.mint,
+            &mut ctx.accounts.collateral_vault,
+            &mut ctx.accounts.vault_tokens,
+            &ctx.accounts.owner_tokens,
+            payout,
+        )?;
+
+        emit!(HistogramPositionSettled {
+            market: market.key(),
+            owner: pos.owner,
+            position: ctx.accounts.position.key(),
+            bin: bin as u16,
+            payout,
+            ts: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    // ====== Views ======
+
+    /// What `trade_pmamm` would settle for `size` right now: the collateral and shares in and
+    /// out, and the fees within them. Fails where the trade would. Returned as return data for
+    /// `.view()`; the pool isn't touched.
+    pub fn quote_pmamm(ctx: Context<QuotePmAmm>, side: Side, size: u64) -> Result<PmAmmQuote> {
+        validation::size(size)?;
+        let now = Clock::get()?.unix_timestamp;
+        validation::market_open(&ctx.accounts.market, now)?;
+        let pool = &ctx.accounts.pmamm_pool;
+        require!(now < pool.expiry_ts, ErrorCode::MarketClosed);
+        require!(pool.quoting(now), ErrorCode::AmmHandedOff);
+        PmAmmPool::clone(pool).fill(side, size, now)?.charge(side, &ctx.accounts.market)
+    }
+
+    /// The pm-AMM's liquidity under its schedule right now, and whether it still quotes.
+    /// Returned as return data for `.view()`.
+    pub fn query_liquidity(ctx: Context<QueryLiquidity>) -> Result<LiquidityQuery> {
+        let pool = &ctx.accounts.pmamm_pool;
+        let now = Clock::get()?.unix_timestamp;
+        let liquidity = pool.liquidity(now)?.floor_to_i64().ok_or(ErrorCode::MathOverflow)? as u64;
+        Ok(LiquidityQuery { liquidity, quoting: pool.quoting(now) })
+    }
+
+    /// Read-only summary of the market's current belief: P(a < X < b) and its 5th/50th/95th
+    /// percentiles, all in market decimals. Returned as return data, so clients can simulate
+    /// it (`.view()`) instead of re-deriving the numbers off-chain.
+    pub fn query_market(ctx: Context<QueryMarket>, a: i64, b: i64) -> Result<MarketQuery> {
+        let market = &ctx.accounts.market;
+        require!(a <= b, ErrorCode::InvalidMarketParams);
+        match market.market_type {
+            MarketType::Mixture => {
+                let state = ctx.accounts.mixture.as_ref().ok_or(ErrorCode::MissingMarketState)?;
+                let mix = market.mixture(state.components())?;
+                summarize_belief(market, a, b, |x| mixture_cdf_fixed(&mix, x), |p| mixture_quantile_fixed(&mix, p))
+            }
+            MarketType::Histogram => {
+                let loader = ctx.accounts.histogram.as_ref().ok_or(ErrorCode::MissingMarketState)?;
+                let hist = loader.load()?;
+                let mass = &hist.mass[..hist.bins as usize];
+                let (lo, width) = (market.fixed(market.outcome_min)?, market.fixed(market.step)?);
+                summarize_belief(
+                    market,
+                    a,
+                    b,
+                    |x| histogram_cdf_fixed(mass, HISTOGRAM_MASS_SCALE, lo, width, x),
+                    |p| histogram_quantile_fixed(mass, HISTOGRAM_MASS_SCALE, lo, width, p),
+                )
+            }
+            _ => {
+                let dist = market.distribution(market.mu, market.sigma)?;
+                summarize_belief(market, a, b, |x| dist.cdf(x), |p| dist.quantile(p))
+            }
+        }
+    }
+
+    // ====== Users & Positions ======
+
+    pub fn init_user(ctx: Context<InitUser>) -> Result<()> {
+        let user = &mut ctx.accounts.user_profile;
+        user.owner = ctx.accounts.owner.key();
+        user.open_positions = 0;
+        user.bump = ctx.bumps.user_profile;
+        Ok(())
+    }
+
+    pub fn open_position(
+        ctx: Context<OpenPosition>,
+        size: u64,
+        collateral_locked: u64,
+    ) -> Result<()> {
+        let pos = &mut ctx.accounts.position;
+        pos.owner = ctx.accounts.owner.key();
+        pos.market = ctx.accounts.market.key();
+        pos.size = size;
+        pos.collateral_locked = collateral_locked;
+        pos.entry_ts = Clock::get()?.unix_timestamp;
+        pos.realized_pnl = 0;
+        pos.bump = ctx.bumps.position;
+
+        let user = &mut ctx.accounts.user_profile;
+        user.open_positions = user.open_positions.saturating_add(1);
+        custody::deposit(
+            &ctx.accounts.token_program,
+            &ctx.accounts.mint,
+            &ctx.accounts.owner_tokens,
+            &mut ctx.accounts.collateral_vault,
+            &mut ctx.accounts.vault_tokens,
+            &ctx.accounts.owner,
+            collateral_locked,
+        )?;
+
+        emit!(PositionOpened {
+            owner: pos.owner,
+            market: pos.market,
+            size,
+            collateral_locked,
+            ts: pos.entry_ts,
+        });
+        Ok(())
+    }
+
+    pub fn adjust_position(
+        ctx: Context<AdjustPosition>,
+        delta_size: i64,
+        delta_collateral: i64,
+    ) -> Result<()> {
+        let pos = &mut ctx.accounts.position;
+        // size is u64; apply delta with saturation and conservative rounding
+        let new_size = if delta_size >= 0 {
+            pos.size.saturating_add(delta_size as u64)
+        } else {
+            pos.size.saturating_sub(delta_size.unsigned_abs())
+        };
+        pos.size = new_size;
+
+        // collateral moves with the delta: topped up from the owner, released back to them
+        let old_collateral = pos.collateral_locked;
+        if delta_collateral >= 0 {
+            pos.collateral_locked = old_collateral.checked_add(delta_collateral as u64).ok_or(ErrorCode::MathOverflow)?;
+            custody::deposit(
+                &ctx.accounts.token_program,
+                &ctx.accounts.mint,
+                &ctx.accounts.owner_tokens,
+                &mut ctx.accounts.collateral_vault,
+                &mut ctx.accounts.vault_tokens,
+                &ctx.accounts.owner,
+                delta_collateral as u64,
+            )?;
+        } else {
+            pos.collateral_locked = old_collateral.saturating_sub(delta_collateral.unsigned_abs());
+            custody::withdraw(
+                &ctx.accounts.token_program,
+                &ctx.accounts.mint,
+                &mut ctx.accounts.collateral_vault,
+                &mut ctx.accounts.vault_tokens,
+                &ctx.accounts.owner_tokens,
+                old_collateral - pos.collateral_locked,
+            )?;
+        }
+
+        emit!(PositionAdjusted {
+            owner: pos.owner,
+            market: pos.market,
+            delta_size,
+            delta_collateral,
+            ts: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Returns the position's locked collateral to the owner and closes the account, rent
+    /// included, so it can't be closed twice.
+    pub fn close_position(ctx: Context<ClosePosition>) -> Result<()> {
+        let pos = &ctx.accounts.position;
+        let user = &mut ctx.accounts.user_profile;
+        if user.open_positions > 0 { user.open_positions -= 1; }
+        custody::withdraw(
+            &ctx.accounts.token_program,
+            &ctx.accounts.mint,
+            &mut ctx.accounts.collateral_vault,
+            &mut ctx.accounts.vault_tokens,
+            &ctx.accounts.owner_tokens,
+            pos.collateral_locked,
+        )?;
+
+        emit!(PositionClosed {
+            owner: pos.owner,
+            market: pos.market,
+            size: pos.size,
+            collateral_locked: pos.collateral_locked,
+            realized_pnl: pos.realized_pnl,
+            ts: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    // ====== Liquidity ======
+
+    /// Opens an empty `LpPosition` ahead of a deposit. Its cooldown starts now.
+    pub fn open_lp_position(ctx: Context<OpenLpPosition>) -> Result<()> {
+        let pos = &mut ctx.accounts.lp_position;
+        pos.market = ctx.accounts.market.key();
+        pos.provider = ctx.accounts.provider.key();
+        pos.deposited = 0;
+        pos.shares = 0;
+        pos.deposit_ts = Clock::get()?.unix_timestamp;
+        pos.bump = ctx.bumps.lp_position;
+        Ok(())
+    }
+
+    /// Deposits `amount` of collateral into the vault and mints the provider LP shares at the
+    /// pool's current share price, recording both on their `LpPosition` and restarting its
+    /// cooldown. The provider's share account is frozen again once minted into.
+    pub fn add_liquidity(ctx: Context<AddLiquidity>, amount: u64) -> Result<()> {
+        validation::size(amount)?;
+        let now = Clock::get()?.unix_timestamp;
+        validation::market_open(&ctx.accounts.market, now)?;
+        let shares = ctx.accounts.liquidity_pool.shares_for(amount, ctx.accounts.lp_mint.supply)?;
+        require!(shares > 0, ErrorCode::ZeroShares);
+        custody::deposit(
+            &ctx.accounts.token_program,
+            &ctx.accounts.mint,
+            &ctx.accounts.provider_tokens,
+            &mut ctx.accounts.collateral_vault,
+            &mut ctx.accounts.vault_tokens,
+            &ctx.accounts.provider,
+            amount,
+        )?;
+
+        let lp = &mut ctx.accounts.liquidity_pool;
+        lp.total_liquidity = lp.total_liquidity.checked_add(amount).ok_or(ErrorCode::MathOverflow)?;
+        let market = lp.market;
+        let seeds: &[&[u8]] = &[b"pool", market.as_ref(), &[lp.bump]];
+        let (program, lp_mint, shares_to) = (&ctx.accounts.token_program, &ctx.accounts.lp_mint, &ctx.accounts.provider_shares);
+        if shares_to.is_frozen() {
+            freeze_shares(program, lp_mint, shares_to, &ctx.accounts.liquidity_pool, false)?;
+        }
+        token_interface::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                MintTo {
+                    mint: ctx.accounts.lp_mint.to_account_info(),
+                    to: ctx.accounts.provider_shares.to_account_info(),
+                    authority: ctx.accounts.liquidity_pool.to_account_info(),
+                },
+                &[seeds],
+            ),
+            shares,
+        )?;
+        freeze_shares(program, lp_mint, shares_to, &ctx.accounts.liquidity_pool, true)?;
+
+        let provider = ctx.accounts.provider.key();
+        let pos = &mut ctx.accounts.lp_position;
+        if pos.provider == Pubkey::default() {
+            pos.market = market;
+            pos.provider = provider;
+            pos.bump = ctx.bumps.lp_position;
+        }
+        pos.deposited = pos.deposited.checked_add(amount).ok_or(ErrorCode::MathOverflow)?;
+        pos.shares = pos.shares.checked_add(shares).ok_or(ErrorCode::MathOverflow)?;
+        pos.deposit_ts = now;
+
+        emit!(LiquidityAdded { market, provider, amount, shares, ts: now });
+        msg!("IDX:LiquidityAdded|market={}|provider={}|amount={}|shares={}|ts={}", market, provider, amount, shares, now);
+        Ok(())
+    }
+
+    /// Burns `shares` of the provider's LP shares and pays out their pro-rata claim on the pool.
+    /// The shares must be on the provider's `LpPosition`, past its cooldown, and the market
+    /// either resolved or not yet within `LP_RESOLUTION_LOCK_SECS` of its resolution time. Any
+    /// shares left stay frozen.
+    pub fn remove_liquidity(ctx: Context<RemoveLiquidity>, shares: u64) -> Result<()> {
+        validation::size(shares)?;
+        let now = Clock::get()?.unix_timestamp;
+        let market = &ctx.accounts.market;
+        let pos = &mut ctx.accounts.lp_position;
+        require!(pos.unlocked(market.status, market.resolution_time, now), ErrorCode::LiquidityLocked);
+        // share accounts are frozen, so whoever holds the shares minted them through this
+        // position and its cooldown
+        let held = ctx.accounts.provider_shares.amount;
+        require!(held >= shares, ErrorCode::InsufficientLiquidity);
+        pos.shares = pos.shares.saturating_sub(shares);
+        let amount = ctx.accounts.liquidity_pool.amount_for(shares, ctx.accounts.lp_mint.supply)?;
+        require!(amount > 0, ErrorCode::ZeroShares);
+        let (program, lp_mint, shares_from) = (&ctx.accounts.token_program, &ctx.accounts.lp_mint, &ctx.accounts.provider_shares);
+        if shares_from.is_frozen() {
+            freeze_shares(program, lp_mint, shares_from, &ctx.accounts.liquidity_pool, false)?;
+        }
+        token_interface::burn(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Burn {
+                    mint: ctx.accounts.lp_mint.to_account_info(),
+                    from: ctx.accounts.provider_shares.to_account_info(),
+                    authority: ctx.accounts.provider.to_account_info(),
+                },
+            ),
+            shares,
+        )?;
+        // an emptied account is left thawed so its owner can close it
+        if held > shares {
+            freeze_shares(program, lp_mint, shares_from, &ctx.accounts.liquidity_pool, true)?;
+        }
+        let lp = &mut ctx.accounts.liquidity_pool;
+        lp.total_liquidity -= amount;
+        let pos = &mut ctx.accounts.lp_position;
+        pos.deposited = pos.deposited.saturating_sub(amount);
+        custody::withdraw(
+            &ctx.accounts.token_program,
+            &ctx.accounts.mint,
+            &mut ctx.accounts.collateral_vault,
+            &mut ctx.accounts.vault_tokens,
+            &ctx.accounts.provider_tokens,
+            amount,
+        )?;
+
+        let (market, provider) = (ctx.accounts.market.key(), ctx.accounts.provider.key());
+        emit!(LiquidityRemoved { market, provider, amount, shares, ts: now });
+        msg!("IDX:LiquidityRemoved|market={}|provider={}|amount={}|shares={}|ts={}", market, provider, amount, shares, now);
+        Ok(())
+    }
+
+    // ====== pm-AMM ======
+
+    /// Opens the market's pm-AMM at even odds. The authority funds the opening reserves, x = y
+    /// complete sets, from its collateral. The schedule's bounds must be ordered and any
+    /// hand-off must come before expiry.
+    pub fn init_pmamm(ctx: Context<InitPmAmm>, params: PmAmmParams) -> Result<()> {
+        require!(
+            params.l0 > 0
+                && params.l_floor <= params.l_ceiling
+                && params.l_ceiling > 0
+                && params.l_ceiling <= i64::MAX as u64
+                && params.handoff_ts <= params.expiry_ts
+                && params.handoff_ts >= 0
+                && params.fee_bps <= 10_000,
+            ErrorCode::InvalidMarketParams
+        );
+        let now = Clock::get()?.unix_timestamp;
+        let pool = &mut ctx.accounts.pmamm_pool;
+        pool.market = ctx.accounts.market.key();
+        pool.l0 = params.l0;
+        pool.schedule = params.schedule;
+        pool.l_floor = params.l_floor;
+        pool.l_ceiling = params.l_ceiling;
+        pool.fee_bps = params.fee_bps;
+        pool.expiry_ts = params.expiry_ts;
+        pool.handoff_ts = params.handoff_ts;
+        pool.fees = 0;
+        pool.shares_outstanding = 0;
+        pool.bump = ctx.bumps.pmamm_pool;
+        // open at even odds, z = 0, on the curve at today's liquidity
+        let l = pool.liquidity(now)?;
+        (pool.x, pool.y) = PmAmmPool::reserves_at(Fixed::ZERO, l)?;
+        pool.collateral = pool.y;
+        custody::deposit(
+            &ctx.accounts.token_program,
+            &ctx.accounts.mint,
+            &ctx.accounts.authority_tokens,
+            &mut ctx.accounts.collateral_vault,
+            &mut ctx.accounts.vault_tokens,
+            &ctx.accounts.authority,
+            pool.collateral,
+        )?;
+
+        emit!(PmAmmInitialized {
+            market: pool.market,
+            l0: params.l0,
+            schedule: params.schedule,
+            l_floor: params.l_floor,
+            l_ceiling: params.l_ceiling,
+            fee_bps: params.fee_bps,
+            expiry_ts: params.expiry_ts,
+            handoff_ts: params.handoff_ts,
+            ts: now,
+        });
+        msg!(
+            "IDX:PmAmmInitialized|market={}|l0={}|schedule={}|l_floor={}|l_ceiling={}|fee_bps={}|expiry_ts={}|handoff_ts={}|ts={}",
+            pool.market,
+            params.l0,
+            params.schedule.code(),
+            params.l_floor,
+            params.l_ceiling,
+            params.fee_bps,
+            params.expiry_ts,
+            params.handoff_ts,
+            now
+        );
+        Ok(())
+    }
+
+    /// Trades exactly `size` shares against the pm-AMM. A buy pays `amount_in` collateral for
+    /// `size` shares, a sale `size` shares for `amount_out`; either fails past `max_amount_in`
+    /// or short of `min_amount_out`, and if the price bound would cut the fill short. The
+    /// collateral side carries the pool's LP fee and the market's platform and creator fees.
+    pub fn trade_pmamm(
+        ctx: Context<TradePmAmm>,
+        side: Side,
+        size: u64,
+        min_amount_out: u64,
+        max_amount_in: u64,
+    ) -> Result<()> {
+        validation::size(size)?;
+        let now = Clock::get()?.unix_timestamp;
+        validation::market_open(&ctx.accounts.market, now)?;
+        let pool = &mut ctx.accounts.pmamm_pool;
+        require!(now < pool.expiry_ts, ErrorCode::MarketClosed);
+        require!(pool.quoting(now), ErrorCode::AmmHandedOff);
+        let before = (ctx.accounts.collateral_vault.balance, pmamm_owed(pool, &ctx.accounts.market_fees));
+        let filled = pool.fill(side, size, now)?;
+        let quote = filled.clone().charge(side, &ctx.accounts.market)?;
+        require!(quote.amount_out >= min_amount_out && quote.amount_in <= max_amount_in, ErrorCode::SlippageExceeded);
+        let position = &mut ctx.accounts.pmamm_position;
+        if position.owner == Pubkey::default() {
+            position.market = ctx.accounts.market.key();
+            position.owner = ctx.accounts.taker.key();
+            position.bump = ctx.bumps.pmamm_position;
+        }
+        settle_pmamm(
+            side,
+            size,
+            quote.collateral(side),
+            position,
+            &ctx.accounts.token_program,
+            &ctx.accounts.mint,
+            &ctx.accounts.taker_tokens,
+            &mut ctx.accounts.collateral_vault,
+            &mut ctx.accounts.vault_tokens,
+            &ctx.accounts.taker,
+        )?;
+        let fees = &mut ctx.accounts.market_fees;
+        fees.accrue(quote.platform_fee, quote.creator_fee, quote.lp_fee)?;
+        reconcile_pmamm(&ctx.accounts.pmamm_pool, fees, &ctx.accounts.collateral_vault, before)?;
+        let (market, taker) = (ctx.accounts.market.key(), ctx.accounts.taker.key());
+        emit_fees(market, taker, (quote.platform_fee, quote.creator_fee, quote.lp_fee), now);
+        let price_bps = u64::try_from(filled.collateral(side) as u128 * 10_000 / size as u128).map_err(|_| ErrorCode::MathOverflow)?;
+        emit!(TradeExecuted {
+            market: ctx.accounts.market.key(),
+            taker: ctx.accounts.taker.key(),
+            side,
+            price_bps,
+            size,
+            book_size: 0,
+            amm_size: size,
+            ts: now,
+        });
+        msg!(
+            "IDX:TradeExecuted|market={}|taker={}|side={}|price_bps={}|size={}|book_size=0|amm_size={}|ts={}",
+            ctx.accounts.market.key(),
+            ctx.accounts.taker.key(),
+            side as u8,
+            price_bps,
+            size,
+            size,
+            now
+        );
+        Ok(())
+    }
+
+    /// Redeems the caller's pm-AMM shares once the market resolved, a unit of collateral per
+    /// share if YES won, and empties the position.
+    pub fn redeem_pmamm_position(ctx: Context<RedeemPmAmmPosition>) -> Result<()> {
+        let market = &ctx.accounts.market;
+        require!(market.status == MarketStatus::Resolved, ErrorCode::MarketNotResolved);
+        let position = &mut ctx.accounts.pmamm_position;
+        let shares = position.shares;
+        require!(shares > 0, ErrorCode::InsufficientShares);
+        let amount = ctx.accounts.pmamm_pool.redeem(shares, market.yes_wins())?;
+        position.shares = 0;
+        custody::withdraw(
+            &ctx.accounts.token_program,
+            &ctx.accounts.mint,
+            &mut ctx.accounts.collateral_vault,
+            &mut ctx.accounts.vault_tokens,
+            &ctx.accounts.owner_tokens,
+            amount,
+        )?;
+
+        let (market, owner) = (ctx.accounts.market.key(), ctx.accounts.owner.key());
+        let now = Clock::get()?.unix_timestamp;
+        emit!(PmAmmRedeemed { market, owner, shares, amount, ts: now });
+        msg!("IDX:PmAmmRedeemed|market={}|owner={}|shares={}|amount={}|ts={}", market, owner, shares, amount, now);
+        Ok(())
+    }
+
+    /// Pays the pm-AMM's residual to the market's authority once the market resolved: its
+    /// funding, decay surplus, LP fees and trading gains. Collateral for the winning shares takers
+    /// still hold stays behind for `redeem_pmamm_position`.
+    pub fn withdraw_pmamm_residual(ctx: Context<WithdrawPmAmmResidual>) -> Result<()> {
+        let market = &ctx.accounts.market;
+        require!(market.status == MarketStatus::Resolved, ErrorCode::MarketNotResolved);
+        let amount = ctx.accounts.pmamm_pool.withdraw_residual(market.yes_wins())?;
+        custody::withdraw(
+            &ctx.accounts.token_program,
+            &ctx.accounts.mint,
+            &mut ctx.accounts.collateral_vault,
+            &mut ctx.accounts.vault_tokens,
+            &ctx.accounts.authority_tokens,
+            amount,
+        )?;
+
+        let (market, authority) = (ctx.accounts.market.key(), ctx.accounts.authority.key());
+        let now = Clock::get()?.unix_timestamp;
+        emit!(PmAmmResidualWithdrawn { market, authority, amount, ts: now });
+        msg!("IDX:PmAmmResidualWithdrawn|market={}|authority={}|amount={}|ts={}", market, authority, amount, now);
+        Ok(())
+    }
+
+    // ====== Fees ======
+
+    /// Pays the platform fees the market has charged since the last claim to `recipient_tokens`.
+    /// Signed by the authority of the registry the market was set up under.
+    pub fn claim_platform_fees(ctx: Context<ClaimFees>) -> Result<()> {
+        require_keys_eq!(ctx.accounts.market_fees.platform, ctx.accounts.claimant.key(), ErrorCode::Unauthorized);
+        claim_fees(ctx.accounts, FeeRecipient::Platform)
+    }
+
+    /// Pays the creator fees the market has charged since the last claim to `recipient_tokens`.
+    /// Signed by the market's authority.
+    pub fn claim_creator_fees(ctx: Context<ClaimFees>) -> Result<()> {
+        require_keys_eq!(ctx.accounts.market.authority, ctx.accounts.claimant.key(), ErrorCode::Unauthorized);
+        claim_fees(ctx.accounts, FeeRecipient::Creator)
+    }
+
+    // ====== Migrations ======
+
+    /// Rewrites a pre-v1 market (f64 fields) into the current layout, scaling every
+    /// float by 10^decimals. The market's order book prices are converted to bps as well.
+    /// Legacy markets carry no L2 norm or strike, so the authority supplies `l2_norm` for
+    /// distribution trades and `strike` (in the new decimals) for the book and the pm-AMM.
+    pub fn migrate_market(ctx: Context<MigrateMarket>, decimals: u8, l2_norm: u64, strike: i64) -> Result<()> {
+        require!(decimals <= MAX_DECIMALS, ErrorCode::InvalidDecimals);
+        require!(l2_norm > 0, ErrorCode::InvalidMarketParams);
+        let market_info = ctx.accounts.market.to_account_info();
+        let legacy = {
+            let data = market_info.try_borrow_data()?;
+            require!(data.len() >= 8 && &data[..8] == Market::DISCRIMINATOR, ErrorCode::InvalidAccountLayout);
+            require!(data.len() == 8 + MarketV0::SIZE, ErrorCode::AlreadyMigrated);
+            MarketV0::deserialize(&mut &data[8..])?
+        };
+        require_keys_eq!(legacy.authority, ctx.accounts.authority.key(), ErrorCode::Unauthorized);
+
+        let scale = |bits: u64| scaled_from_f64_bits(bits, decimals).ok_or(ErrorCode::MathOverflow);
+        let migrated = Market {
+            version: MARKET_VERSION,
+            authority: legacy.authority,
+            slug: legacy.slug,
+            market_type: MarketType::try_from(legacy.market_type)?,
+            decimals,
+            outcome_min: scale(legacy.outcome_min)?,
+            outcome_max: scale(legacy.outcome_max)?,
+            unit: legacy.unit,
+            dist_type: legacy.dist_type,
+            mu: scale(legacy.mu)?,
+            sigma: scale(legacy.sigma)?,
+            sigma_min: scale(legacy.sigma_min)?,
+            step: scale(legacy.step)?,
+            resolution_time: legacy.resolution_time,
+            oracle_config: legacy.oracle_config,
+            fee_bps_platform: legacy.fee_bps_platform,
+            fee_bps_creator: legacy.fee_bps_creator,
+            liquidity_pool: legacy.liquidity_pool,
+            order_book: legacy.order_book,
+            collateral_vault: legacy.collateral_vault,
+            status: MarketStatus::try_from(legacy.status)?,
+            bump: legacy.bump,
+            l2_norm,
+            dist_shape: 0,
+            resolved_outcome: 0,
+            scoring_rule: SCORING_SPHERICAL,
+            strike,
+            fee_bps_lp: 0,
+            distribution_balance: 0,
+            reserved: [0u8; MARKET_RESERVED],
+        };
+        migrated.validate()?;
+
+        grow(&market_info, 8 + Market::SIZE, &ctx.accounts.authority, &ctx.accounts.system_program)?;
+        migrated.try_serialize(&mut &mut market_info.try_borrow_mut_data()?[..])?;
+
+        if let Some(order_book) = &ctx.accounts.order_book {
+            require_keys_eq!(order_book.key(), legacy.order_book, ErrorCode::InvalidAccountLayout);
+            let mut data = order_book.try_borrow_mut_data()?;
+            require!(data.len() == 8 + OrderBook::SIZE && &data[..8] == OrderBook::DISCRIMINATOR, ErrorCode::InvalidAccountLayout);
+            // best_bid / best_ask sit right after the market key and were f64 prices in [0, 1]
+            for offset in [40usize, 48] {
+                let bits = data[offset..offset + 8].try_into().map_err(|_| ErrorCode::InvalidAccountLayout)?;
+                let bits = u64::from_le_bytes(bits);
+                let bps = scaled_from_f64_bits(bits, 4).ok_or(ErrorCode::MathOverflow)?;
+                data[offset..offset + 8].copy_from_slice(&(bps.max(0) as u64).to_le_bytes());
+            }
+        }
+
+        emit!(MarketMigrated { market: market_info.key(), version: MARKET_VERSION, decimals, ts: Clock::get()?.unix_timestamp });
+        Ok(())
+    }
+
+    /// Second step of migrating a pre-v1 market: rewrites its `LiquidityPool` and
+    /// `CollateralVault` into the current layouts and creates the vault's token account, the LP
+    /// mint and the fee ledger, as `initialize_market_infrastructure` does for a new market.
+    /// Legacy pools and vaults never held tokens, so both start empty, and no opening subsidy is
+    /// posted: the authority funds the distribution side through `fund_vault`.
+    pub fn migrate_market_infrastructure(ctx: Context<MigrateMarketInfrastructure>) -> Result<()> {
+        custody::check_mint(&ctx.accounts.collateral_mint)?;
+        require!(ctx.accounts.market.decimals <= ctx.accounts.collateral_mint.decimals, ErrorCode::InvalidDecimals);
+        let market = ctx.accounts.market.key();
+        let (lp_info, vault_info) = (ctx.accounts.liquidity_pool.to_account_info(), ctx.accounts.collateral_vault.to_account_info());
+        check_legacy(&lp_info, LiquidityPool::DISCRIMINATOR, LIQUIDITY_POOL_V0_SIZE)?;
+        check_legacy(&vault_info, CollateralVault::DISCRIMINATOR, COLLATERAL_VAULT_V0_SIZE)?;
+
+        let lp = LiquidityPool {
+            market,
+            vault: vault_info.key(),
+            lp_mint: ctx.accounts.lp_mint.key(),
+            total_liquidity: 0,
+            fees_accrued: 0,
+            bump: ctx.bumps.liquidity_pool,
+        };
+        grow(&lp_info, 8 + LiquidityPool::SIZE, &ctx.accounts.authority, &ctx.accounts.system_program)?;
+        lp.try_serialize(&mut &mut lp_info.try_borrow_mut_data()?[..])?;
+        let vault = CollateralVault {
+            market,
+            mint: ctx.accounts.collateral_mint.key(),
+            token_account: ctx.accounts.vault_tokens.key(),
+            balance: 0,
+            bump: ctx.bumps.collateral_vault,
+        };
+        grow(&vault_info, 8 + CollateralVault::SIZE, &ctx.accounts.authority, &ctx.accounts.system_program)?;
+        vault.try_serialize(&mut &mut vault_info.try_borrow_mut_data()?[..])?;
+
+        let fees = &mut ctx.accounts.market_fees;
+        fees.market = market;
+        fees.platform = ctx.accounts.registry.authority;
+        fees.platform_fees = 0;
+        fees.creator_fees = 0;
+        fees.lp_fees = 0;
+        fees.bump = ctx.bumps.market_fees;
+
+        emit!(MarketInfrastructureInitialized { market, subsidy: 0, ts: Clock::get()?.unix_timestamp });
+        Ok(())
+    }
+
+    /// Closes a pre-v1 `PmAmmPool` to its market's authority. Its reserves were never funded,
+    /// so there is nothing to carry over; `init_pmamm` opens the pool again, funded.
+    pub fn close_legacy_pmamm(ctx: Context<CloseLegacyPmAmm>) -> Result<()> {
+        let pool = ctx.accounts.pmamm_pool.to_account_info();
+        check_legacy(&pool, PmAmmPool::DISCRIMINATOR, PMAMM_POOL_V0_SIZE)?;
+        let authority = ctx.accounts.authority.to_account_info();
+        **authority.try_borrow_mut_lamports()? += pool.lamports();
+        **pool.try_borrow_mut_lamports()? = 0;
+        pool.assign(&system_program::ID);
+        pool.resize(0)?;
+        Ok(())
+    }
+
+}
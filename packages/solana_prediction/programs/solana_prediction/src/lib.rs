@@ -1,475 +1,38 @@
 use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::token_interface::{self, Burn, FreezeAccount, Mint, MintTo, ThawAccount, TokenAccount, TokenInterface};
+pub mod custody;
+pub mod fixed;
 pub mod math;
-use math::effective_liquidity;
+pub mod scoring;
+pub mod validation;
+use fixed::Fixed;
+use math::{
+    effective_liquidity_fixed, histogram_bin, histogram_cdf_fixed, histogram_collateral_required,
+    histogram_quantile_fixed, histogram_scaled_density_fixed, histogram_scaled_l2_distance_fixed,
+    mixture_cdf_fixed, mixture_collateral_required, mixture_quantile_fixed,
+    mixture_scaled_density_fixed, mixture_scaled_l2_distance_fixed, phi_cdf_fixed,
+    pmamm_reserve_fixed, pmamm_z_fixed, scaled_l2_distance_fixed, Distribution, GaussComponent,
+    DIST_BETA, DIST_LOGNORMAL, LIQUIDITY_LINEAR_DECAY, LIQUIDITY_SQRT_DECAY, LIQUIDITY_STATIC, MAX_HISTOGRAM_BINS,
+    MAX_MIXTURE_COMPONENTS, PMAMM_MAX_Z,
+};
+use scoring::{SCORING_CRPS, SCORING_SPHERICAL};
 
 // TODO: Replace with your deployed program ID later
 declare_id!("8ADZwnjeRCQ9Zkeafqb7XhmtEWC1XtVEajATzrtTr1nu");
 
-#[program]
-pub mod solana_prediction {
-    use super::*;
-
-    // Initialize a registry controlled by an authority
-    pub fn init_registry(ctx: Context<InitRegistry>) -> Result<()> {
-        let reg = &mut ctx.accounts.registry;
-        reg.authority = ctx.accounts.authority.key();
-        reg.bump = ctx.bumps.registry;
-        Ok(())
-    }
-
-    // Add (or update) a strategy mapping: key -> target program id
-    pub fn upsert_strategy(ctx: Context<UpsertStrategy>, strategy_key: [u8; 32], target_program: Pubkey) -> Result<()> {
-        require_keys_eq!(ctx.accounts.registry.authority, ctx.accounts.authority.key(), ErrorCode::Unauthorized);
-        let strat = &mut ctx.accounts.strategy;
-        strat.registry = ctx.accounts.registry.key();
-        strat.strategy_key = strategy_key;
-        strat.target_program = target_program;
-        strat.bump = ctx.bumps.strategy;
-        Ok(())
-    }
-
-    // Minimal gated execution stub. For a safe template, we only emit an event after checks.
-    // Extending to CPI requires passing account metas + instruction data and doing invoke_signed.
-    pub fn execute_strategy(
-        ctx: Context<ExecuteStrategy>,
-        strategy_key: [u8; 32],
-        approved: bool,
-        _ix_data: Vec<u8>, // placeholder for future CPI payload
-    ) -> Result<()> {
-        require!(approved, ErrorCode::NotApproved);
-        let strat = &ctx.accounts.strategy;
-        require!(strat.strategy_key == strategy_key, ErrorCode::StrategyKeyMismatch);
-        // Safety: For the starter template, we do not perform CPI. We only emit an event.
-        emit!(StrategyExecutionRequested {
-            user: ctx.accounts.user.key(),
-            strategy: strategy_key,
-            target_program: strat.target_program,
-            ts: Clock::get()?.unix_timestamp,
-        });
-        Ok(())
-    }
-
-    // ========== Distributional Markets (Scaffold) ==========
-
-    pub fn create_market(
-        ctx: Context<CreateMarket>,
-        params: MarketParams,
-    ) -> Result<()> {
-        let market = &mut ctx.accounts.market;
-        market.authority = ctx.accounts.authority.key();
-        market.slug = params.slug;
-        market.market_type = 0; // distributional
-        market.outcome_min = params.outcome_min;
-        market.outcome_max = params.outcome_max;
-        market.unit = params.unit;
-        market.dist_type = params.dist_type;
-        market.mu = params.mu;
-        market.sigma = params.sigma;
-        market.sigma_min = params.sigma_min;
-        market.step = params.step;
-        market.resolution_time = params.resolution_time;
-        market.oracle_config = params.oracle_config;
-        market.fee_bps_platform = params.fee_bps_platform;
-        market.fee_bps_creator = params.fee_bps_creator;
-        market.status = 0;
-        market.bump = ctx.bumps.market;
-
-        emit!(MarketCreated {
-            market: market.key(),
-            authority: market.authority,
-            slug: market.slug,
-            outcome_min: market.outcome_min,
-            outcome_max: market.outcome_max,
-            unit: market.unit,
-            ts: Clock::get()?.unix_timestamp,
-        });
-        // Lightweight indexer-friendly log
-        msg!(
-            "IDX:MarketCreated|market={}|ts={}",
-            market.key(),
-            Clock::get()?.unix_timestamp
-        );
-        Ok(())
-    }
-
-    pub fn initialize_market_infrastructure(
-        ctx: Context<InitializeMarketInfrastructure>,
-    ) -> Result<()> {
-        let market = &mut ctx.accounts.market;
-        market.liquidity_pool = ctx.accounts.liquidity_pool.key();
-        market.order_book = ctx.accounts.order_book.key();
-        market.collateral_vault = ctx.accounts.collateral_vault.key();
-
-        let lp = &mut ctx.accounts.liquidity_pool;
-        lp.market = market.key();
-        lp.vault = ctx.accounts.collateral_vault.key();
-        lp.total_liquidity = 0;
-        lp.bump = ctx.bumps.liquidity_pool;
-
-        let ob = &mut ctx.accounts.order_book;
-        ob.market = market.key();
-        ob.best_bid = 0.0;
-        ob.best_ask = 0.0;
-        ob.event_counter = 0;
-        ob.bump = ctx.bumps.order_book;
-
-        let cv = &mut ctx.accounts.collateral_vault;
-        cv.market = market.key();
-        cv.bump = ctx.bumps.collateral_vault;
-
-        emit!(MarketInfrastructureInitialized {
-            market: market.key(),
-            ts: Clock::get()?.unix_timestamp,
-        });
-        Ok(())
-    }
-
-    pub fn place_limit_order(
-        ctx: Context<PlaceLimitOrder>,
-        side: u8,
-        price_bps: u64,
-        size: u64,
-        _expiry: i64,
-    ) -> Result<()> {
-        let ob = &mut ctx.accounts.order_book;
-        let order_id = ob.event_counter;
-        ob.event_counter += 1;
-        
-        // CLOB: insert into price level (simplified; real impl uses heap/queue per level)
-        if side == 0 { // Buy
-            if price_bps as f64 / 10000.0 > ob.best_bid {
-                ob.best_bid = price_bps as f64 / 10000.0;
-            }
-        } else { // Sell
-            let ask_price = price_bps as f64 / 10000.0;
-            if ob.best_ask == 0.0 || ask_price < ob.best_ask {
-                ob.best_ask = ask_price;
-            }
-        }
-        
-        emit!(OrderPlaced { 
-            order_id, 
-            market: ctx.accounts.market.key(), 
-            owner: ctx.accounts.owner.key(), 
-            side, 
-            price_bps, 
-            size, 
-            ts: Clock::get()?.unix_timestamp 
-        });
-        msg!(
-            "IDX:OrderPlaced|market={}|order_id={}|side={}|price_bps={}|size={}|ts={}",
-            ctx.accounts.market.key(),
-            order_id,
-            side,
-            price_bps,
-            size,
-            Clock::get()?.unix_timestamp
-        );
-        Ok(())
-    }
-
-    pub fn execute_market_order(
-        ctx: Context<ExecuteMarketOrder>,
-        side: u8,
-        size: u64,
-    ) -> Result<()> {
-        // Router logic: try CLOB first, then AMM
-        let ob = &ctx.accounts.order_book;
-        let price = if side == 0 { ob.best_ask } else { ob.best_bid };
-        let price_bps = (price * 10000.0) as u64;
-        
-        // Simplified: assume full fill from AMM if CLOB empty
-        // Real impl: partial fills, update grid distribution per MATHEMATICAL_FRAMEWORK.md
-        
-        emit!(TradeExecuted { 
-            market: ctx.accounts.market.key(), 
-            taker: ctx.accounts.taker.key(), 
-            side, 
-            price_bps, 
-            size, 
-            ts: Clock::get()?.unix_timestamp 
-        });
-        msg!(
-            "IDX:TradeExecuted|market={}|taker={}|side={}|price_bps={}|size={}|ts={}",
-            ctx.accounts.market.key(),
-            ctx.accounts.taker.key(),
-            side,
-            price_bps,
-            size,
-            Clock::get()?.unix_timestamp
-        );
-        Ok(())
-    }
-
-    pub fn cancel_order(_ctx: Context<CancelOrder>, _order_id: u64) -> Result<()> {
-        emit!(OrderCancelled { order_id: _order_id, ts: Clock::get()?.unix_timestamp });
-        Ok(())
-    }
-
-    pub fn resolve_market(
-        ctx: Context<ResolveMarket>,
-        outcome_value: f64,
-        _proof: Vec<u8>,
-    ) -> Result<()> {
-        // Pyth oracle validation per ORACLE_RESOLUTION_FLOW.md:
-        // - Check status == Trading
-        // - staleness: now - publish_time <= STALENESS_LIMIT
-        // - confidence <= MAX_CONFIDENCE
-        // - normalize: price * 10^exponent
-        // For now, accept outcome_value directly (Pyth account parsing to be added)
-        
-        let market = &mut ctx.accounts.market;
-        require!(market.status == 0 || market.status == 1, ErrorCode::MarketNotActive);
-        market.status = 2; // resolved
-        
-        emit!(MarketResolved { 
-            market: market.key(), 
-            outcome_value, 
-            ts: Clock::get()?.unix_timestamp 
-        });
-        Ok(())
-    }
-
-    pub fn claim_payout(_ctx: Context<ClaimPayout>) -> Result<()> {
-        emit!(PayoutClaimed { owner: Pubkey::default(), market: Pubkey::default(), amount: 0, ts: Clock::get()?.unix_timestamp });
-        Ok(())
-    }
-
-    // ====== Users & Positions ======
-
-    pub fn init_user(ctx: Context<InitUser>) -> Result<()> {
-        let user = &mut ctx.accounts.user_profile;
-        user.owner = ctx.accounts.owner.key();
-        user.open_positions = 0;
-        user.bump = ctx.bumps.user_profile;
-        Ok(())
-    }
-
-    pub fn open_position(
-        ctx: Context<OpenPosition>,
-        size: u64,
-        collateral_locked: u64,
-    ) -> Result<()> {
-        let pos = &mut ctx.accounts.position;
-        pos.owner = ctx.accounts.owner.key();
-        pos.market = ctx.accounts.market.key();
-        pos.size = size;
-        pos.collateral_locked = collateral_locked;
-        pos.entry_ts = Clock::get()?.unix_timestamp;
-        pos.realized_pnl = 0;
-        pos.bump = ctx.bumps.position;
-
-        let user = &mut ctx.accounts.user_profile;
-        user.open_positions = user.open_positions.saturating_add(1);
-
-        emit!(PositionOpened {
-            owner: pos.owner,
-            market: pos.market,
-            size,
-            collateral_locked,
-            ts: pos.entry_ts,
-        });
-        Ok(())
-    }
-
-    pub fn adjust_position(
-        ctx: Context<AdjustPosition>,
-        delta_size: i64,
-        delta_collateral: i64,
-    ) -> Result<()> {
-        let pos = &mut ctx.accounts.position;
-        // size is u64; apply delta with saturation and conservative rounding
-        let new_size = if delta_size >= 0 {
-            pos.size.saturating_add(delta_size as u64)
-        } else {
-            pos.size.saturating_sub(delta_size.unsigned_abs())
-        };
-        pos.size = new_size;
-
-        let new_collateral = if delta_collateral >= 0 {
-            pos.collateral_locked.saturating_add(delta_collateral as u64)
-        } else {
-            pos.collateral_locked.saturating_sub(delta_collateral.unsigned_abs())
-        };
-        pos.collateral_locked = new_collateral;
-
-        emit!(PositionAdjusted {
-            owner: pos.owner,
-            market: pos.market,
-            delta_size,
-            delta_collateral,
-            ts: Clock::get()?.unix_timestamp,
-        });
-        Ok(())
-    }
-
-    pub fn close_position(ctx: Context<ClosePosition>) -> Result<()> {
-        let pos = &mut ctx.accounts.position;
-        let user = &mut ctx.accounts.user_profile;
-        if user.open_positions > 0 { user.open_positions -= 1; }
-
-        emit!(PositionClosed {
-            owner: pos.owner,
-            market: pos.market,
-            size: pos.size,
-            collateral_locked: pos.collateral_locked,
-            realized_pnl: pos.realized_pnl,
-            ts: Clock::get()?.unix_timestamp,
-        });
-        Ok(())
-    }
-
-    // ====== Liquidity (Pool counters only; token transfers added later) ======
-
-    pub fn add_liquidity(ctx: Context<AddLiquidity>, amount: u64) -> Result<()> {
-        let lp = &mut ctx.accounts.liquidity_pool;
-        lp.total_liquidity = lp.total_liquidity.saturating_add(amount);
-        emit!(LiquidityAdded { market: lp.market, provider: ctx.accounts.provider.key(), amount, ts: Clock::get()?.unix_timestamp });
-        Ok(())
-    }
-
-    pub fn remove_liquidity(ctx: Context<RemoveLiquidity>, amount: u64) -> Result<()> {
-        let lp = &mut ctx.accounts.liquidity_pool;
-        require!(lp.total_liquidity >= amount, ErrorCode::MarketNotActive); // reuse error for brevity
-        lp.total_liquidity -= amount;
-        emit!(LiquidityRemoved { market: lp.market, provider: ctx.accounts.provider.key(), amount, ts: Clock::get()?.unix_timestamp });
-        Ok(())
-    }
-
-    // ====== pm-AMM (Scaffold) ======
-
-    pub fn init_pmamm(
-        ctx: Context<InitPmAmm>,
-        l0: u64,
-        dynamic_on: bool,
-        fee_bps: u16,
-        expiry_ts: i64,
-    ) -> Result<()> {
-        let pool = &mut ctx.accounts.pmamm_pool;
-        pool.market = ctx.accounts.market.key();
-        // Initialize virtual-like reserves split by l0 to avoid division by zero
-        pool.x = l0 / 2;
-        pool.y = l0 - pool.x;
-        pool.l0 = l0;
-        pool.dynamic_on = if dynamic_on { 1 } else { 0 };
-        pool.fee_bps = fee_bps;
-        pool.expiry_ts = expiry_ts;
-        pool.bump = ctx.bumps.pmamm_pool;
-
-        emit!(PmAmmInitialized { 
-            market: pool.market, 
-            l0, 
-            dynamic_on: pool.dynamic_on, 
-            fee_bps, 
-            expiry_ts, 
-            ts: Clock::get()?.unix_timestamp 
-        });
-        msg!(
-            "IDX:PmAmmInitialized|market={}|l0={}|dynamic_on={}|fee_bps={}|expiry_ts={}|ts={}",
-            pool.market,
-            l0,
-            pool.dynamic_on,
-            fee_bps,
-            expiry_ts,
-            Clock::get()?.unix_timestamp
-        );
-        Ok(())
-    }
-
-    pub fn trade_pmamm(
-        ctx: Context<TradePmAmm>,
-        side: u8,
-        size: u64,
-    ) -> Result<()> {
-        require!(size > 0, ErrorCode::MarketNotActive); // reuse
-        let pool = &mut ctx.accounts.pmamm_pool;
-        let now = Clock::get()?.unix_timestamp;
-        let l_eff = effective_liquidity(pool.l0, pool.dynamic_on, pool.expiry_ts, now);
-
-        // Virtual reserves (prevent zero and control slippage)
-        let mut vx = pool.x as f64 + l_eff / 2.0;
-        let mut vy = pool.y as f64 + l_eff / 2.0;
-        // Constant product invariant
-        let k = vx * vy;
-
-        let s = size as f64;
-        require!(now < pool.expiry_ts, ErrorCode::MarketNotActive);
-        let delta_y: f64;
-        if side == 0 {
-            // Buy X: remove X from pool, pay Y to pool
-            let max_buy = (vx - 1.0).max(0.0); // leave 1 unit to avoid div-by-zero
-            let buy = s.min(max_buy);
-            let new_vx = (vx - buy).max(1.0);
-            let new_vy = k / new_vx;
-            delta_y = (new_vy - vy).max(0.0);
-            // vx = new_vx; // Not used after
-            // vy = new_vy; // Not used after
-            // Fees accrue to pool; buyer pays delta_y + fee
-            let fee = (delta_y * (pool.fee_bps as f64) / 10_000.0).max(0.0);
-            let add_y = (delta_y + fee).max(0.0) as u64;
-            pool.x = pool.x.saturating_sub(buy as u64);
-            pool.y = pool.y.saturating_add(add_y);
-            let avg_price = if s > 0.0 { (delta_y + fee) / s } else { 0.0 };
-            let price_bps = (avg_price * 10_000.0) as u64;
-            emit!(TradeExecuted {
-                market: ctx.accounts.market.key(),
-                taker: ctx.accounts.taker.key(),
-                side,
-                price_bps,
-                size,
-                ts: now,
-            });
-            msg!(
-                "IDX:TradeExecuted|market={}|taker={}|side={}|price_bps={}|size={}|ts={}",
-                ctx.accounts.market.key(),
-                ctx.accounts.taker.key(),
-                side,
-                price_bps,
-                size,
-                now
-            );
-        } else {
-            // Sell X: add X to pool, receive Y from pool
-            let new_vx = vx + s;
-            let new_vy = (k / new_vx).max(0.0);
-            delta_y = (vy - new_vy).max(0.0);
-            // vx = new_vx; // Not used after
-            // vy = new_vy; // Not used after
-            // Seller receives delta_y - fee; pool keeps fee
-            let fee = (delta_y * (pool.fee_bps as f64) / 10_000.0).max(0.0);
-            let payout = (delta_y - fee).max(0.0) as u64;
-            pool.x = pool.x.saturating_add(s as u64);
-            pool.y = pool.y.saturating_sub(payout);
-            let avg_price = if s > 0.0 { (delta_y - fee).max(0.0) / s } else { 0.0 };
-            let price_bps = (avg_price * 10_000.0) as u64;
-            emit!(TradeExecuted {
-                market: ctx.accounts.market.key(),
-                taker: ctx.accounts.taker.key(),
-                side,
-                price_bps,
-                size,
-                ts: now,
-            });
-            msg!(
-                "IDX:TradeExecuted|market={}|taker={}|side={}|price_bps={}|size={}|ts={}",
-                ctx.accounts.market.key(),
-                ctx.accounts.taker.key(),
-                side,
-                price_bps,
-                size,
-                now
-            );
-        }
-        Ok(())
-    }
-}
+// Anchor's generated IDL instructions still call the deprecated AccountInfo::realloc. The
+// `#[program]` module lives in its own file so the allow covers only it and its expansion.
+#[allow(deprecated)]
+mod instructions;
+pub use instructions::*;
 
 // Accounts
 #[derive(Accounts)]
 pub struct InitRegistry<'info> {
     #[account(
         init,
-        seeds = [b"registry", authority.key().as_ref()],
+        seeds = [b"registry"],
         bump,
         payer = authority,
         space = 8 + Registry::SIZE,
@@ -485,7 +48,7 @@ pub struct InitRegistry<'info> {
 pub struct UpsertStrategy<'info> {
     #[account(
         mut,
-        seeds = [b"registry", authority.key().as_ref()],
+        seeds = [b"registry"],
         bump = registry.bump,
     )]
     pub registry: Account<'info, Registry>,
@@ -506,7 +69,7 @@ pub struct UpsertStrategy<'info> {
 #[instruction(strategy_key: [u8; 32])]
 pub struct ExecuteStrategy<'info> {
     #[account(
-        seeds = [b"registry", registry.authority.as_ref()],
+        seeds = [b"registry"],
         bump = registry.bump,
     )]
     pub registry: Account<'info, Registry>,
@@ -555,40 +118,159 @@ pub enum ErrorCode {
     #[msg("Strategy key mismatch")] StrategyKeyMismatch,
     #[msg("Registry mismatch")] RegistryMismatch,
     #[msg("Market not active")] MarketNotActive,
+    #[msg("Math overflow")] MathOverflow,
+    #[msg("Invalid decimals")] InvalidDecimals,
+    #[msg("Account already migrated")] AlreadyMigrated,
+    #[msg("Unexpected account layout")] InvalidAccountLayout,
+    #[msg("Invalid market parameters")] InvalidMarketParams,
+    #[msg("Unsupported distribution type")] UnsupportedDistribution,
+    #[msg("Sigma below market minimum")] SigmaBelowMin,
+    #[msg("Slippage limit exceeded")] SlippageExceeded,
+    #[msg("Instruction not supported for this market type")] WrongMarketType,
+    #[msg("Invalid mixture components")] InvalidMixture,
+    #[msg("Histogram grid exceeds the bin limit")] TooManyBins,
+    #[msg("Invalid histogram mass shift")] InvalidHistogramShift,
+    #[msg("Market not resolved")] MarketNotResolved,
+    #[msg("Position already settled")] AlreadySettled,
+    #[msg("Unsupported scoring rule")] UnsupportedScoringRule,
+    #[msg("Market state account required for this market type")] MissingMarketState,
+    #[msg("Side must be buy or sell")] InvalidSide,
+    #[msg("Order would cross the spread")] OrderWouldCross,
+    #[msg("Order book is full")] BookFull,
+    #[msg("No liquidity within the price limit")] NoLiquidityWithinLimit,
+    #[msg("Order not found on the book")] OrderNotFound,
+    #[msg("Signer does not own this order")] NotOrderOwner,
+    #[msg("Good-till-time orders need an expiry in the future")] InvalidExpiry,
+    #[msg("Fill-or-kill order cannot be filled in full")] FillOrKillUnfilled,
+    #[msg("Remaining accounts must be distinct open orders accounts of this market")] InvalidOpenOrders,
+    #[msg("Price must be in (0, 10000] bps")] PriceOutOfRange,
+    #[msg("Size must be positive")] ZeroSize,
+    #[msg("Market is closed for trading")] MarketClosed,
+    #[msg("Collateral mint uses an unsupported Token-2022 extension")] UnsupportedMint,
+    #[msg("Vault received less collateral than required")] CollateralShortfall,
+    #[msg("Vault holds less collateral than requested")] InsufficientVaultBalance,
+    #[msg("Amount is too small to mint or redeem any LP shares")] ZeroShares,
+    #[msg("Not enough liquidity in the pool")] InsufficientLiquidity,
+    #[msg("Liquidity is locked by the deposit cooldown or the approaching resolution")] LiquidityLocked,
+    #[msg("The pm-AMM has handed off to the order book")] AmmHandedOff,
+    #[msg("Position holds fewer outcome shares than requested")] InsufficientShares,
+    #[msg("pm-AMM reserves do not reconcile with the vault")] PoolOutOfBalance,
+    #[msg("The pm-AMM's price bound would only partly fill the trade")] PartialFill,
+    #[msg("Market can't be resolved before its resolution time")] ResolutionTooEarly,
+    #[msg("Outcome is outside the market's outcome range")] OutcomeOutOfRange,
 }
 
 // ====== Distributional Markets: Params, Accounts, Contexts, Events ======
 
+/// Current `Market` layout version; bump when fields move and extend `migrate_market`.
+pub const MARKET_VERSION: u8 = 1;
+/// Upper bound on `Market.decimals` so scaled values and their fixed-point forms stay in range.
+pub const MAX_DECIMALS: u8 = 12;
+/// Spare bytes at the end of `Market` so new fields don't force a realloc.
+pub const MARKET_RESERVED: usize = 85;
+
+/// What a market's belief is and which trade instruction moves it.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MarketType {
+    /// One (mu, sigma) in the market's `dist_type` family.
+    Distributional,
+    /// A weighted Gaussian mixture held in the market's `MixtureState`.
+    Mixture,
+    /// A probability vector over the `step` grid held in the market's `HistogramState`.
+    Histogram,
+}
+
+impl TryFrom<u8> for MarketType {
+    type Error = Error;
+
+    fn try_from(value: u8) -> Result<MarketType> {
+        match value {
+            0 => Ok(MarketType::Distributional),
+            1 => Ok(MarketType::Mixture),
+            2 => Ok(MarketType::Histogram),
+            _ => err!(ErrorCode::InvalidMarketParams),
+        }
+    }
+}
+
+/// Lifecycle of a market: `Active` until `close_market` or `resolve_market` once its
+/// `resolution_time` has passed. Only `Active` markets before their `resolution_time` trade.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MarketStatus {
+    Active,
+    /// Trading has stopped; the market waits for `resolve_market`.
+    Closed,
+    Resolved,
+}
+
+impl TryFrom<u8> for MarketStatus {
+    type Error = Error;
+
+    fn try_from(value: u8) -> Result<MarketStatus> {
+        match value {
+            0 => Ok(MarketStatus::Active),
+            1 => Ok(MarketStatus::Closed),
+            2 => Ok(MarketStatus::Resolved),
+            _ => err!(ErrorCode::InvalidAccountLayout),
+        }
+    }
+}
+/// Mixture weights are parts per million and must sum to exactly this.
+pub const MIXTURE_WEIGHT_SCALE: u32 = 1_000_000;
+/// Histogram bin masses are parts per billion and always sum to exactly this.
+pub const HISTOGRAM_MASS_SCALE: u32 = 1_000_000_000;
+/// Upper bound on mass moves per `trade_histogram`.
+pub const MAX_HISTOGRAM_SHIFTS: usize = 16;
+/// `MarketQuery.prob_between` is parts per billion.
+pub const QUERY_PROB_SCALE: u64 = 1_000_000_000;
+
+// Outcome-space values (outcome_min/max, mu, sigma, sigma_min, step) are integers scaled by
+// 10^decimals, e.g. decimals = 2 stores 50_000.25 as 5_000_025.
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 pub struct MarketParams {
     pub slug: [u8; 32],
-    pub outcome_min: f64,
-    pub outcome_max: f64,
+    pub outcome_min: i64,
+    pub outcome_max: i64,
     pub unit: [u8; 12],
+    pub decimals: u8,
+    pub market_type: MarketType,
+    /// One of `math::DIST_*`.
     pub dist_type: u8,
-    pub mu: f64,
-    pub sigma: f64,
-    pub sigma_min: f64,
-    pub step: f64,
+    pub mu: i64,
+    pub sigma: i64,
+    pub sigma_min: i64,
+    pub step: i64,
     pub resolution_time: i64,
     pub oracle_config: [u8; 64],
     pub fee_bps_platform: u16,
     pub fee_bps_creator: u16,
+    /// L2 norm k of the market's scaled pdf, in collateral base units.
+    pub l2_norm: u64,
+    /// Family shape parameter in market decimals (Student-t degrees of freedom; unused otherwise).
+    pub dist_shape: i64,
+    /// One of `scoring::SCORING_*`; decides how positions are paid at resolution.
+    pub scoring_rule: u8,
+    /// Outcome in market decimals at or above which the book's and the pm-AMM's YES shares pay.
+    pub strike: i64,
+    /// LP fee on book fills; the pm-AMM charges its pool's `fee_bps` instead.
+    pub fee_bps_lp: u16,
 }
 
 #[account]
 pub struct Market {
+    pub version: u8,
     pub authority: Pubkey,
     pub slug: [u8; 32],
-    pub market_type: u8,
-    pub outcome_min: f64,
-    pub outcome_max: f64,
+    pub market_type: MarketType,
+    pub decimals: u8,
+    pub outcome_min: i64,
+    pub outcome_max: i64,
     pub unit: [u8; 12],
     pub dist_type: u8,
-    pub mu: f64,
-    pub sigma: f64,
-    pub sigma_min: f64,
-    pub step: f64,
+    pub mu: i64,
+    pub sigma: i64,
+    pub sigma_min: i64,
+    pub step: i64,
     pub resolution_time: i64,
     pub oracle_config: [u8; 64],
     pub fee_bps_platform: u16,
@@ -596,99 +278,1502 @@ pub struct Market {
     pub liquidity_pool: Pubkey,
     pub order_book: Pubkey,
     pub collateral_vault: Pubkey,
-    pub status: u8,
+    pub status: MarketStatus,
     pub bump: u8,
+    pub l2_norm: u64,
+    pub dist_shape: i64,
+    /// Outcome in market decimals, set by `resolve_market`.
+    pub resolved_outcome: i64,
+    pub scoring_rule: u8,
+    /// YES shares (book fills and pm-AMM X) pay one unit of collateral if `resolved_outcome >=
+    /// strike`, NO shares otherwise.
+    pub strike: i64,
+    /// See `MarketParams::fee_bps_lp`.
+    pub fee_bps_lp: u16,
+    /// Collateral owed to distribution, mixture and histogram positions: the opening subsidy and
+    /// `fund_vault` top-ups plus every trade's collateral, less what has been paid out. Their
+    /// claims draw on this alone, never on the book, LP, pm-AMM or fee balances in the same vault.
+    pub distribution_balance: u64,
+    pub reserved: [u8; MARKET_RESERVED],
 }
 
 impl Market {
-    pub const SIZE: usize = 32 + 32 + 1 + 8 + 8 + 12 + 1 + 8 + 8 + 8 + 8 + 8 + 64 + 2 + 2 + 32 + 32 + 32 + 1 + 1;
+    pub const SIZE: usize = 1 + 32 + 32 + 1 + 1 + 8 + 8 + 12 + 1 + 8 + 8 + 8 + 8 + 8 + 64 + 2 + 2 + 32 + 32 + 32 + 1 + 1 + 8 + 8 + 8 + 1 + 8 + 2 + 8 + MARKET_RESERVED;
+
+    /// Lifts a value stored with this market's decimals into fixed point.
+    pub fn fixed(&self, value: i64) -> Result<Fixed> {
+        Fixed::from_scaled(value, self.decimals).ok_or_else(|| error!(ErrorCode::MathOverflow))
+    }
+
+    /// The checks `create_market` puts its parameters through, also run on migrated markets.
+    pub fn validate(&self) -> Result<()> {
+        require!(self.decimals <= MAX_DECIMALS, ErrorCode::InvalidDecimals);
+        require!(self.dist_type <= DIST_BETA, ErrorCode::UnsupportedDistribution);
+        require!(self.scoring_rule <= SCORING_CRPS, ErrorCode::UnsupportedScoringRule);
+        // Mixture and histogram positions settle on the spherical (L2) payoff only
+        require!(
+            self.scoring_rule == SCORING_SPHERICAL || self.market_type == MarketType::Distributional,
+            ErrorCode::UnsupportedScoringRule
+        );
+        require!(self.outcome_min < self.outcome_max && self.step > 0, ErrorCode::InvalidMarketParams);
+        require!(self.sigma_min > 0 && self.sigma >= self.sigma_min, ErrorCode::InvalidMarketParams);
+        require!(
+            self.fee_bps_platform as u32 + self.fee_bps_creator as u32 + self.fee_bps_lp as u32 <= 10_000,
+            ErrorCode::InvalidMarketParams
+        );
+        require!((self.outcome_min..=self.outcome_max).contains(&self.strike), ErrorCode::InvalidMarketParams);
+        // Reject parameters the selected family can't represent (e.g. beta moments out of range)
+        self.distribution(self.mu, self.sigma)?
+            .l2_norm()
+            .ok_or(ErrorCode::InvalidMarketParams)?;
+        if self.market_type == MarketType::Histogram {
+            self.histogram_bins()?;
+        }
+        Ok(())
+    }
+
+    /// Platform and creator fees on a trade of `notional` (price_bps * size summed, i.e.
+    /// collateral * 10_000), each rounded up and together never more than `cap`.
+    pub fn trade_fees(&self, notional: u128, cap: u64) -> Result<(u64, u64)> {
+        let fee = |bps: u16| u64::try_from((notional * bps as u128).div_ceil(100_000_000)).map_err(|_| ErrorCode::MathOverflow);
+        let platform = fee(self.fee_bps_platform)?.min(cap);
+        let creator = fee(self.fee_bps_creator)?.min(cap - platform);
+        Ok((platform, creator))
+    }
+
+    /// LP fee on a book fill of `notional` (as for `trade_fees`), rounded up, paid to its maker.
+    pub fn book_lp_fee(&self, notional: u128) -> Result<u64> {
+        u64::try_from((notional * self.fee_bps_lp as u128).div_ceil(100_000_000)).map_err(|_| error!(ErrorCode::MathOverflow))
+    }
+
+    /// True if the resolved outcome pays the YES side of the book and the pm-AMM.
+    pub fn yes_wins(&self) -> bool { self.resolved_outcome >= self.strike }
+
+    /// This market's distribution family evaluated at (mu, sigma), both in market decimals.
+    pub fn distribution(&self, mu: i64, sigma: i64) -> Result<Distribution> {
+        Distribution::from_params(
+            self.dist_type,
+            self.fixed(mu)?,
+            self.fixed(sigma)?,
+            self.fixed(self.dist_shape)?,
+            self.fixed(self.outcome_min)?,
+            self.fixed(self.outcome_max)?,
+        )
+        .ok_or_else(|| error!(ErrorCode::InvalidMarketParams))
+    }
+
+    /// Bin count of a histogram market: the `step` grid over [outcome_min, outcome_max], which
+    /// must divide the range exactly.
+    pub fn histogram_bins(&self) -> Result<usize> {
+        let span = self.outcome_max.checked_sub(self.outcome_min).ok_or(ErrorCode::MathOverflow)?;
+        require!(self.step > 0 && span > 0 && span % self.step == 0, ErrorCode::InvalidMarketParams);
+        let bins = span / self.step;
+        require!(bins <= MAX_HISTOGRAM_BINS as i64, ErrorCode::TooManyBins);
+        Ok(bins as usize)
+    }
+
+    /// Validates mixture components against this market and lifts them into fixed point.
+    pub fn mixture(&self, components: &[MixtureComponent]) -> Result<Vec<GaussComponent>> {
+        require!(!components.is_empty() && components.len() <= MAX_MIXTURE_COMPONENTS, ErrorCode::InvalidMixture);
+        let total: u64 = components.iter().map(|c| c.weight_ppm as u64).sum();
+        require!(total == MIXTURE_WEIGHT_SCALE as u64, ErrorCode::InvalidMixture);
+        components
+            .iter()
+            .map(|c| {
+                require!(c.weight_ppm > 0, ErrorCode::InvalidMixture);
+                require!(c.sigma >= self.sigma_min && c.sigma > 0, ErrorCode::SigmaBelowMin);
+                Ok(GaussComponent {
+                    weight: Fixed::from_ratio(c.weight_ppm as i128, MIXTURE_WEIGHT_SCALE as i128)
+                        .ok_or(ErrorCode::MathOverflow)?,
+                    mu: self.fixed(c.mu)?,
+                    sigma: self.fixed(c.sigma)?,
+                })
+            })
+            .collect()
+    }
+
+    /// The sharpest belief a trade can move this market to around `y`: its family at
+    /// `sigma_min`, located at y (at ln y for a lognormal, whose mu is log-space).
+    fn sharpest(&self, y: Fixed) -> Option<Distribution> {
+        let mu = if self.dist_type == DIST_LOGNORMAL {
+            if !y.is_positive() { return None; }
+            y.ln()?
+        } else {
+            y
+        };
+        let sigma = Fixed::from_scaled(self.sigma_min, self.decimals)?;
+        let shape = Fixed::from_scaled(self.dist_shape, self.decimals)?;
+        let (lo, hi) = (Fixed::from_scaled(self.outcome_min, self.decimals)?, Fixed::from_scaled(self.outcome_max, self.decimals)?);
+        Distribution::from_params(self.dist_type, mu, sigma, shape, lo, hi)
+    }
+
+    /// Collateral the authority posts when the market opens so distribution payouts are always
+    /// covered: the most the traders can win beyond their own collateral, starting from the
+    /// opening belief. Mixtures and histograms settle on the spherical payoff, which peaks at
+    /// a single `sigma_min` component and at all mass in one bin respectively. Zero for markets
+    /// with no `l2_norm`, which can't trade their distribution.
+    pub fn subsidy(&self) -> Result<u64> {
+        if self.l2_norm == 0 { return Ok(0); }
+        let k = Fixed::from_int(i64::try_from(self.l2_norm).map_err(|_| ErrorCode::MathOverflow)?);
+        let subsidy = match self.market_type {
+            MarketType::Distributional => {
+                let (lo, hi) = (self.fixed(self.outcome_min)?, self.fixed(self.outcome_max)?);
+                let first = self.distribution(self.mu, self.sigma)?;
+                scoring::max_subsidy(self.scoring_rule, k, &first, |y| self.sharpest(y), lo, hi)
+            }
+            // The opening mixture's density can be arbitrarily close to zero away from its modes
+            MarketType::Mixture => {
+                let peak = Distribution::Normal { mu: Fixed::ZERO, sigma: self.fixed(self.sigma_min)? };
+                scoring::spherical_score(&peak, Fixed::ZERO).and_then(|s| k.checked_mul(s))
+            }
+            // k / sqrt(width) for a single full bin, less k / sqrt(bins * width) for the uniform prior
+            MarketType::Histogram => {
+                let width = self.fixed(self.step)?;
+                let all = width.checked_mul_int(self.histogram_bins()? as i64).ok_or(ErrorCode::MathOverflow)?;
+                let one = width.sqrt().and_then(|w| k.checked_div(w));
+                let uniform = all.sqrt().and_then(|w| k.checked_div(w));
+                one.zip(uniform).and_then(|(one, uniform)| one.checked_sub(uniform))
+            }
+        };
+        let subsidy = subsidy.and_then(Fixed::ceil_to_i64).ok_or(ErrorCode::MathOverflow)?;
+        Ok(subsidy as u64)
+    }
+}
+
+/// Return data of `quote_pmamm`, and what `PmAmmPool::fill` settles. A buy pays `amount_in`
+/// collateral for `amount_out` shares, a sale the reverse; the fees are included in the
+/// collateral side. `PmAmmPool::fill` only charges `lp_fee`; `charge` adds the market's.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct PmAmmQuote {
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub lp_fee: u64,
+    pub platform_fee: u64,
+    pub creator_fee: u64,
+}
+
+impl PmAmmQuote {
+    /// `shares` traded for `collateral` on `side`, `lp_fee` included.
+    pub fn new(side: Side, shares: u64, collateral: u64, lp_fee: u64) -> Self {
+        let (amount_in, amount_out) = if side == Side::Buy { (collateral, shares) } else { (shares, collateral) };
+        PmAmmQuote { amount_in, amount_out, lp_fee, platform_fee: 0, creator_fee: 0 }
+    }
+
+    /// The collateral side: paid on a buy, received on a sale.
+    pub fn collateral(&self, side: Side) -> u64 {
+        if side == Side::Buy { self.amount_in } else { self.amount_out }
+    }
+
+    /// Adds `market`'s platform and creator fees on the curve's price, net of the LP fee, to
+    /// what a buyer pays or takes them out of what a seller receives.
+    pub fn charge(mut self, side: Side, market: &Market) -> Result<Self> {
+        if side == Side::Buy {
+            let curve = self.amount_in - self.lp_fee;
+            (self.platform_fee, self.creator_fee) = market.trade_fees(curve as u128 * 10_000, u64::MAX)?;
+            self.amount_in = self.amount_in
+                .checked_add(self.platform_fee + self.creator_fee)
+                .ok_or(ErrorCode::MathOverflow)?;
+        } else {
+            let curve = self.amount_out.checked_add(self.lp_fee).ok_or(ErrorCode::MathOverflow)?;
+            (self.platform_fee, self.creator_fee) = market.trade_fees(curve as u128 * 10_000, self.amount_out)?;
+            self.amount_out -= self.platform_fee + self.creator_fee;
+        }
+        Ok(self)
+    }
+}
+
+/// Return data of `query_liquidity`. `liquidity` is rounded down to a whole unit.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct LiquidityQuery {
+    pub liquidity: u64,
+    pub quoting: bool,
+}
+
+/// Return data of `query_market`. Percentiles are in market decimals.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct MarketQuery {
+    /// P(a < X < b) in parts per billion.
+    pub prob_between: u64,
+    pub p05: i64,
+    pub p50: i64,
+    pub p95: i64,
+}
+
+/// Settles a pm-AMM leg `PmAmmPool::swap` priced: a buyer pays `amount` into the vault and
+/// is minted `traded` shares, a seller has `traded` shares burnt and is paid `amount`.
+#[allow(clippy::too_many_arguments)]
+fn settle_pmamm<'info>(
+    side: Side,
+    traded: u64,
+    amount: u64,
+    position: &mut Account<'info, PmAmmPosition>,
+    token_program: &Interface<'info, TokenInterface>,
+    mint: &InterfaceAccount<'info, Mint>,
+    taker_tokens: &InterfaceAccount<'info, TokenAccount>,
+    vault: &mut Account<'info, CollateralVault>,
+    vault_tokens: &mut InterfaceAccount<'info, TokenAccount>,
+    taker: &Signer<'info>,
+) -> Result<()> {
+    if side == Side::Buy {
+        custody::deposit(token_program, mint, taker_tokens, vault, vault_tokens, taker, amount)?;
+        position.shares = position.shares.checked_add(traded).ok_or(ErrorCode::MathOverflow)?;
+    } else {
+        position.shares = position.shares.checked_sub(traded).ok_or(ErrorCode::InsufficientShares)?;
+        custody::withdraw(token_program, mint, vault, vault_tokens, taker_tokens, amount)?;
+    }
+    Ok(())
+}
+
+/// Freezes or thaws a provider's LP share account, signed by the pool PDA as the LP mint's
+/// freeze authority. Share accounts stay frozen between deposits and withdrawals so shares
+/// can't be transferred to a position whose cooldown has already run.
+fn freeze_shares<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    lp_mint: &InterfaceAccount<'info, Mint>,
+    shares: &InterfaceAccount<'info, TokenAccount>,
+    pool: &Account<'info, LiquidityPool>,
+    frozen: bool,
+) -> Result<()> {
+    let seeds: &[&[u8]] = &[b"pool", pool.market.as_ref(), &[pool.bump]];
+    let (account, mint, authority) = (shares.to_account_info(), lp_mint.to_account_info(), pool.to_account_info());
+    let program = token_program.to_account_info();
+    if frozen {
+        token_interface::freeze_account(CpiContext::new_with_signer(program, FreezeAccount { account, mint, authority }, &[seeds]))
+    } else {
+        token_interface::thaw_account(CpiContext::new_with_signer(program, ThawAccount { account, mint, authority }, &[seeds]))
+    }
+}
+
+/// What a pm-AMM trade can move in the market's vault: the pool's collateral, its LP fee
+/// included, and the platform and creator fees.
+fn pmamm_owed(pool: &PmAmmPool, fees: &MarketFees) -> u128 {
+    pool.held() + fees.held()
+}
+
+/// Checks a pm-AMM trade against the vault: what the pool and the fee recipients are owed
+/// moved by no more than the vault's ledger did since `before` (vault balance, `pmamm_owed`),
+/// and the pool's collateral still backs every outcome token.
+fn reconcile_pmamm(pool: &PmAmmPool, fees: &MarketFees, vault: &CollateralVault, before: (u64, u128)) -> Result<()> {
+    let vault_in = vault.balance as i128 - before.0 as i128;
+    let owed_in = pmamm_owed(pool, fees) as i128 - before.1 as i128;
+    require!(owed_in <= vault_in && pool.backed(), ErrorCode::PoolOutOfBalance);
+    Ok(())
+}
+
+/// Logs the fees one trade was charged, as (platform, creator, LP).
+fn emit_fees(market: Pubkey, taker: Pubkey, (platform_fee, creator_fee, lp_fee): (u64, u64, u64), now: i64) {
+    emit!(FeesCharged { market, taker, platform_fee, creator_fee, lp_fee, ts: now });
+    msg!(
+        "IDX:FeesCharged|market={}|taker={}|platform_fee={}|creator_fee={}|lp_fee={}|ts={}",
+        market,
+        taker,
+        platform_fee,
+        creator_fee,
+        lp_fee,
+        now
+    );
+}
+
+/// Pays `recipient`'s unclaimed fees out of the vault. The signer has been checked by the caller.
+fn claim_fees(accounts: &mut ClaimFees, recipient: FeeRecipient) -> Result<()> {
+    let fees = &mut accounts.market_fees;
+    let amount = match recipient {
+        FeeRecipient::Platform => std::mem::take(&mut fees.platform_fees),
+        FeeRecipient::Creator => std::mem::take(&mut fees.creator_fees),
+    };
+    custody::withdraw(
+        &accounts.token_program,
+        &accounts.mint,
+        &mut accounts.collateral_vault,
+        &mut accounts.vault_tokens,
+        &accounts.recipient_tokens,
+        amount,
+    )?;
+    let (market, claimant) = (accounts.market.key(), accounts.claimant.key());
+    let now = Clock::get()?.unix_timestamp;
+    emit!(FeesClaimed { market, recipient, claimant, amount, ts: now });
+    msg!(
+        "IDX:FeesClaimed|market={}|recipient={}|claimant={}|amount={}|ts={}",
+        market,
+        recipient as u8,
+        claimant,
+        amount,
+        now
+    );
+    Ok(())
+}
+
+/// Removes `order_id` from `side` of the book, frees the collateral behind its remaining size
+/// and refreshes the best price cache. Ownership has been checked by the caller.
+fn cancel_resting(accounts: &mut CancelOrder, side: Side, order_id: u64) -> Result<()> {
+    let order = {
+        let mut book = if side == Side::Buy { accounts.bids.load_mut()? } else { accounts.asks.load_mut()? };
+        let order = book.remove(order_id);
+        accounts.order_book.set_best(side, book.best_price());
+        order
+    }
+    .ok_or(ErrorCode::OrderNotFound)?;
+    let released = accounts.open_orders.release(side, &order)?;
+
+    let market = accounts.market.key();
+    let now = Clock::get()?.unix_timestamp;
+    emit!(OrderCancelled { order_id, market, owner: order.owner, side, size: order.size, released, ts: now });
+    msg!(
+        "IDX:OrderCancelled|market={}|order_id={}|owner={}|side={}|size={}|released={}|ts={}",
+        market,
+        order_id,
+        order.owner,
+        side as u8,
+        order.size,
+        released,
+        now
+    );
+    Ok(())
+}
+
+/// Emits a `Fill` per maker order the taker met. Returns the filled size and its notional,
+/// the sum of `price_bps * size`.
+fn emit_fills(market: Pubkey, taker: Pubkey, side: Side, fills: &[BookFill], now: i64) -> (u64, u128) {
+    let (mut size, mut notional) = (0u64, 0u128);
+    for fill in fills {
+        emit!(Fill {
+            market,
+            maker_order_id: fill.order_id,
+            maker: fill.maker,
+            taker,
+            taker_side: side,
+            price_bps: fill.price_bps,
+            size: fill.size,
+            ts: now,
+        });
+        msg!(
+            "IDX:Fill|market={}|maker_order_id={}|maker={}|taker={}|side={}|price_bps={}|size={}|ts={}",
+            market,
+            fill.order_id,
+            fill.maker,
+            taker,
+            side as u8,
+            fill.price_bps,
+            fill.size,
+            now
+        );
+        size += fill.size;
+        notional += fill.price_bps as u128 * fill.size as u128;
+    }
+    (size, notional)
+}
+
+/// Settles every fill between its maker's `OpenOrders` and the taker's `taker_oo`, crediting
+/// each maker with the LP fee on its fill, and returns the collateral the taker owes before
+/// fees and the LP fees it owes on top. A maker is the taker itself or one of `makers`; a fill
+/// against anyone else fails, so takers pass every maker they may meet.
+fn settle_fills(
+    market: &Market,
+    taker_oo: &mut OpenOrders,
+    makers: &mut [Account<OpenOrders>],
+    side: Side,
+    fills: &[BookFill],
+) -> Result<(u64, u64)> {
+    let (mut owed, mut lp) = (0u64, 0u64);
+    for fill in fills {
+        let fee = market.book_lp_fee(fill.price_bps as u128 * fill.size as u128)?;
+        let maker: &mut OpenOrders = if fill.maker == taker_oo.owner {
+            taker_oo
+        } else {
+            makers.iter_mut().find(|m| m.owner == fill.maker).ok_or(ErrorCode::InvalidOpenOrders)?
+        };
+        owed = owed.checked_add(maker.fill(side.opposite(), fill)?).ok_or(ErrorCode::MathOverflow)?;
+        maker.earn(fee)?;
+        lp = lp.checked_add(fee).ok_or(ErrorCode::MathOverflow)?;
+        taker_oo.credit(side, fill.size)?;
+    }
+    Ok((owed, lp))
+}
+
+/// `OpenOrders` passed as remaining accounts, so fills against their owners can settle and
+/// their expired orders can be pruned and refunded. They must be writable, distinct, of this
+/// market and not `named`, an open orders account the instruction already holds; the caller
+/// writes them back with `exit`.
+fn remaining_open_orders<'info>(
+    market: Pubkey,
+    accounts: &'info [AccountInfo<'info>],
+    named: Option<Pubkey>,
+) -> Result<Vec<Account<'info, OpenOrders>>> {
+    let mut out: Vec<Account<'info, OpenOrders>> = Vec::with_capacity(accounts.len());
+    for info in accounts {
+        let fresh = Some(info.key()) != named && out.iter().all(|oo| oo.key() != info.key());
+        require!(info.is_writable && fresh, ErrorCode::InvalidOpenOrders);
+        let oo = Account::<OpenOrders>::try_from(info)?;
+        require_keys_eq!(oo.market, market, ErrorCode::InvalidOpenOrders);
+        out.push(oo);
+    }
+    Ok(out)
+}
+
+/// Frees the collateral of an expired order pruned from `side` into its owner's `oo`.
+fn refund_expired(oo: &mut OpenOrders, market: Pubkey, side: Side, order: &OrderNode, now: i64) -> Result<()> {
+    let released = oo.release(side, order)?;
+    emit!(OrderExpired { order_id: order.order_id, market, owner: order.owner, side, size: order.size, released, ts: now });
+    msg!(
+        "IDX:OrderExpired|market={}|order_id={}|owner={}|side={}|size={}|released={}|ts={}",
+        market,
+        order.order_id,
+        order.owner,
+        side as u8,
+        order.size,
+        released,
+        now
+    );
+    Ok(())
+}
+
+fn summarize_belief<C, Q>(market: &Market, a: i64, b: i64, cdf: C, quantile: Q) -> Result<MarketQuery>
+where
+    C: Fn(Fixed) -> Option<Fixed>,
+    Q: Fn(Fixed) -> Option<Fixed>,
+{
+    let overflow = || error!(ErrorCode::MathOverflow);
+    let mass = cdf(market.fixed(b)?)
+        .zip(cdf(market.fixed(a)?))
+        .and_then(|(fb, fa)| fb.checked_sub(fa))
+        .ok_or_else(overflow)?
+        .max(Fixed::ZERO)
+        .min(Fixed::ONE);
+    let prob_between = mass.checked_mul_int(QUERY_PROB_SCALE as i64).and_then(|v| v.to_scaled_round(0)).ok_or_else(overflow)? as u64;
+    let percentile = |pct: i128| {
+        Fixed::from_ratio(pct, 100)
+            .and_then(&quantile)
+            .and_then(|x| x.to_scaled_round(market.decimals))
+            .ok_or_else(overflow)
+    };
+    Ok(MarketQuery { prob_between, p05: percentile(5)?, p50: percentile(50)?, p95: percentile(95)? })
+}
+
+/// Pre-v1 `Market` layout. The f64 fields are read as raw IEEE-754 bits so the migration
+/// never touches floating point.
+#[derive(AnchorDeserialize)]
+struct MarketV0 {
+    authority: Pubkey,
+    slug: [u8; 32],
+    market_type: u8,
+    outcome_min: u64,
+    outcome_max: u64,
+    unit: [u8; 12],
+    dist_type: u8,
+    mu: u64,
+    sigma: u64,
+    sigma_min: u64,
+    step: u64,
+    resolution_time: i64,
+    oracle_config: [u8; 64],
+    fee_bps_platform: u16,
+    fee_bps_creator: u16,
+    liquidity_pool: Pubkey,
+    order_book: Pubkey,
+    collateral_vault: Pubkey,
+    status: u8,
+    bump: u8,
+}
+
+impl MarketV0 { const SIZE: usize = 32 + 32 + 1 + 8 + 8 + 12 + 1 + 8 + 8 + 8 + 8 + 8 + 64 + 2 + 2 + 32 + 32 + 32 + 1 + 1; }
+
+/// Pre-v1 sizes of the other accounts that grew. None of them held collateral: the pool's
+/// `total_liquidity` and the pm-AMM's reserves were counters with no tokens behind them.
+const LIQUIDITY_POOL_V0_SIZE: usize = 32 + 32 + 8 + 1;
+const COLLATERAL_VAULT_V0_SIZE: usize = 32 + 1;
+const PMAMM_POOL_V0_SIZE: usize = 32 + 8 + 8 + 8 + 1 + 2 + 8 + 1;
+
+/// Fails unless `info` holds a `discriminator` account in its pre-v1 layout of `size` bytes.
+fn check_legacy(info: &AccountInfo, discriminator: &[u8], size: usize) -> Result<()> {
+    let data = info.try_borrow_data()?;
+    require!(data.len() >= 8 && &data[..8] == discriminator, ErrorCode::InvalidAccountLayout);
+    require!(data.len() == 8 + size, ErrorCode::AlreadyMigrated);
+    Ok(())
+}
+
+/// Resizes a program account to `len` bytes, topping its rent up from `payer`.
+fn grow<'info>(info: &AccountInfo<'info>, len: usize, payer: &Signer<'info>, system: &Program<'info, System>) -> Result<()> {
+    let rent_due = Rent::get()?.minimum_balance(len).saturating_sub(info.lamports());
+    if rent_due > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system.to_account_info(),
+                system_program::Transfer { from: payer.to_account_info(), to: info.clone() },
+            ),
+            rent_due,
+        )?;
+    }
+    info.resize(len)?;
+    Ok(())
+}
+
+/// `round(value * 10^decimals)` for a raw f64 bit pattern, using integer arithmetic only.
+/// `None` for NaN/inf or results outside i64.
+fn scaled_from_f64_bits(bits: u64, decimals: u8) -> Option<i64> {
+    let negative = bits >> 63 == 1;
+    let biased = ((bits >> 52) & 0x7ff) as i32;
+    let fraction = (bits & ((1u64 << 52) - 1)) as i128;
+    if biased == 0x7ff { return None; }
+    // value = mantissa * 2^exp
+    let (mantissa, exp) = if biased == 0 { (fraction, -1074) } else { (fraction | 1 << 52, biased - 1075) };
+    let scaled = mantissa.checked_mul(10i128.checked_pow(decimals as u32)?)?;
+    let magnitude = if exp >= 0 {
+        if exp > 126 { return None; }
+        scaled.checked_mul(1i128 << exp)?
+    } else if exp < -126 {
+        0
+    } else {
+        let shift = (-exp) as u32;
+        (scaled + (1i128 << (shift - 1))) >> shift
+    };
+    i64::try_from(if negative { -magnitude } else { magnitude }).ok()
 }
 
+/// Collateral that liquidity providers hold in the market's vault, against `lp_mint` shares.
+/// `total_liquidity` is everything the shares are a claim on: deposits, less withdrawals.
+/// `fees_accrued` is kept for the account's layout; trading fees go to whoever posted the
+/// liquidity they were charged on, the book's makers and the pm-AMM's authority.
 #[account]
 pub struct LiquidityPool {
     pub market: Pubkey,
     pub vault: Pubkey,
+    pub lp_mint: Pubkey,
     pub total_liquidity: u64,
+    pub fees_accrued: u64,
     pub bump: u8,
 }
 
-impl LiquidityPool { pub const SIZE: usize = 32 + 32 + 8 + 1; }
+impl LiquidityPool {
+    pub const SIZE: usize = 32 + 32 + 32 + 8 + 8 + 1;
 
-#[account]
-pub struct OrderBook {
-    pub market: Pubkey,
-    pub best_bid: f64,
-    pub best_ask: f64,
-    pub event_counter: u64,
-    pub bump: u8,
+    /// Shares minted for depositing `amount` when `supply` shares exist, rounded down. The
+    /// first deposit into an empty pool mints one share per collateral unit.
+    pub fn shares_for(&self, amount: u64, supply: u64) -> Result<u64> {
+        if supply == 0 {
+            return Ok(amount);
+        }
+        require!(self.total_liquidity > 0, ErrorCode::ZeroShares);
+        let shares = amount as u128 * supply as u128 / self.total_liquidity as u128;
+        u64::try_from(shares).map_err(|_| error!(ErrorCode::MathOverflow))
+    }
+
+    /// Collateral redeemed by burning `shares` of `supply`, rounded down.
+    pub fn amount_for(&self, shares: u64, supply: u64) -> Result<u64> {
+        require!(shares <= supply, ErrorCode::InsufficientLiquidity);
+        let amount = shares as u128 * self.total_liquidity as u128 / supply as u128;
+        u64::try_from(amount).map_err(|_| error!(ErrorCode::MathOverflow))
+    }
 }
 
-impl OrderBook { pub const SIZE: usize = 32 + 8 + 8 + 8 + 1; }
+/// Seconds after a deposit before its shares can be redeemed.
+pub const LP_COOLDOWN_SECS: i64 = 3_600;
+/// Seconds before `resolution_time` from which liquidity stays in until the market resolves.
+pub const LP_RESOLUTION_LOCK_SECS: i64 = 86_400;
 
+/// One provider's liquidity in one market. It carries the cooldown and the resolution lock; the
+/// shares themselves live in the provider's LP token account, which the pool keeps frozen so
+/// they can't move to a position whose cooldown has already run.
 #[account]
-pub struct CollateralVault {
+pub struct LpPosition {
     pub market: Pubkey,
+    pub provider: Pubkey,
+    /// Collateral deposited, less collateral withdrawn, floored at zero.
+    pub deposited: u64,
+    /// LP shares minted through this position, less shares redeemed through it, floored at zero.
+    /// Bookkeeping only: withdrawals are bounded by the LP tokens burned.
+    pub shares: u64,
+    /// Last deposit.
+    pub deposit_ts: i64,
     pub bump: u8,
 }
 
-impl CollateralVault { pub const SIZE: usize = 32 + 1; }
+impl LpPosition {
+    pub const SIZE: usize = 32 + 32 + 8 + 8 + 8 + 1;
+
+    /// Whether the position can withdraw at `now`: its cooldown has passed, and the market has
+    /// either resolved or is still outside the lock window before `resolution_time`.
+    pub fn unlocked(&self, status: MarketStatus, resolution_time: i64, now: i64) -> bool {
+        let cooled = now >= self.deposit_ts.saturating_add(LP_COOLDOWN_SECS);
+        let clear_of_resolution =
+            status == MarketStatus::Resolved || now < resolution_time.saturating_sub(LP_RESOLUTION_LOCK_SECS);
+        cooled && clear_of_resolution
+    }
+}
 
 #[account]
-pub struct PmAmmPool {
+pub struct OrderBook {
     pub market: Pubkey,
-    pub x: u64,
-    pub y: u64,
-    pub l0: u64,
-    pub dynamic_on: u8,
-    pub fee_bps: u16,
-    pub expiry_ts: i64,
+    pub best_bid_bps: u64,
+    pub best_ask_bps: u64,
+    pub event_counter: u64,
     pub bump: u8,
 }
 
-impl PmAmmPool { pub const SIZE: usize = 68; }
+impl OrderBook {
+    pub const SIZE: usize = 32 + 8 + 8 + 8 + 1;
 
-#[account]
-pub struct UserProfile {
-    pub owner: Pubkey,
-    pub open_positions: u32,
-    pub bump: u8,
+    /// Refreshes the cached best price of `side`; zero means that side is empty.
+    pub fn set_best(&mut self, side: Side, best: Option<u64>) {
+        let price = best.unwrap_or(0);
+        if side == Side::Buy { self.best_bid_bps = price } else { self.best_ask_bps = price }
+    }
 }
 
-impl UserProfile { pub const SIZE: usize = 32 + 4 + 1; }
+/// Taker or maker side of an order; the book stores it as `Side as u8`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Side {
+    Buy,
+    Sell,
+}
 
-#[account]
-pub struct Position {
-    pub owner: Pubkey,
-    pub market: Pubkey,
-    pub size: u64,
-    pub collateral_locked: u64,
-    pub entry_ts: i64,
-    pub realized_pnl: i64,
-    pub bump: u8,
+impl Side {
+    pub fn opposite(self) -> Side {
+        match self {
+            Side::Buy => Side::Sell,
+            Side::Sell => Side::Buy,
+        }
+    }
 }
 
-impl Position { pub const SIZE: usize = 32 + 32 + 8 + 8 + 8 + 8 + 1; }
+impl TryFrom<u8> for Side {
+    type Error = Error;
 
-#[account]
-pub struct PendingOrder {
+    fn try_from(value: u8) -> Result<Side> {
+        match value {
+            0 => Ok(Side::Buy),
+            1 => Ok(Side::Sell),
+            _ => err!(ErrorCode::InvalidSide),
+        }
+    }
+}
+
+/// How long a limit order may rest, as passed to `place_limit_order`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimeInForce {
+    /// Good till cancelled: matches what it can and rests the rest.
+    Gtc,
+    /// Good till time: like `Gtc`, but stops matching at its expiry and can then be cranked.
+    Gtt,
+    /// Immediate or cancel: matches what it can and drops the rest.
+    Ioc,
+    /// Fill or kill: fills in full against the book or fails.
+    Fok,
+    /// Rests without taking; fails if it would cross.
+    PostOnly,
+}
+
+/// Resting orders one `BookSide` can hold.
+pub const MAX_BOOK_ORDERS: usize = 96;
+/// Distinct prices one `BookSide` can hold.
+pub const MAX_PRICE_LEVELS: usize = 32;
+/// End of an order queue or of the free list.
+pub const BOOK_NIL: u16 = u16::MAX;
+
+/// One price on a `BookSide`: a FIFO queue of order slots threaded through `OrderNode.next`.
+#[zero_copy]
+pub struct PriceLevel {
+    pub price_bps: u64,
+    /// Sum of the remaining sizes queued here.
+    pub total_size: u64,
+    pub head: u16,
+    pub tail: u16,
+    pub count: u16,
+    pub _padding: [u8; 2],
+}
+
+/// A resting limit order. Free slots reuse `next` to form the free list.
+#[zero_copy]
+pub struct OrderNode {
     pub owner: Pubkey,
-    pub market: Pubkey,
-    pub side: u8,
+    pub order_id: u64,
+    /// Caller-chosen id, so bots can cancel without tracking `order_id`.
+    pub client_order_id: u64,
     pub price_bps: u64,
+    /// Remaining size.
     pub size: u64,
-    pub ts: i64,
-    pub bump: u8,
+    pub placed_ts: i64,
+    pub expiry_ts: i64,
+    pub next: u16,
+    pub _padding: [u8; 6],
 }
 
-impl PendingOrder { pub const SIZE: usize = 32 + 32 + 1 + 8 + 8 + 8 + 1; }
+impl OrderNode {
+    /// Good-till-time orders stop matching at `expiry_ts`; zero never expires.
+    pub fn is_expired(&self, now: i64) -> bool { self.expiry_ts != 0 && now >= self.expiry_ts }
+}
 
-// Contexts
+/// What `execute_market_order` filled, returned as return data. `price_bps` is the blended
+/// average over both legs.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct MarketOrderFill {
+    pub book_size: u64,
+    pub amm_size: u64,
+    pub price_bps: u64,
+}
 
-#[derive(Accounts)]
-#[instruction(params: MarketParams)]
-pub struct CreateMarket<'info> {
-    #[account(
-        init,
+/// What `BookSide::take` matched, and the expired orders it pruned on the way.
+#[derive(Clone, Default)]
+pub struct Take {
+    pub fills: Vec<BookFill>,
+    pub expired: Vec<OrderNode>,
+}
+
+/// A maker order (partly) filled by `BookSide::take`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BookFill {
+    pub maker: Pubkey,
+    pub order_id: u64,
+    pub price_bps: u64,
+    pub size: u64,
+    /// What the maker order still has resting after this fill.
+    pub remaining: u64,
+}
+
+/// One side of a market's order book. `levels[..level_count]` is sorted best price first
+/// (highest for bids, lowest for asks) and each level is served in arrival order.
+#[account(zero_copy)]
+pub struct BookSide {
+    pub market: Pubkey,
+    pub free_head: u16,
+    pub level_count: u16,
+    pub order_count: u16,
+    /// `Side::Buy as u8` for the bid slab, `Side::Sell as u8` for the ask slab.
+    pub side: u8,
+    pub bump: u8,
+    pub levels: [PriceLevel; MAX_PRICE_LEVELS],
+    pub orders: [OrderNode; MAX_BOOK_ORDERS],
+}
+
+impl BookSide {
+    /// Empties the side and threads every order slot onto the free list.
+    pub fn init(&mut self, market: Pubkey, side: Side, bump: u8) {
+        self.market = market;
+        self.side = side as u8;
+        self.bump = bump;
+        self.level_count = 0;
+        self.order_count = 0;
+        for (i, order) in self.orders.iter_mut().enumerate() {
+            order.next = if i + 1 < MAX_BOOK_ORDERS { (i + 1) as u16 } else { BOOK_NIL };
+        }
+        self.free_head = 0;
+    }
+
+    pub fn levels(&self) -> &[PriceLevel] { &self.levels[..self.level_count as usize] }
+
+    pub fn best_price(&self) -> Option<u64> { self.levels().first().map(|l| l.price_bps) }
+
+    /// True if price `a` is served before price `b` on this side.
+    fn ranks_before(&self, a: u64, b: u64) -> bool {
+        if self.side == Side::Buy as u8 { a > b } else { a < b }
+    }
+
+    /// True if an order from the other side at `price_bps` would meet this side's best level.
+    pub fn crosses(&self, price_bps: u64) -> bool {
+        self.best_price().is_some_and(|best| !self.ranks_before(price_bps, best))
+    }
+
+    /// Orders resting at `level`, oldest first.
+    pub fn queue<'a>(&'a self, level: &PriceLevel) -> impl Iterator<Item = &'a OrderNode> + 'a {
+        let mut slot = level.head;
+        std::iter::from_fn(move || {
+            if slot == BOOK_NIL { return None; }
+            let order = &self.orders[slot as usize];
+            slot = order.next;
+            Some(order)
+        })
+    }
+
+    /// Fills up to `size` against this side, best level first and FIFO within a level,
+    /// stopping at the first level worse than `limit_price_bps`. Orders expired at `now` never
+    /// match: those `prune` accepts are taken off the book and returned in `Take::expired`,
+    /// the rest are stepped over. Exhausted orders go back on the free list and empty levels
+    /// are closed.
+    pub fn take(&mut self, size: u64, limit_price_bps: u64, now: i64, mut prune: impl FnMut(&OrderNode) -> bool) -> Take {
+        let mut out = Take::default();
+        let mut left = size;
+        let mut level = 0;
+        while left > 0 && level < self.level_count as usize {
+            let price_bps = self.levels[level].price_bps;
+            if self.ranks_before(limit_price_bps, price_bps) { break; }
+            let (mut prev, mut slot) = (BOOK_NIL, self.levels[level].head);
+            let mut closed = false;
+            while left > 0 && slot != BOOK_NIL && !closed {
+                let order = self.orders[slot as usize];
+                let qty = if order.is_expired(now) { 0 } else { left.min(order.size) };
+                if qty > 0 {
+                    left -= qty;
+                    self.orders[slot as usize].size -= qty;
+                    let remaining = order.size - qty;
+                    out.fills.push(BookFill { maker: order.owner, order_id: order.order_id, price_bps, size: qty, remaining });
+                } else if prune(&order) {
+                    out.expired.push(order);
+                } else {
+                    (prev, slot) = (slot, order.next);
+                    continue;
+                }
+                self.levels[level].total_size -= if qty > 0 { qty } else { order.size };
+                if qty > 0 && self.orders[slot as usize].size > 0 {
+                    (prev, slot) = (slot, order.next);
+                } else {
+                    closed = self.unlink(level, prev, slot);
+                    slot = order.next;
+                }
+            }
+            if !closed { level += 1; }
+        }
+        out
+    }
+
+    /// Resting size on this side at `limit_price_bps` or better that hasn't expired at `now`.
+    pub fn depth(&self, limit_price_bps: u64, now: i64) -> u64 {
+        self.levels()
+            .iter()
+            .take_while(|level| !self.ranks_before(limit_price_bps, level.price_bps))
+            .flat_map(|level| self.queue(level))
+            .filter(|order| !order.is_expired(now))
+            .map(|order| order.size)
+            .sum()
+    }
+
+    /// Takes every order expired at `now` that `prune` accepts off the book.
+    pub fn prune_expired(&mut self, now: i64, mut prune: impl FnMut(&OrderNode) -> bool) -> Vec<OrderNode> {
+        let expired: Vec<u64> = self
+            .levels()
+            .iter()
+            .flat_map(|level| self.queue(level))
+            .filter(|order| order.is_expired(now) && prune(order))
+            .map(|order| order.order_id)
+            .collect();
+        expired.into_iter().filter_map(|order_id| self.remove(order_id)).collect()
+    }
+
+    /// First resting order matching `pred`, best level first and FIFO within a level.
+    pub fn find(&self, mut pred: impl FnMut(&OrderNode) -> bool) -> Option<&OrderNode> {
+        self.levels().iter().flat_map(|level| self.queue(level)).find(|order| pred(order))
+    }
+
+    /// Takes order `order_id` off the book and returns it with its remaining size.
+    pub fn remove(&mut self, order_id: u64) -> Option<OrderNode> {
+        for level in 0..self.level_count as usize {
+            let (mut prev, mut slot) = (BOOK_NIL, self.levels[level].head);
+            while slot != BOOK_NIL {
+                let order = self.orders[slot as usize];
+                if order.order_id == order_id {
+                    self.levels[level].total_size -= order.size;
+                    self.unlink(level, prev, slot);
+                    return Some(order);
+                }
+                (prev, slot) = (slot, order.next);
+            }
+        }
+        None
+    }
+
+    /// Unlinks `slot` (queued after `prev`) from `level` and frees it, closing the level if
+    /// that empties it. The caller has already taken its size out of `total_size`. Returns
+    /// whether the level was closed.
+    fn unlink(&mut self, level: usize, prev: u16, slot: u16) -> bool {
+        let next = self.orders[slot as usize].next;
+        if prev == BOOK_NIL {
+            self.levels[level].head = next;
+        } else {
+            self.orders[prev as usize].next = next;
+        }
+        let lvl = &mut self.levels[level];
+        if lvl.tail == slot { lvl.tail = prev; }
+        lvl.count -= 1;
+        self.orders[slot as usize].next = self.free_head;
+        self.free_head = slot;
+        self.order_count -= 1;
+        if self.levels[level].count > 0 { return false; }
+        let count = self.level_count as usize;
+        self.levels.copy_within(level + 1..count, level);
+        self.level_count -= 1;
+        true
+    }
+
+    /// Appends `order` to the back of its price level, opening the level if it is new.
+    /// Returns the order's slot.
+    pub fn insert(&mut self, mut order: OrderNode) -> Result<u16> {
+        let count = self.level_count as usize;
+        let pos = self.levels().iter().position(|l| !self.ranks_before(l.price_bps, order.price_bps)).unwrap_or(count);
+        let opens_level = pos == count || self.levels[pos].price_bps != order.price_bps;
+        require!(self.free_head != BOOK_NIL, ErrorCode::BookFull);
+        require!(!opens_level || count < MAX_PRICE_LEVELS, ErrorCode::BookFull);
+        if opens_level {
+            self.levels.copy_within(pos..count, pos + 1);
+            self.levels[pos] = PriceLevel { price_bps: order.price_bps, total_size: 0, head: BOOK_NIL, tail: BOOK_NIL, count: 0, _padding: [0; 2] };
+            self.level_count += 1;
+        }
+
+        let slot = self.free_head;
+        self.free_head = self.orders[slot as usize].next;
+        order.next = BOOK_NIL;
+        self.orders[slot as usize] = order;
+        let level = &mut self.levels[pos];
+        if level.tail == BOOK_NIL {
+            level.head = slot;
+        } else {
+            self.orders[level.tail as usize].next = slot;
+        }
+        level.tail = slot;
+        level.count += 1;
+        level.total_size = level.total_size.checked_add(order.size).ok_or(ErrorCode::MathOverflow)?;
+        self.order_count += 1;
+        Ok(slot)
+    }
+}
+
+/// An owner's collateral and outcome shares on one market's book, all of it in the market's
+/// collateral mint. The book trades YES at `price_bps`; every fill mints a YES and a NO share
+/// against one unit of collateral in the vault.
+///
+/// Placing a limit order deposits its collateral into `*_locked`: the quote notional for a bid,
+/// the base size for an ask. A fill moves the filled size's share of it to the counterparty:
+/// a bid's maker pays its price out of `quote_locked` for YES shares and the taker posts the
+/// rest of the unit for NO; an ask's maker posts the whole unit out of `base_locked` for NO
+/// and receives the taker's payment for YES in `quote_free`. Cancelling releases what the
+/// order still had resting into `*_free`. Makers also earn the LP fee takers pay on their
+/// fills in `quote_free`. `settle_funds` pays back the free balances and a
+/// unit for every YES and NO pair held, and after resolution the winning side's shares.
+#[account]
+pub struct OpenOrders {
+    pub market: Pubkey,
+    pub owner: Pubkey,
+    pub base_locked: u64,
+    pub quote_locked: u64,
+    pub base_free: u64,
+    pub quote_free: u64,
+    pub yes_shares: u64,
+    pub no_shares: u64,
+    pub bump: u8,
+}
+
+impl OpenOrders {
+    pub const SIZE: usize = 32 + 32 + 8 * 6 + 1;
+
+    /// Collateral an order of `size` at `price_bps` needs on `side`, in base units for an ask
+    /// and in quote units, rounded up, for a bid.
+    pub fn collateral(side: Side, price_bps: u64, size: u64) -> Result<u64> {
+        if side == Side::Sell { return Ok(size); }
+        let quote = (size as u128 * price_bps as u128).div_ceil(10_000);
+        u64::try_from(quote).map_err(|_| error!(ErrorCode::MathOverflow))
+    }
+
+    /// Locks the collateral for `size` at `price_bps` and returns the amount to deposit.
+    pub fn lock(&mut self, side: Side, price_bps: u64, size: u64) -> Result<u64> {
+        let amount = Self::collateral(side, price_bps, size)?;
+        let locked = if side == Side::Sell { &mut self.base_locked } else { &mut self.quote_locked };
+        *locked = locked.checked_add(amount).ok_or(ErrorCode::MathOverflow)?;
+        Ok(amount)
+    }
+
+    /// Empties both free balances and returns their sum, to be paid out of the vault.
+    pub fn settle(&mut self) -> Result<u64> {
+        let amount = self.base_free.checked_add(self.quote_free).ok_or(ErrorCode::MathOverflow)?;
+        self.base_free = 0;
+        self.quote_free = 0;
+        Ok(amount)
+    }
+
+    /// Settles the maker's side of `fill` against its order resting on `side` and returns the
+    /// collateral the taker owes for the other side. A bid pays `price_bps` out of its lock for
+    /// YES; an ask posts the full size for NO and is credited the taker's payment.
+    pub fn fill(&mut self, side: Side, fill: &BookFill) -> Result<u64> {
+        if side == Side::Sell {
+            let paid = Self::collateral(Side::Buy, fill.price_bps, fill.size)?;
+            self.base_locked = self.base_locked.checked_sub(fill.size).ok_or(ErrorCode::MathOverflow)?;
+            self.quote_free = self.quote_free.checked_add(paid).ok_or(ErrorCode::MathOverflow)?;
+            self.no_shares = self.no_shares.checked_add(fill.size).ok_or(ErrorCode::MathOverflow)?;
+            return Ok(paid);
+        }
+        // The bid locked its whole size rounded up once, so take what the filled part adds to
+        // the lock of the rest
+        let before = fill.remaining.checked_add(fill.size).ok_or(ErrorCode::MathOverflow)?;
+        let paid = Self::collateral(Side::Buy, fill.price_bps, before)? - Self::collateral(Side::Buy, fill.price_bps, fill.remaining)?;
+        self.quote_locked = self.quote_locked.checked_sub(paid).ok_or(ErrorCode::MathOverflow)?;
+        self.yes_shares = self.yes_shares.checked_add(fill.size).ok_or(ErrorCode::MathOverflow)?;
+        Ok(fill.size - paid)
+    }
+
+    /// Credits a maker with the LP fee a taker paid on its fill, free to settle.
+    pub fn earn(&mut self, fee: u64) -> Result<()> {
+        self.quote_free = self.quote_free.checked_add(fee).ok_or(ErrorCode::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Credits a taker on `side` with the `size` shares it bought: YES for a buy, NO for a sale.
+    pub fn credit(&mut self, side: Side, size: u64) -> Result<()> {
+        let shares = if side == Side::Buy { &mut self.yes_shares } else { &mut self.no_shares };
+        *shares = shares.checked_add(size).ok_or(ErrorCode::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Burns every YES and NO pair for a unit of collateral each and, once the market resolved
+    /// with `yes_wins`, the rest of both sides for the winners. Returns the amount to pay out.
+    pub fn redeem(&mut self, yes_wins: Option<bool>) -> Result<u64> {
+        let pairs = self.yes_shares.min(self.no_shares);
+        self.yes_shares -= pairs;
+        self.no_shares -= pairs;
+        let won = match yes_wins {
+            Some(true) => self.yes_shares,
+            Some(false) => self.no_shares,
+            None => return Ok(pairs),
+        };
+        self.yes_shares = 0;
+        self.no_shares = 0;
+        pairs.checked_add(won).ok_or_else(|| error!(ErrorCode::MathOverflow))
+    }
+
+    /// Moves the collateral behind the unfilled rest of `order` from locked to free.
+    pub fn release(&mut self, side: Side, order: &OrderNode) -> Result<u64> {
+        let amount = Self::collateral(side, order.price_bps, order.size)?;
+        let (locked, free) = if side == Side::Sell {
+            (&mut self.base_locked, &mut self.base_free)
+        } else {
+            (&mut self.quote_locked, &mut self.quote_free)
+        };
+        *locked = locked.checked_sub(amount).ok_or(ErrorCode::MathOverflow)?;
+        *free = free.checked_add(amount).ok_or(ErrorCode::MathOverflow)?;
+        Ok(amount)
+    }
+}
+
+/// Custodian of a market's collateral. Its PDA owns `token_account`, an account of the
+/// market's collateral `mint` under either token program, and signs every transfer out of it.
+/// `balance` is the vault's ledger: what deposits actually delivered, less what was paid out.
+/// Tokens sent to `token_account` any other way are not part of it and can't be withdrawn.
+#[account]
+pub struct CollateralVault {
+    pub market: Pubkey,
+    pub mint: Pubkey,
+    pub token_account: Pubkey,
+    pub balance: u64,
+    pub bump: u8,
+}
+
+impl CollateralVault { pub const SIZE: usize = 32 + 32 + 32 + 8 + 1; }
+
+/// Fees a market has charged, by recipient. Book takers pay the platform, creator and LP fees
+/// (`Market::fee_bps_*`) on the notional of their fills; pm-AMM trades pay the platform and
+/// creator fees on the curve's price, plus the pool's `fee_bps` for its liquidity. Platform and
+/// creator fees sit in the collateral vault until claimed; LP fees are credited to the book
+/// maker of each fill or stay in the pm-AMM's `PmAmmPool::collateral`, and `lp_fees` only
+/// counts them.
+#[account]
+pub struct MarketFees {
+    pub market: Pubkey,
+    /// Authority of the platform's `Registry`, who claims `platform_fees`.
+    pub platform: Pubkey,
+    pub platform_fees: u64,
+    pub creator_fees: u64,
+    pub lp_fees: u64,
+    pub bump: u8,
+}
+
+impl MarketFees {
+    pub const SIZE: usize = 32 + 32 + 8 * 3 + 1;
+
+    /// What the platform and the creator are owed out of the market's vault.
+    pub fn held(&self) -> u128 { self.platform_fees as u128 + self.creator_fees as u128 }
+
+    pub fn accrue(&mut self, platform: u64, creator: u64, lp: u64) -> Result<()> {
+        self.platform_fees = self.platform_fees.checked_add(platform).ok_or(ErrorCode::MathOverflow)?;
+        self.creator_fees = self.creator_fees.checked_add(creator).ok_or(ErrorCode::MathOverflow)?;
+        self.lp_fees = self.lp_fees.checked_add(lp).ok_or(ErrorCode::MathOverflow)?;
+        Ok(())
+    }
+}
+
+/// Who a `claim_*_fees` instruction pays.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FeeRecipient {
+    /// `MarketFees::platform`.
+    Platform,
+    /// The market's authority.
+    Creator,
+}
+
+/// How a pm-AMM's liquidity L moves towards `expiry_ts` (T). Whatever the schedule, L is
+/// clamped to the pool's `[l_floor, l_ceiling]`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LiquiditySchedule {
+    /// L = l0 throughout.
+    Static,
+    /// L = l0·sqrt(T - t): the dynamic pm-AMM, whose expected LVR stays a constant share of
+    /// the pool's value all the way to expiry.
+    SqrtDecay,
+    /// L = l0·(T - t), thinning out faster as expiry nears.
+    LinearDecay,
+}
+
+impl LiquiditySchedule {
+    /// The `math::LIQUIDITY_*` code `effective_liquidity_fixed` takes.
+    pub fn code(self) -> u8 {
+        match self {
+            LiquiditySchedule::Static => LIQUIDITY_STATIC,
+            LiquiditySchedule::SqrtDecay => LIQUIDITY_SQRT_DECAY,
+            LiquiditySchedule::LinearDecay => LIQUIDITY_LINEAR_DECAY,
+        }
+    }
+}
+
+/// Arguments of `init_pmamm`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct PmAmmParams {
+    pub l0: u64,
+    pub schedule: LiquiditySchedule,
+    /// Bounds on the scheduled liquidity; `l_ceiling` must be positive and fit an i64.
+    pub l_floor: u64,
+    pub l_ceiling: u64,
+    pub fee_bps: u16,
+    pub expiry_ts: i64,
+    /// When the pool stops quoting and leaves the market to the order book; zero never.
+    pub handoff_ts: i64,
+}
+
+/// pm-AMM over a binary outcome: `x` and `y` are the pool's reserves of the two outcome
+/// tokens, kept on the pm-AMM curve at the liquidity its schedule gives for the time of the
+/// last trade. Each trade first re-bases them onto the curve at the current liquidity, at the
+/// same price, so a decaying L releases reserves instead of moving the price.
+///
+/// Takers only hold X, on their `PmAmmPosition`; every Y is the pool's. A unit of collateral
+/// backs one X and one Y, so `collateral` covers both `x + shares_outstanding` and `y`, and
+/// whatever decay releases stays in `collateral` as surplus.
+#[account]
+pub struct PmAmmPool {
+    pub market: Pubkey,
+    pub x: u64,
+    pub y: u64,
+    pub l0: u64,
+    pub schedule: LiquiditySchedule,
+    pub fee_bps: u16,
+    pub expiry_ts: i64,
+    /// Trading fees charged on top of the curve since the pool opened. They stay in
+    /// `collateral`, for the authority who funded the pool to withdraw with the residual.
+    pub fees: u64,
+    pub l_floor: u64,
+    pub l_ceiling: u64,
+    /// See `PmAmmParams::handoff_ts`.
+    pub handoff_ts: i64,
+    /// Collateral in the market's vault behind the pool, its LP fees included.
+    pub collateral: u64,
+    /// X held by takers across every `PmAmmPosition`.
+    pub shares_outstanding: u64,
+    pub bump: u8,
+}
+
+impl PmAmmPool {
+    pub const SIZE: usize = 32 + 8 * 3 + 1 + 2 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 1;
+
+    /// Whether `collateral` backs every X and every Y in existence.
+    pub fn backed(&self) -> bool {
+        let x = self.x as u128 + self.shares_outstanding as u128;
+        x <= self.collateral as u128 && self.y <= self.collateral
+    }
+
+    /// What the pool is owed out of the market's vault: its collateral.
+    pub fn held(&self) -> u128 { self.collateral as u128 }
+
+    /// Whether the pool still quotes at `now`: before expiry and before any hand-off.
+    pub fn quoting(&self, now: i64) -> bool {
+        now < self.expiry_ts && (self.handoff_ts == 0 || now < self.handoff_ts)
+    }
+
+    /// Reserves on the curve at liquidity `l` and price Φ(z), rounded down.
+    pub fn reserves_at(z: Fixed, l: Fixed) -> Result<(u64, u64)> {
+        let at = |z: Fixed| {
+            pmamm_reserve_fixed(z)
+                .and_then(|r| r.checked_mul(l))
+                .and_then(Fixed::floor_to_i64)
+                .ok_or(ErrorCode::MathOverflow)
+        };
+        Ok((at(z.checked_neg().ok_or(ErrorCode::MathOverflow)?)? as u64, at(z)? as u64))
+    }
+
+    /// Liquidity the curve trades at `now`.
+    pub fn liquidity(&self, now: i64) -> Result<Fixed> {
+        let l = effective_liquidity_fixed(self.l0, self.schedule.code(), self.expiry_ts, now, self.l_floor, self.l_ceiling)
+            .ok_or(ErrorCode::MathOverflow)?;
+        require!(l.is_positive(), ErrorCode::InvalidMarketParams);
+        Ok(l)
+    }
+
+    /// Price of the next unit of X in bps, fee included: Φ(z) for the pool's z, to the nearest
+    /// bps since `phi_cdf_fixed` is only good to ~1e-3 bps, then widened by the fee, rounded
+    /// up for a buyer and down for a seller. It doesn't depend on the liquidity, only on where
+    /// the reserves sit on the curve.
+    pub fn marginal_price_bps(&self, side: Side) -> Result<u64> {
+        let z = pmamm_z_fixed(self.x, self.y).ok_or(ErrorCode::MathOverflow)?;
+        let price = phi_cdf_fixed(z)
+            .and_then(|p| p.checked_mul_int(10_000))
+            .and_then(|p| p.checked_add(Fixed::HALF))
+            .and_then(Fixed::floor_to_i64)
+            .ok_or(ErrorCode::MathOverflow)?
+            .clamp(0, 10_000) as u128;
+        let fee = self.fee_bps as u128;
+        let price = if side == Side::Buy {
+            (price * (10_000 + fee)).div_ceil(10_000)
+        } else {
+            price * 10_000u128.saturating_sub(fee) / 10_000
+        };
+        Ok(price as u64)
+    }
+
+    /// Liquidity a trade at `now` runs at: the schedule's, but never more than the reserves
+    /// hold at z (L* = reserve / ŷ, from the larger reserve). When the schedule has decayed
+    /// below L*, the reserves are scaled down to it, never up, keeping z.
+    fn rebase(&mut self, z: Fixed, now: i64) -> Result<Fixed> {
+        let (reserve, per_l) = if self.y >= self.x {
+            (self.y, pmamm_reserve_fixed(z))
+        } else {
+            (self.x, z.checked_neg().and_then(pmamm_reserve_fixed))
+        };
+        let held = per_l
+            .and_then(|per_l| Fixed::from_int(reserve as i64).checked_div(per_l))
+            .ok_or(ErrorCode::MathOverflow)?;
+        let l = self.liquidity(now)?;
+        if l >= held {
+            return Ok(held);
+        }
+        let (x, y) = Self::reserves_at(z, l)?;
+        (self.x, self.y) = (x.min(self.x), y.min(self.y));
+        Ok(l)
+    }
+
+    /// Trades `size` X against the pool along the pm-AMM curve. A buyer's collateral is split
+    /// into X and Y, the Y goes into the pool and the pool pays out the rest of the X; a sale
+    /// runs the other way. With z moved by `size / L`, the quote is L·(ŷ(z') - ŷ(z)) in either
+    /// direction, rounded against the taker and, if need be, further so the pool ends on or
+    /// above the curve on both sides. z stops at ±`PMAMM_MAX_Z`, so an order that would push
+    /// the price past it is only partly filled. Returns the X traded and the collateral the
+    /// taker pays (buy, fee included) or receives (sell, fee deducted); `collateral`,
+    /// `shares_outstanding` and `fees` move with it, so `backed` still holds.
+    pub fn swap(&mut self, side: Side, size: u64, now: i64) -> Result<(u64, u64)> {
+        let z = pmamm_z_fixed(self.x, self.y).ok_or(ErrorCode::MathOverflow)?;
+        let l = self.rebase(z, now)?;
+        let dz = Fixed::from_int(i64::try_from(size).map_err(|_| ErrorCode::MathOverflow)?)
+            .checked_div(l)
+            .ok_or(ErrorCode::MathOverflow)?;
+        let target = if side == Side::Buy { z.checked_add(dz) } else { z.checked_sub(dz) }.ok_or(ErrorCode::MathOverflow)?;
+        let bound = Fixed::from_int(PMAMM_MAX_Z);
+        let to = target.max(Fixed::from_int(-PMAMM_MAX_Z)).min(bound);
+        let traded = if to == target {
+            size
+        } else {
+            to.checked_sub(z).and_then(|d| d.abs().checked_mul(l)).and_then(Fixed::floor_to_i64).ok_or(ErrorCode::MathOverflow)? as u64
+        };
+        let y_at = |z: Fixed| pmamm_reserve_fixed(z).and_then(|r| r.checked_mul(l)).ok_or(ErrorCode::MathOverflow);
+        let moved = y_at(to)?.checked_sub(y_at(z)?).map(Fixed::abs).ok_or(ErrorCode::MathOverflow)?;
+        let (curve_x, curve_y) = Self::reserves_at(to, l)?;
+        let quote = if side == Side::Buy {
+            let quote = moved.ceil_to_i64().ok_or(ErrorCode::MathOverflow)? as u64;
+            quote.max(curve_y.saturating_sub(self.y)).max((traded + curve_x).saturating_sub(self.x))
+        } else {
+            let quote = moved.floor_to_i64().ok_or(ErrorCode::MathOverflow)? as u64;
+            quote.min(self.y.saturating_sub(curve_y)).min((self.x + traded).saturating_sub(curve_x))
+        };
+        let fee = u64::try_from((quote as u128 * self.fee_bps as u128).div_ceil(10_000)).map_err(|_| ErrorCode::MathOverflow)?;
+        if side == Side::Buy {
+            self.x = self.x.checked_add(quote).and_then(|x| x.checked_sub(traded)).ok_or(ErrorCode::MathOverflow)?;
+            self.y = self.y.checked_add(quote).ok_or(ErrorCode::MathOverflow)?;
+            self.collateral = self.collateral.checked_add(quote).ok_or(ErrorCode::MathOverflow)?;
+            self.shares_outstanding = self.shares_outstanding.checked_add(traded).ok_or(ErrorCode::MathOverflow)?;
+        } else {
+            self.x = self.x.checked_add(traded).and_then(|x| x.checked_sub(quote)).ok_or(ErrorCode::MathOverflow)?;
+            self.y = self.y.checked_sub(quote).ok_or(ErrorCode::MathOverflow)?;
+            self.collateral = self.collateral.checked_sub(quote).ok_or(ErrorCode::MathOverflow)?;
+            self.shares_outstanding = self.shares_outstanding.checked_sub(traded).ok_or(ErrorCode::InsufficientShares)?;
+        }
+        self.fees = self.fees.checked_add(fee).ok_or(ErrorCode::MathOverflow)?;
+        self.collateral = self.collateral.checked_add(fee).ok_or(ErrorCode::MathOverflow)?;
+        let total = if side == Side::Buy { quote.checked_add(fee).ok_or(ErrorCode::MathOverflow)? } else { quote.saturating_sub(fee) };
+        Ok((traded, total))
+    }
+
+    /// `swap` for exactly `size`, failing with `PartialFill` where the price bound cuts it short.
+    pub fn fill(&mut self, side: Side, size: u64, now: i64) -> Result<PmAmmQuote> {
+        let fees = self.fees;
+        let (traded, total) = self.swap(side, size, now)?;
+        require!(traded == size, ErrorCode::PartialFill);
+        Ok(PmAmmQuote::new(side, size, total, self.fees - fees))
+    }
+
+    /// Burns `shares` X of a taker's once the market resolved: each pays a unit of collateral
+    /// if YES won, nothing otherwise. Returns the payout, which leaves `collateral` with them.
+    pub fn redeem(&mut self, shares: u64, yes_wins: bool) -> Result<u64> {
+        self.shares_outstanding = self.shares_outstanding.checked_sub(shares).ok_or(ErrorCode::InsufficientShares)?;
+        let payout = if yes_wins { shares } else { 0 };
+        self.collateral = self.collateral.checked_sub(payout).ok_or(ErrorCode::MathOverflow)?;
+        Ok(payout)
+    }
+
+    /// Releases what the pool holds beyond the takers' winning shares once the market resolved:
+    /// its opening funding, decay surplus, LP fees and trading gains. The pool's own X and Y are
+    /// spent with it. Returns the amount, which leaves `collateral`; what stays covers `redeem`.
+    pub fn withdraw_residual(&mut self, yes_wins: bool) -> Result<u64> {
+        let owed = if yes_wins { self.shares_outstanding } else { 0 };
+        let residual = self.collateral.checked_sub(owed).ok_or(ErrorCode::PoolOutOfBalance)?;
+        self.collateral = owed;
+        self.x = 0;
+        self.y = 0;
+        Ok(residual)
+    }
+}
+
+/// A taker's X (YES) shares in a market's pm-AMM, minted on buys and burnt on sells or
+/// when `redeem_pmamm_position` pays them out after resolution.
+#[account]
+pub struct PmAmmPosition {
+    pub market: Pubkey,
+    pub owner: Pubkey,
+    pub shares: u64,
+    pub bump: u8,
+}
+
+impl PmAmmPosition { pub const SIZE: usize = 32 + 32 + 8 + 1; }
+
+#[account]
+pub struct UserProfile {
+    pub owner: Pubkey,
+    pub open_positions: u32,
+    pub bump: u8,
+}
+
+impl UserProfile { pub const SIZE: usize = 32 + 4 + 1; }
+
+#[account]
+pub struct Position {
+    pub owner: Pubkey,
+    pub market: Pubkey,
+    pub size: u64,
+    pub collateral_locked: u64,
+    pub entry_ts: i64,
+    pub realized_pnl: i64,
+    pub bump: u8,
+}
+
+impl Position { pub const SIZE: usize = 32 + 32 + 8 + 8 + 8 + 8 + 1; }
+
+/// A trader's move of the market distribution from (from_mu, from_sigma) to (to_mu, to_sigma),
+/// both in market decimals. Pays k * (q(x)/||q|| - p(x)/||p||) at the resolved outcome x.
+#[account]
+pub struct DistributionPosition {
+    pub owner: Pubkey,
+    pub market: Pubkey,
+    pub position_id: u64,
+    pub from_mu: i64,
+    pub from_sigma: i64,
+    pub to_mu: i64,
+    pub to_sigma: i64,
+    pub l2_norm: u64,
+    pub collateral_locked: u64,
+    pub opened_ts: i64,
+    pub settled: bool,
+    pub bump: u8,
+}
+
+impl DistributionPosition { pub const SIZE: usize = 32 + 32 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 1 + 1; }
+
+/// One weighted Gaussian of a mixture; mu and sigma are in market decimals.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub struct MixtureComponent {
+    pub weight_ppm: u32,
+    pub mu: i64,
+    pub sigma: i64,
+}
+
+impl MixtureComponent { pub const SIZE: usize = 4 + 8 + 8; }
+
+/// Current mixture of a `MarketType::Mixture` market; only the first `len` components are live.
+#[account]
+pub struct MixtureState {
+    pub market: Pubkey,
+    pub len: u8,
+    pub components: [MixtureComponent; MAX_MIXTURE_COMPONENTS],
+    pub bump: u8,
+}
+
+impl MixtureState {
+    pub const SIZE: usize = 32 + 1 + MixtureComponent::SIZE * MAX_MIXTURE_COMPONENTS + 1;
+
+    pub fn components(&self) -> &[MixtureComponent] { &self.components[..self.len as usize] }
+
+    /// Stores already-validated components, zeroing the unused slots.
+    fn set(&mut self, components: &[MixtureComponent]) {
+        self.components = [MixtureComponent::default(); MAX_MIXTURE_COMPONENTS];
+        self.components[..components.len()].copy_from_slice(components);
+        self.len = components.len() as u8;
+    }
+}
+
+/// A trader's replacement of the market mixture `from` with `to`. Pays
+/// k * (q(x)/||q|| - p(x)/||p||) at the resolved outcome x, like `DistributionPosition`.
+#[account]
+pub struct MixturePosition {
+    pub owner: Pubkey,
+    pub market: Pubkey,
+    pub position_id: u64,
+    pub from: [MixtureComponent; MAX_MIXTURE_COMPONENTS],
+    pub from_len: u8,
+    pub to: [MixtureComponent; MAX_MIXTURE_COMPONENTS],
+    pub to_len: u8,
+    pub l2_norm: u64,
+    pub collateral_locked: u64,
+    pub opened_ts: i64,
+    pub settled: bool,
+    pub bump: u8,
+}
+
+impl MixturePosition {
+    pub const SIZE: usize = 32 + 32 + 8 + (MixtureComponent::SIZE * MAX_MIXTURE_COMPONENTS + 1) * 2 + 8 + 8 + 8 + 1 + 1;
+}
+
+/// Moves `mass` parts per billion of probability from `from_bin` to `to_bin`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BinShift {
+    pub from_bin: u16,
+    pub to_bin: u16,
+    pub mass: u32,
+}
+
+/// Current bin masses of a `MarketType::Histogram` market; only the first `bins` entries are live.
+#[account(zero_copy)]
+pub struct HistogramState {
+    pub market: Pubkey,
+    pub mass: [u32; MAX_HISTOGRAM_BINS],
+    pub bins: u16,
+    pub bump: u8,
+    pub _padding: [u8; 5],
+}
+
+/// A trader's move of the histogram from `from_mass` to `to_mass`. Pays
+/// k * (q_b/||q|| - p_b/||p||) on the bin b containing the resolved outcome.
+#[account(zero_copy)]
+pub struct HistogramPosition {
+    pub owner: Pubkey,
+    pub market: Pubkey,
+    pub position_id: u64,
+    pub l2_norm: u64,
+    pub collateral_locked: u64,
+    pub opened_ts: i64,
+    /// Set by `settle_histogram_position`.
+    pub payout: u64,
+    pub from_mass: [u32; MAX_HISTOGRAM_BINS],
+    pub to_mass: [u32; MAX_HISTOGRAM_BINS],
+    pub bins: u16,
+    pub settled: u8,
+    pub bump: u8,
+    pub _padding: [u8; 4],
+}
+
+// Contexts
+
+#[derive(Accounts)]
+#[instruction(params: MarketParams)]
+pub struct CreateMarket<'info> {
+    #[account(
+        init,
         seeds = [b"market", authority.key().as_ref(), &params.slug],
         bump,
         payer = authority,
@@ -733,52 +1818,511 @@ pub struct InitializeMarketInfrastructure<'info> {
         space = 8 + CollateralVault::SIZE,
     )]
     pub collateral_vault: Account<'info, CollateralVault>,
+    /// The market's collateral under either token program, e.g. a USDC mock on localnet.
+    pub collateral_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        init,
+        seeds = [b"vault_tokens", market.key().as_ref()],
+        bump,
+        payer = authority,
+        token::mint = collateral_mint,
+        token::authority = collateral_vault,
+        token::token_program = token_program,
+    )]
+    pub vault_tokens: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// LP shares, minted by the pool PDA with the collateral's decimals.
+    #[account(
+        init,
+        seeds = [b"lp_mint", market.key().as_ref()],
+        bump,
+        payer = authority,
+        mint::decimals = collateral_mint.decimals,
+        mint::authority = liquidity_pool,
+        mint::freeze_authority = liquidity_pool,
+        mint::token_program = token_program,
+    )]
+    pub lp_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        init,
+        seeds = [b"fees", market.key().as_ref()],
+        bump,
+        payer = authority,
+        space = 8 + MarketFees::SIZE,
+    )]
+    pub market_fees: Account<'info, MarketFees>,
+    /// The platform's registry; its authority claims the market's platform fees.
+    #[account(seeds = [b"registry"], bump = registry.bump)]
+    pub registry: Account<'info, Registry>,
+    /// Where the opening subsidy comes from; see `Market::subsidy`.
+    #[account(mut, token::mint = collateral_mint, token::authority = authority, token::token_program = token_program)]
+    pub authority_tokens: Box<InterfaceAccount<'info, TokenAccount>>,
     #[account(mut)]
     pub authority: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct PlaceLimitOrder<'info> {
+pub struct InitOrderBook<'info> {
+    #[account(has_one = authority)]
     pub market: Account<'info, Market>,
+    #[account(
+        init,
+        seeds = [b"bids", market.key().as_ref()],
+        bump,
+        payer = authority,
+        space = 8 + std::mem::size_of::<BookSide>(),
+    )]
+    pub bids: AccountLoader<'info, BookSide>,
+    #[account(
+        init,
+        seeds = [b"asks", market.key().as_ref()],
+        bump,
+        payer = authority,
+        space = 8 + std::mem::size_of::<BookSide>(),
+    )]
+    pub asks: AccountLoader<'info, BookSide>,
     #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct PlaceLimitOrder<'info> {
+    pub market: Account<'info, Market>,
+    #[account(
+        mut,
+        seeds = [b"orderbook", market.key().as_ref()],
+        bump = order_book.bump,
+        has_one = market,
+    )]
     pub order_book: Account<'info, OrderBook>,
+    #[account(mut, seeds = [b"bids", market.key().as_ref()], bump = bids.load()?.bump)]
+    pub bids: AccountLoader<'info, BookSide>,
+    #[account(mut, seeds = [b"asks", market.key().as_ref()], bump = asks.load()?.bump)]
+    pub asks: AccountLoader<'info, BookSide>,
+    #[account(
+        init_if_needed,
+        seeds = [b"open_orders", market.key().as_ref(), owner.key().as_ref()],
+        bump,
+        payer = owner,
+        space = 8 + OpenOrders::SIZE,
+    )]
+    pub open_orders: Account<'info, OpenOrders>,
+    #[account(
+        mut,
+        seeds = [b"collateral", market.key().as_ref()],
+        bump = collateral_vault.bump,
+        has_one = market,
+        has_one = mint,
+    )]
+    pub collateral_vault: Account<'info, CollateralVault>,
+    #[account(mut, address = collateral_vault.token_account)]
+    pub vault_tokens: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(mut, token::mint = mint, token::authority = owner, token::token_program = token_program)]
+    pub owner_tokens: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut, seeds = [b"fees", market.key().as_ref()], bump = market_fees.bump, has_one = market)]
+    pub market_fees: Account<'info, MarketFees>,
+    pub token_program: Interface<'info, TokenInterface>,
+    #[account(mut)]
     pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
 pub struct ExecuteMarketOrder<'info> {
     pub market: Account<'info, Market>,
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [b"orderbook", market.key().as_ref()],
+        bump = order_book.bump,
+        has_one = market,
+    )]
     pub order_book: Account<'info, OrderBook>,
+    #[account(mut, seeds = [b"bids", market.key().as_ref()], bump = bids.load()?.bump)]
+    pub bids: AccountLoader<'info, BookSide>,
+    #[account(mut, seeds = [b"asks", market.key().as_ref()], bump = asks.load()?.bump)]
+    pub asks: AccountLoader<'info, BookSide>,
+    /// Holds the shares of the taker's book fills.
+    #[account(
+        init_if_needed,
+        seeds = [b"open_orders", market.key().as_ref(), taker.key().as_ref()],
+        bump,
+        payer = taker,
+        space = 8 + OpenOrders::SIZE,
+    )]
+    pub open_orders: Account<'info, OpenOrders>,
+    /// Takes whatever the book can't fill at or better than the AMM; omit for book-only orders.
+    #[account(mut, seeds = [b"pmamm", market.key().as_ref()], bump = pmamm_pool.bump)]
+    pub pmamm_pool: Option<Account<'info, PmAmmPool>>,
+    /// Required with `pmamm_pool`; holds the shares of the AMM leg.
+    #[account(
+        init_if_needed,
+        seeds = [b"pmamm_position", market.key().as_ref(), taker.key().as_ref()],
+        bump,
+        payer = taker,
+        space = 8 + PmAmmPosition::SIZE,
+    )]
+    pub pmamm_position: Option<Account<'info, PmAmmPosition>>,
+    #[account(
+        mut,
+        seeds = [b"collateral", market.key().as_ref()],
+        bump = collateral_vault.bump,
+        has_one = market,
+        has_one = mint,
+    )]
+    pub collateral_vault: Account<'info, CollateralVault>,
+    #[account(mut, address = collateral_vault.token_account)]
+    pub vault_tokens: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(mut, token::mint = mint, token::authority = taker, token::token_program = token_program)]
+    pub taker_tokens: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut, seeds = [b"fees", market.key().as_ref()], bump = market_fees.bump, has_one = market)]
+    pub market_fees: Account<'info, MarketFees>,
+    pub token_program: Interface<'info, TokenInterface>,
     #[account(mut)]
-    pub liquidity_pool: Account<'info, LiquidityPool>,
     pub taker: Signer<'info>,
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
 pub struct CancelOrder<'info> {
     pub market: Account<'info, Market>,
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [b"orderbook", market.key().as_ref()],
+        bump = order_book.bump,
+        has_one = market,
+    )]
     pub order_book: Account<'info, OrderBook>,
+    #[account(mut, seeds = [b"bids", market.key().as_ref()], bump = bids.load()?.bump)]
+    pub bids: AccountLoader<'info, BookSide>,
+    #[account(mut, seeds = [b"asks", market.key().as_ref()], bump = asks.load()?.bump)]
+    pub asks: AccountLoader<'info, BookSide>,
+    #[account(
+        mut,
+        seeds = [b"open_orders", market.key().as_ref(), owner.key().as_ref()],
+        bump = open_orders.bump,
+        has_one = owner,
+    )]
+    pub open_orders: Account<'info, OpenOrders>,
     pub owner: Signer<'info>,
 }
 
 #[derive(Accounts)]
-pub struct ResolveMarket<'info> {
+pub struct SettleFunds<'info> {
+    pub market: Account<'info, Market>,
+    #[account(
+        mut,
+        seeds = [b"open_orders", market.key().as_ref(), owner.key().as_ref()],
+        bump = open_orders.bump,
+        has_one = owner,
+    )]
+    pub open_orders: Account<'info, OpenOrders>,
+    #[account(
+        mut,
+        seeds = [b"collateral", market.key().as_ref()],
+        bump = collateral_vault.bump,
+        has_one = market,
+        has_one = mint,
+    )]
+    pub collateral_vault: Account<'info, CollateralVault>,
+    #[account(mut, address = collateral_vault.token_account)]
+    pub vault_tokens: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(mut, token::mint = mint, token::authority = owner, token::token_program = token_program)]
+    pub owner_tokens: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub owner: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct PruneExpiredOrders<'info> {
+    pub market: Account<'info, Market>,
+    #[account(
+        mut,
+        seeds = [b"orderbook", market.key().as_ref()],
+        bump = order_book.bump,
+        has_one = market,
+    )]
+    pub order_book: Account<'info, OrderBook>,
+    #[account(mut, seeds = [b"bids", market.key().as_ref()], bump = bids.load()?.bump)]
+    pub bids: AccountLoader<'info, BookSide>,
+    #[account(mut, seeds = [b"asks", market.key().as_ref()], bump = asks.load()?.bump)]
+    pub asks: AccountLoader<'info, BookSide>,
+}
+
+#[derive(Accounts)]
+pub struct CloseMarket<'info> {
     #[account(mut)]
     pub market: Account<'info, Market>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveMarket<'info> {
+    #[account(mut, has_one = authority)]
+    pub market: Account<'info, Market>,
     pub authority: Signer<'info>,
 }
 
+#[derive(Accounts)]
+pub struct FundVault<'info> {
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+    #[account(
+        mut,
+        seeds = [b"collateral", market.key().as_ref()],
+        bump = collateral_vault.bump,
+        has_one = market,
+        has_one = mint,
+    )]
+    pub collateral_vault: Account<'info, CollateralVault>,
+    #[account(mut, address = collateral_vault.token_account)]
+    pub vault_tokens: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(mut, token::mint = mint, token::authority = funder, token::token_program = token_program)]
+    pub funder_tokens: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub funder: Signer<'info>,
+}
+
 #[derive(Accounts)]
 pub struct ClaimPayout<'info> {
+    #[account(mut)]
     pub market: Account<'info, Market>,
+    #[account(mut, has_one = owner, has_one = market)]
+    pub position: Account<'info, DistributionPosition>,
+    #[account(
+        mut,
+        seeds = [b"collateral", market.key().as_ref()],
+        bump = collateral_vault.bump,
+        has_one = market,
+        has_one = mint,
+    )]
+    pub collateral_vault: Account<'info, CollateralVault>,
+    #[account(mut, address = collateral_vault.token_account)]
+    pub vault_tokens: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(mut, token::mint = mint, token::authority = owner, token::token_program = token_program)]
+    pub owner_tokens: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(position_id: u64)]
+pub struct TradeDistribution<'info> {
     #[account(mut)]
-    pub position: Account<'info, Position>,
+    pub market: Account<'info, Market>,
+    #[account(
+        init,
+        seeds = [b"dist_position", market.key().as_ref(), trader.key().as_ref(), &position_id.to_le_bytes()],
+        bump,
+        payer = trader,
+        space = 8 + DistributionPosition::SIZE,
+    )]
+    pub position: Account<'info, DistributionPosition>,
+    #[account(
+        mut,
+        seeds = [b"collateral", market.key().as_ref()],
+        bump = collateral_vault.bump,
+        has_one = market,
+        has_one = mint,
+    )]
+    pub collateral_vault: Account<'info, CollateralVault>,
+    #[account(mut, address = collateral_vault.token_account)]
+    pub vault_tokens: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(mut, token::mint = mint, token::authority = trader, token::token_program = token_program)]
+    pub trader_tokens: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Interface<'info, TokenInterface>,
+    #[account(mut)]
+    pub trader: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitMixture<'info> {
+    #[account(has_one = authority)]
+    pub market: Account<'info, Market>,
+    #[account(
+        init,
+        seeds = [b"mixture", market.key().as_ref()],
+        bump,
+        payer = authority,
+        space = 8 + MixtureState::SIZE,
+    )]
+    pub mixture: Account<'info, MixtureState>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(position_id: u64)]
+pub struct TradeMixture<'info> {
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+    #[account(
+        mut,
+        seeds = [b"mixture", market.key().as_ref()],
+        bump = mixture.bump,
+    )]
+    pub mixture: Account<'info, MixtureState>,
+    #[account(
+        init,
+        seeds = [b"mixture_position", market.key().as_ref(), trader.key().as_ref(), &position_id.to_le_bytes()],
+        bump,
+        payer = trader,
+        space = 8 + MixturePosition::SIZE,
+    )]
+    pub position: Account<'info, MixturePosition>,
+    #[account(
+        mut,
+        seeds = [b"collateral", market.key().as_ref()],
+        bump = collateral_vault.bump,
+        has_one = market,
+        has_one = mint,
+    )]
+    pub collateral_vault: Account<'info, CollateralVault>,
+    #[account(mut, address = collateral_vault.token_account)]
+    pub vault_tokens: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(mut, token::mint = mint, token::authority = trader, token::token_program = token_program)]
+    pub trader_tokens: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Interface<'info, TokenInterface>,
+    #[account(mut)]
+    pub trader: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimMixturePayout<'info> {
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+    #[account(mut, has_one = owner, has_one = market)]
+    pub position: Account<'info, MixturePosition>,
+    #[account(
+        mut,
+        seeds = [b"collateral", market.key().as_ref()],
+        bump = collateral_vault.bump,
+        has_one = market,
+        has_one = mint,
+    )]
+    pub collateral_vault: Account<'info, CollateralVault>,
+    #[account(mut, address = collateral_vault.token_account)]
+    pub vault_tokens: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(mut, token::mint = mint, token::authority = owner, token::token_program = token_program)]
+    pub owner_tokens: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitHistogram<'info> {
+    #[account(has_one = authority)]
+    pub market: Account<'info, Market>,
+    #[account(
+        init,
+        seeds = [b"histogram", market.key().as_ref()],
+        bump,
+        payer = authority,
+        space = 8 + std::mem::size_of::<HistogramState>(),
+    )]
+    pub histogram: AccountLoader<'info, HistogramState>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(position_id: u64)]
+pub struct TradeHistogram<'info> {
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+    #[account(
+        mut,
+        seeds = [b"histogram", market.key().as_ref()],
+        bump = histogram.load()?.bump,
+    )]
+    pub histogram: AccountLoader<'info, HistogramState>,
+    #[account(
+        init,
+        seeds = [b"histogram_position", market.key().as_ref(), trader.key().as_ref(), &position_id.to_le_bytes()],
+        bump,
+        payer = trader,
+        space = 8 + std::mem::size_of::<HistogramPosition>(),
+    )]
+    pub position: AccountLoader<'info, HistogramPosition>,
+    #[account(
+        mut,
+        seeds = [b"collateral", market.key().as_ref()],
+        bump = collateral_vault.bump,
+        has_one = market,
+        has_one = mint,
+    )]
+    pub collateral_vault: Account<'info, CollateralVault>,
+    #[account(mut, address = collateral_vault.token_account)]
+    pub vault_tokens: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(mut, token::mint = mint, token::authority = trader, token::token_program = token_program)]
+    pub trader_tokens: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Interface<'info, TokenInterface>,
+    #[account(mut)]
+    pub trader: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SettleHistogramPosition<'info> {
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+    #[account(mut)]
+    pub position: AccountLoader<'info, HistogramPosition>,
+    #[account(
+        mut,
+        seeds = [b"collateral", market.key().as_ref()],
+        bump = collateral_vault.bump,
+        has_one = market,
+        has_one = mint,
+    )]
+    pub collateral_vault: Account<'info, CollateralVault>,
+    #[account(mut, address = collateral_vault.token_account)]
+    pub vault_tokens: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(mut, token::mint = mint, token::authority = owner, token::token_program = token_program)]
+    pub owner_tokens: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Interface<'info, TokenInterface>,
     pub owner: Signer<'info>,
 }
 
+#[derive(Accounts)]
+pub struct QuotePmAmm<'info> {
+    pub market: Account<'info, Market>,
+    #[account(seeds = [b"pmamm", market.key().as_ref()], bump = pmamm_pool.bump)]
+    pub pmamm_pool: Account<'info, PmAmmPool>,
+}
+
+#[derive(Accounts)]
+pub struct QueryLiquidity<'info> {
+    pub market: Account<'info, Market>,
+    #[account(seeds = [b"pmamm", market.key().as_ref()], bump = pmamm_pool.bump)]
+    pub pmamm_pool: Account<'info, PmAmmPool>,
+}
+
+#[derive(Accounts)]
+pub struct QueryMarket<'info> {
+    pub market: Account<'info, Market>,
+    /// Required for mixture markets.
+    #[account(seeds = [b"mixture", market.key().as_ref()], bump = mixture.bump)]
+    pub mixture: Option<Account<'info, MixtureState>>,
+    /// Required for histogram markets.
+    #[account(seeds = [b"histogram", market.key().as_ref()], bump = histogram.load()?.bump)]
+    pub histogram: Option<AccountLoader<'info, HistogramState>>,
+}
+
 #[derive(Accounts)]
 pub struct InitUser<'info> {
     #[account(
@@ -811,6 +2355,20 @@ pub struct OpenPosition<'info> {
         bump = user_profile.bump,
     )]
     pub user_profile: Account<'info, UserProfile>,
+    #[account(
+        mut,
+        seeds = [b"collateral", market.key().as_ref()],
+        bump = collateral_vault.bump,
+        has_one = market,
+        has_one = mint,
+    )]
+    pub collateral_vault: Account<'info, CollateralVault>,
+    #[account(mut, address = collateral_vault.token_account)]
+    pub vault_tokens: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(mut, token::mint = mint, token::authority = owner, token::token_program = token_program)]
+    pub owner_tokens: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Interface<'info, TokenInterface>,
     #[account(mut)]
     pub owner: Signer<'info>,
     pub system_program: Program<'info, System>,
@@ -830,6 +2388,20 @@ pub struct AdjustPosition<'info> {
         bump = user_profile.bump,
     )]
     pub user_profile: Account<'info, UserProfile>,
+    #[account(
+        mut,
+        seeds = [b"collateral", market.key().as_ref()],
+        bump = collateral_vault.bump,
+        has_one = market,
+        has_one = mint,
+    )]
+    pub collateral_vault: Account<'info, CollateralVault>,
+    #[account(mut, address = collateral_vault.token_account)]
+    pub vault_tokens: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(mut, token::mint = mint, token::authority = owner, token::token_program = token_program)]
+    pub owner_tokens: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Interface<'info, TokenInterface>,
     pub owner: Signer<'info>,
 }
 
@@ -840,6 +2412,7 @@ pub struct ClosePosition<'info> {
         mut,
         seeds = [b"position", owner.key().as_ref(), market.key().as_ref()],
         bump = position.bump,
+        close = owner,
     )]
     pub position: Account<'info, Position>,
     #[account(
@@ -848,32 +2421,119 @@ pub struct ClosePosition<'info> {
         bump = user_profile.bump,
     )]
     pub user_profile: Account<'info, UserProfile>,
+    #[account(
+        mut,
+        seeds = [b"collateral", market.key().as_ref()],
+        bump = collateral_vault.bump,
+        has_one = market,
+        has_one = mint,
+    )]
+    pub collateral_vault: Account<'info, CollateralVault>,
+    #[account(mut, address = collateral_vault.token_account)]
+    pub vault_tokens: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(mut, token::mint = mint, token::authority = owner, token::token_program = token_program)]
+    pub owner_tokens: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Interface<'info, TokenInterface>,
+    #[account(mut)]
     pub owner: Signer<'info>,
 }
 
 #[derive(Accounts)]
-pub struct AddLiquidity<'info> {
+pub struct OpenLpPosition<'info> {
+    pub market: Account<'info, Market>,
+    #[account(
+        init,
+        seeds = [b"lp", market.key().as_ref(), provider.key().as_ref()],
+        bump,
+        payer = provider,
+        space = 8 + LpPosition::SIZE,
+    )]
+    pub lp_position: Account<'info, LpPosition>,
     #[account(mut)]
+    pub provider: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AddLiquidity<'info> {
     pub market: Account<'info, Market>,
+    #[account(
+        init_if_needed,
+        seeds = [b"lp", market.key().as_ref(), provider.key().as_ref()],
+        bump,
+        payer = provider,
+        space = 8 + LpPosition::SIZE,
+    )]
+    pub lp_position: Account<'info, LpPosition>,
     #[account(
         mut,
         seeds = [b"pool", market.key().as_ref()],
         bump = liquidity_pool.bump,
+        has_one = market,
+        has_one = lp_mint,
     )]
     pub liquidity_pool: Account<'info, LiquidityPool>,
+    #[account(mut)]
+    pub lp_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        seeds = [b"collateral", market.key().as_ref()],
+        bump = collateral_vault.bump,
+        has_one = market,
+        has_one = mint,
+    )]
+    pub collateral_vault: Account<'info, CollateralVault>,
+    #[account(mut, address = collateral_vault.token_account)]
+    pub vault_tokens: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(mut, token::mint = mint, token::authority = provider, token::token_program = token_program)]
+    pub provider_tokens: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut, token::mint = lp_mint, token::authority = provider, token::token_program = token_program)]
+    pub provider_shares: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Interface<'info, TokenInterface>,
+    #[account(mut)]
     pub provider: Signer<'info>,
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
 pub struct RemoveLiquidity<'info> {
-    #[account(mut)]
     pub market: Account<'info, Market>,
+    #[account(
+        mut,
+        seeds = [b"lp", market.key().as_ref(), provider.key().as_ref()],
+        bump = lp_position.bump,
+        has_one = market,
+        has_one = provider,
+    )]
+    pub lp_position: Account<'info, LpPosition>,
     #[account(
         mut,
         seeds = [b"pool", market.key().as_ref()],
         bump = liquidity_pool.bump,
+        has_one = market,
+        has_one = lp_mint,
     )]
     pub liquidity_pool: Account<'info, LiquidityPool>,
+    #[account(mut)]
+    pub lp_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        seeds = [b"collateral", market.key().as_ref()],
+        bump = collateral_vault.bump,
+        has_one = market,
+        has_one = mint,
+    )]
+    pub collateral_vault: Account<'info, CollateralVault>,
+    #[account(mut, address = collateral_vault.token_account)]
+    pub vault_tokens: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(mut, token::mint = mint, token::authority = provider, token::token_program = token_program)]
+    pub provider_tokens: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut, token::mint = lp_mint, token::authority = provider, token::token_program = token_program)]
+    pub provider_shares: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Interface<'info, TokenInterface>,
     pub provider: Signer<'info>,
 }
 
@@ -889,11 +2549,102 @@ pub struct InitPmAmm<'info> {
         space = 8 + PmAmmPool::SIZE,
     )]
     pub pmamm_pool: Account<'info, PmAmmPool>,
+    #[account(
+        mut,
+        seeds = [b"collateral", market.key().as_ref()],
+        bump = collateral_vault.bump,
+        has_one = market,
+        has_one = mint,
+    )]
+    pub collateral_vault: Account<'info, CollateralVault>,
+    #[account(mut, address = collateral_vault.token_account)]
+    pub vault_tokens: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(mut, token::mint = mint, token::authority = authority, token::token_program = token_program)]
+    pub authority_tokens: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Interface<'info, TokenInterface>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateMarket<'info> {
+    /// CHECK: decoded by hand in `migrate_market` (discriminator, legacy size and authority are verified)
+    #[account(mut, owner = crate::ID)]
+    pub market: UncheckedAccount<'info>,
+    /// CHECK: must be the market's order book; layout checked in `migrate_market`
+    #[account(mut, owner = crate::ID)]
+    pub order_book: Option<UncheckedAccount<'info>>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateMarketInfrastructure<'info> {
+    #[account(
+        seeds = [b"market", authority.key().as_ref(), &market.slug],
+        bump = market.bump,
+        has_one = authority,
+    )]
+    pub market: Account<'info, Market>,
+    /// CHECK: the market's pre-v1 pool; layout checked and rewritten in `migrate_market_infrastructure`
+    #[account(mut, owner = crate::ID, seeds = [b"pool", market.key().as_ref()], bump)]
+    pub liquidity_pool: UncheckedAccount<'info>,
+    /// CHECK: the market's pre-v1 vault; layout checked and rewritten in `migrate_market_infrastructure`
+    #[account(mut, owner = crate::ID, seeds = [b"collateral", market.key().as_ref()], bump)]
+    pub collateral_vault: UncheckedAccount<'info>,
+    pub collateral_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        init,
+        seeds = [b"vault_tokens", market.key().as_ref()],
+        bump,
+        payer = authority,
+        token::mint = collateral_mint,
+        token::authority = collateral_vault,
+        token::token_program = token_program,
+    )]
+    pub vault_tokens: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init,
+        seeds = [b"lp_mint", market.key().as_ref()],
+        bump,
+        payer = authority,
+        mint::decimals = collateral_mint.decimals,
+        mint::authority = liquidity_pool,
+        mint::freeze_authority = liquidity_pool,
+        mint::token_program = token_program,
+    )]
+    pub lp_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        init,
+        seeds = [b"fees", market.key().as_ref()],
+        bump,
+        payer = authority,
+        space = 8 + MarketFees::SIZE,
+    )]
+    pub market_fees: Account<'info, MarketFees>,
+    /// See `InitializeMarketInfrastructure::registry`.
+    #[account(seeds = [b"registry"], bump = registry.bump)]
+    pub registry: Account<'info, Registry>,
     #[account(mut)]
     pub authority: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct CloseLegacyPmAmm<'info> {
+    #[account(has_one = authority)]
+    pub market: Account<'info, Market>,
+    /// CHECK: the market's pre-v1 pm-AMM pool; layout checked in `close_legacy_pmamm`
+    #[account(mut, owner = crate::ID, seeds = [b"pmamm", market.key().as_ref()], bump)]
+    pub pmamm_pool: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
 #[derive(Accounts)]
 pub struct TradePmAmm<'info> {
     pub market: Account<'info, Market>,
@@ -903,7 +2654,116 @@ pub struct TradePmAmm<'info> {
         bump = pmamm_pool.bump,
     )]
     pub pmamm_pool: Account<'info, PmAmmPool>,
+    #[account(
+        init_if_needed,
+        seeds = [b"pmamm_position", market.key().as_ref(), taker.key().as_ref()],
+        bump,
+        payer = taker,
+        space = 8 + PmAmmPosition::SIZE,
+    )]
+    pub pmamm_position: Account<'info, PmAmmPosition>,
+    #[account(
+        mut,
+        seeds = [b"collateral", market.key().as_ref()],
+        bump = collateral_vault.bump,
+        has_one = market,
+        has_one = mint,
+    )]
+    pub collateral_vault: Account<'info, CollateralVault>,
+    #[account(mut, address = collateral_vault.token_account)]
+    pub vault_tokens: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(mut, token::mint = mint, token::authority = taker, token::token_program = token_program)]
+    pub taker_tokens: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut, seeds = [b"fees", market.key().as_ref()], bump = market_fees.bump, has_one = market)]
+    pub market_fees: Account<'info, MarketFees>,
+    pub token_program: Interface<'info, TokenInterface>,
+    #[account(mut)]
     pub taker: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RedeemPmAmmPosition<'info> {
+    pub market: Account<'info, Market>,
+    #[account(
+        mut,
+        seeds = [b"pmamm", market.key().as_ref()],
+        bump = pmamm_pool.bump,
+    )]
+    pub pmamm_pool: Account<'info, PmAmmPool>,
+    #[account(
+        mut,
+        seeds = [b"pmamm_position", market.key().as_ref(), owner.key().as_ref()],
+        bump = pmamm_position.bump,
+        has_one = market,
+        has_one = owner,
+    )]
+    pub pmamm_position: Account<'info, PmAmmPosition>,
+    #[account(
+        mut,
+        seeds = [b"collateral", market.key().as_ref()],
+        bump = collateral_vault.bump,
+        has_one = market,
+        has_one = mint,
+    )]
+    pub collateral_vault: Account<'info, CollateralVault>,
+    #[account(mut, address = collateral_vault.token_account)]
+    pub vault_tokens: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(mut, token::mint = mint, token::authority = owner, token::token_program = token_program)]
+    pub owner_tokens: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawPmAmmResidual<'info> {
+    #[account(has_one = authority)]
+    pub market: Account<'info, Market>,
+    #[account(
+        mut,
+        seeds = [b"pmamm", market.key().as_ref()],
+        bump = pmamm_pool.bump,
+    )]
+    pub pmamm_pool: Account<'info, PmAmmPool>,
+    #[account(
+        mut,
+        seeds = [b"collateral", market.key().as_ref()],
+        bump = collateral_vault.bump,
+        has_one = market,
+        has_one = mint,
+    )]
+    pub collateral_vault: Account<'info, CollateralVault>,
+    #[account(mut, address = collateral_vault.token_account)]
+    pub vault_tokens: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(mut, token::mint = mint, token::authority = authority, token::token_program = token_program)]
+    pub authority_tokens: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimFees<'info> {
+    pub market: Account<'info, Market>,
+    #[account(mut, seeds = [b"fees", market.key().as_ref()], bump = market_fees.bump, has_one = market)]
+    pub market_fees: Account<'info, MarketFees>,
+    #[account(
+        mut,
+        seeds = [b"collateral", market.key().as_ref()],
+        bump = collateral_vault.bump,
+        has_one = market,
+        has_one = mint,
+    )]
+    pub collateral_vault: Account<'info, CollateralVault>,
+    #[account(mut, address = collateral_vault.token_account)]
+    pub vault_tokens: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(mut, token::mint = mint, token::token_program = token_program)]
+    pub recipient_tokens: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub claimant: Signer<'info>,
 }
 
 // Events for front-end subscriptions
@@ -912,8 +2772,8 @@ pub struct MarketCreated {
     pub market: Pubkey,
     pub authority: Pubkey,
     pub slug: [u8; 32],
-    pub outcome_min: f64,
-    pub outcome_max: f64,
+    pub outcome_min: i64,
+    pub outcome_max: i64,
     pub unit: [u8; 12],
     pub ts: i64,
 }
@@ -921,6 +2781,8 @@ pub struct MarketCreated {
 #[event]
 pub struct MarketInfrastructureInitialized {
     pub market: Pubkey,
+    /// What the authority posted towards distribution payouts; see `Market::subsidy`.
+    pub subsidy: u64,
     pub ts: i64,
 }
 
@@ -928,9 +2790,18 @@ pub struct MarketInfrastructureInitialized {
 pub struct PmAmmInitialized {
     pub market: Pubkey,
     pub l0: u64,
-    pub dynamic_on: u8,
+    pub schedule: LiquiditySchedule,
+    pub l_floor: u64,
+    pub l_ceiling: u64,
     pub fee_bps: u16,
     pub expiry_ts: i64,
+    pub handoff_ts: i64,
+    pub ts: i64,
+}
+
+#[event]
+pub struct OrderBookInitialized {
+    pub market: Pubkey,
     pub ts: i64,
 }
 
@@ -939,7 +2810,19 @@ pub struct OrderPlaced {
     pub order_id: u64,
     pub market: Pubkey,
     pub owner: Pubkey,
-    pub side: u8,
+    pub side: Side,
+    pub price_bps: u64,
+    pub size: u64,
+    pub ts: i64,
+}
+
+#[event]
+pub struct Fill {
+    pub market: Pubkey,
+    pub maker_order_id: u64,
+    pub maker: Pubkey,
+    pub taker: Pubkey,
+    pub taker_side: Side,
     pub price_bps: u64,
     pub size: u64,
     pub ts: i64,
@@ -949,22 +2832,70 @@ pub struct OrderPlaced {
 pub struct TradeExecuted {
     pub market: Pubkey,
     pub taker: Pubkey,
-    pub side: u8,
+    pub side: Side,
     pub price_bps: u64,
     pub size: u64,
+    pub book_size: u64,
+    pub amm_size: u64,
     pub ts: i64,
 }
 
 #[event]
 pub struct OrderCancelled {
     pub order_id: u64,
+    pub market: Pubkey,
+    pub owner: Pubkey,
+    pub side: Side,
+    pub size: u64,
+    pub released: u64,
+    pub ts: i64,
+}
+
+#[event]
+pub struct OrderExpired {
+    pub order_id: u64,
+    pub market: Pubkey,
+    pub owner: Pubkey,
+    pub side: Side,
+    pub size: u64,
+    pub released: u64,
+    pub ts: i64,
+}
+
+#[event]
+pub struct FundsSettled {
+    pub market: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub ts: i64,
+}
+
+#[event]
+pub struct VaultFunded {
+    pub market: Pubkey,
+    pub funder: Pubkey,
+    pub amount: u64,
+    pub ts: i64,
+}
+
+#[event]
+pub struct MarketClosed {
+    pub market: Pubkey,
     pub ts: i64,
 }
 
 #[event]
 pub struct MarketResolved {
     pub market: Pubkey,
-    pub outcome_value: f64,
+    pub outcome_value: i64,
+    pub ts: i64,
+}
+
+#[event]
+pub struct MarketMigrated {
+    pub market: Pubkey,
+    pub version: u8,
+    pub decimals: u8,
     pub ts: i64,
 }
 
@@ -976,6 +2907,84 @@ pub struct PayoutClaimed {
     pub ts: i64,
 }
 
+#[event]
+pub struct PmAmmRedeemed {
+    pub market: Pubkey,
+    pub owner: Pubkey,
+    pub shares: u64,
+    pub amount: u64,
+    pub ts: i64,
+}
+
+#[event]
+pub struct PmAmmResidualWithdrawn {
+    pub market: Pubkey,
+    pub authority: Pubkey,
+    pub amount: u64,
+    pub ts: i64,
+}
+
+#[event]
+pub struct DistributionTraded {
+    pub market: Pubkey,
+    pub trader: Pubkey,
+    pub position: Pubkey,
+    pub from_mu: i64,
+    pub from_sigma: i64,
+    pub to_mu: i64,
+    pub to_sigma: i64,
+    pub collateral: u64,
+    pub l2_distance: u64,
+    pub ts: i64,
+}
+
+#[event]
+pub struct MixtureInitialized {
+    pub market: Pubkey,
+    pub len: u8,
+    pub ts: i64,
+}
+
+#[event]
+pub struct MixtureTraded {
+    pub market: Pubkey,
+    pub trader: Pubkey,
+    pub position: Pubkey,
+    pub from_len: u8,
+    pub to_len: u8,
+    pub collateral: u64,
+    pub l2_distance: u64,
+    pub ts: i64,
+}
+
+#[event]
+pub struct HistogramInitialized {
+    pub market: Pubkey,
+    pub bins: u16,
+    pub ts: i64,
+}
+
+#[event]
+pub struct HistogramTraded {
+    pub market: Pubkey,
+    pub trader: Pubkey,
+    pub position: Pubkey,
+    pub shifts: u8,
+    pub collateral: u64,
+    pub l2_distance: u64,
+    pub ts: i64,
+}
+
+#[event]
+pub struct HistogramPositionSettled {
+    pub market: Pubkey,
+    pub owner: Pubkey,
+    pub position: Pubkey,
+    pub bin: u16,
+    pub payout: u64,
+    pub ts: i64,
+}
+
 #[event]
 pub struct PositionOpened {
     pub owner: Pubkey,
@@ -1009,6 +3018,7 @@ pub struct LiquidityAdded {
     pub market: Pubkey,
     pub provider: Pubkey,
     pub amount: u64,
+    pub shares: u64,
     pub ts: i64,
 }
 
@@ -1017,5 +3027,25 @@ pub struct LiquidityRemoved {
     pub market: Pubkey,
     pub provider: Pubkey,
     pub amount: u64,
+    pub shares: u64,
+    pub ts: i64,
+}
+
+#[event]
+pub struct FeesCharged {
+    pub market: Pubkey,
+    pub taker: Pubkey,
+    pub platform_fee: u64,
+    pub creator_fee: u64,
+    pub lp_fee: u64,
+    pub ts: i64,
+}
+
+#[event]
+pub struct FeesClaimed {
+    pub market: Pubkey,
+    pub recipient: FeeRecipient,
+    pub claimant: Pubkey,
+    pub amount: u64,
     pub ts: i64,
 }
@@ -1,6 +1,13 @@
 
 // Simple Gaussian PDF and helper integrals (scaffold)
 // NOTE: On-chain math should be deterministic and use conservative rounding.
+// The f64 versions are kept as references for off-chain tooling; instructions use the
+// `*_fixed` counterparts backed by `crate::fixed::Fixed`.
+
+use crate::fixed::Fixed;
+
+/// Upper bound on grid points visited by the fixed-point discretized integrals.
+pub const MAX_GRID_POINTS: u32 = 4_096;
 
 pub fn gauss_pdf(x: f64, mu: f64, sigma: f64) -> f64 {
     if sigma <= 0.0 { return 0.0; }
@@ -31,6 +38,47 @@ pub fn phi_cdf_as(z: f64) -> f64 {
     1.0 - pdf * poly
 }
 
+/// Inverse standard normal CDF, Wichura's AS241 (PPND16): relative error ~1e-16 on (0, 1).
+// Coefficients are kept exactly as published
+#[allow(clippy::excessive_precision)]
+pub fn probit(p: f64) -> f64 {
+    const A: [f64; 8] = [
+        3.387_132_872_796_366_608, 133.141_667_891_784_377_45, 1_971.590_950_306_551_442_7, 13_731.693_765_509_461_125,
+        45_921.953_931_549_871_457, 67_265.770_927_008_700_853, 33_430.575_583_588_128_105, 2_509.080_928_730_122_672_7,
+    ];
+    const B: [f64; 8] = [
+        1.0, 42.313_330_701_600_911_252, 687.187_007_492_057_908_3, 5_394.196_021_424_751_107_7,
+        21_213.794_301_586_595_867, 39_307.895_800_092_710_61, 28_729.085_735_721_942_674, 5_226.495_278_852_854_561,
+    ];
+    const C: [f64; 8] = [
+        1.423_437_110_749_683_577_34, 4.630_337_846_156_545_295_9, 5.769_497_221_460_691_405_5, 3.647_848_324_763_204_605_04,
+        1.270_458_252_452_368_382_58, 0.241_780_725_177_450_611_77, 0.022_723_844_989_269_184_583_3, 7.745_450_142_783_414_076_4e-4,
+    ];
+    const D: [f64; 8] = [
+        1.0, 2.053_191_626_637_758_821_87, 1.676_384_830_183_803_849_4, 0.689_767_334_985_100_004_55,
+        0.148_103_976_427_480_074_59, 0.015_198_666_563_616_457_196_6, 5.475_938_084_995_344_946e-4, 1.050_750_071_644_416_843_24e-9,
+    ];
+    const E: [f64; 8] = [
+        6.657_904_643_501_103_777_2, 5.463_784_911_164_114_369_9, 1.784_826_539_917_291_335_8, 0.296_560_571_828_504_891_23,
+        0.026_532_189_526_576_123_093, 0.001_242_660_947_388_078_438_6, 2.711_555_568_743_487_578_15e-5, 2.010_334_399_292_288_132_65e-7,
+    ];
+    const F: [f64; 8] = [
+        1.0, 0.599_832_206_555_887_937_69, 0.136_929_880_922_735_805_31, 0.014_875_361_290_850_614_852_5,
+        7.868_691_311_456_132_591e-4, 1.846_318_317_510_054_681_8e-5, 1.421_511_758_316_445_888_7e-7, 2.044_263_103_389_939_785_64e-15,
+    ];
+    let poly = |c: &[f64; 8], x: f64| c.iter().rev().fold(0.0, |acc, &k| acc * x + k);
+    if p <= 0.0 { return f64::NEG_INFINITY; }
+    if p >= 1.0 { return f64::INFINITY; }
+    let q = p - 0.5;
+    if q.abs() <= 0.425 {
+        let r = 0.180_625 - q * q;
+        return q * poly(&A, r) / poly(&B, r);
+    }
+    let r = (-(p.min(1.0 - p)).ln()).sqrt();
+    let z = if r <= 5.0 { poly(&C, r - 1.6) / poly(&D, r - 1.6) } else { poly(&E, r - 5.0) / poly(&F, r - 5.0) };
+    if q < 0.0 { -z } else { z }
+}
+
 /// Discretized integral of |g(x) - f(x)| over [min, max] with step.
 /// Returns the L1 distance approximation.
 pub fn discretized_l1_distance<F, G>(min: f64, max: f64, step: f64, f: F, g: G) -> f64
@@ -71,11 +119,976 @@ pub fn conservative_round(amount: f64) -> i64 {
     if amount >= 0.0 { amount.floor() as i64 } else { amount.ceil() as i64 }
 }
 
-pub fn effective_liquidity(l0: u64, dynamic_on: u8, expiry_ts: i64, now_ts: i64) -> f64 {
-    if dynamic_on == 0 { return l0 as f64; }
+/// Liquidity schedules of `effective_liquidity`: held at `l0`, or decaying with the time left.
+pub const LIQUIDITY_STATIC: u8 = 0;
+pub const LIQUIDITY_SQRT_DECAY: u8 = 1;
+pub const LIQUIDITY_LINEAR_DECAY: u8 = 2;
+
+/// pm-AMM liquidity at `now_ts`: `l0`, `l0 * sqrt(T - t)` or `l0 * (T - t)` by `schedule`, with
+/// T - t counted as at least 1s, then clamped to [floor, ceiling]. Unknown schedules are static.
+pub fn effective_liquidity(l0: u64, schedule: u8, expiry_ts: i64, now_ts: i64, floor: u64, ceiling: u64) -> f64 {
     let dt = (expiry_ts - now_ts).max(1) as f64;
-    let mut l = (l0 as f64) * dt.sqrt();
-    if !l.is_finite() || l <= 0.0 { l = l0 as f64; }
-    let cap = (l0 as f64) * 1_000_000.0;
-    if l > cap { cap } else { l }
+    let l = match schedule {
+        LIQUIDITY_SQRT_DECAY => (l0 as f64) * dt.sqrt(),
+        LIQUIDITY_LINEAR_DECAY => (l0 as f64) * dt,
+        _ => l0 as f64,
+    };
+    l.max(floor as f64).min(ceiling as f64)
+}
+
+// ====== Fixed-point counterparts ======
+
+/// Standard normal pdf phi(z) in Q64.64.
+pub fn std_normal_pdf_fixed(z: Fixed) -> Option<Fixed> {
+    // exp(-z^2/2) is below 2^-64 once |z| > ~9.4; skip the square to avoid overflow on huge z
+    if z.abs() > Fixed::from_int(40) { return Some(Fixed::ZERO); }
+    let half_z2 = z.checked_mul(z)?.checked_div(Fixed::TWO)?;
+    half_z2.checked_neg()?.exp()?.checked_mul(Fixed::FRAC_1_SQRT_2PI)
+}
+
+/// Gaussian pdf in Q64.64. Returns zero for non-positive sigma, like `gauss_pdf`.
+pub fn gauss_pdf_fixed(x: Fixed, mu: Fixed, sigma: Fixed) -> Option<Fixed> {
+    if !sigma.is_positive() { return Some(Fixed::ZERO); }
+    let z = x.checked_sub(mu)?.checked_div(sigma)?;
+    std_normal_pdf_fixed(z)?.checked_div(sigma)
+}
+
+/// Standard normal CDF using the same Abramowitz–Stegun polynomial as `phi_cdf_as`.
+/// Tracks the f64 version to ~1e-15; both are within ~7.5e-8 of the true CDF.
+pub fn phi_cdf_fixed(z: Fixed) -> Option<Fixed> {
+    if z.is_negative() {
+        return Fixed::ONE.checked_sub(phi_cdf_fixed(z.checked_neg()?)?);
+    }
+    const P: Fixed = Fixed::from_raw(4_273_038_846_047_820_584);
+    const A: [Fixed; 5] = [
+        Fixed::from_raw(5_891_549_345_779_789_371),
+        Fixed::from_raw(-6_577_440_832_507_964_494),
+        Fixed::from_raw(32_862_467_576_799_067_950),
+        Fixed::from_raw(-33_596_242_918_879_593_516),
+        Fixed::from_raw(24_539_231_939_563_107_688),
+    ];
+    let t = Fixed::ONE.checked_div(Fixed::ONE.checked_add(P.checked_mul(z)?)?)?;
+    // Horner form of a1*t + a2*t^2 + ... + a5*t^5
+    let mut poly = Fixed::ZERO;
+    for a in A.iter().rev() {
+        poly = poly.checked_add(*a)?.checked_mul(t)?;
+    }
+    Fixed::ONE.checked_sub(std_normal_pdf_fixed(z)?.checked_mul(poly)?)
+}
+
+// AS241 coefficients in Q64.64, lowest degree first.
+const PROBIT_A: [Fixed; 8] = [
+    Fixed::from_raw(62_481_573_248_123_184_266),
+    Fixed::from_raw(2_456_030_273_146_578_755_729),
+    Fixed::from_raw(36_369_433_678_346_760_903_577),
+    Fixed::from_raw(253_305_040_590_906_049_334_752),
+    Fixed::from_raw(847_110_531_539_980_635_625_335),
+    Fixed::from_raw(1_240_834_461_211_302_004_542_253),
+    Fixed::from_raw(616_685_272_027_253_537_123_219),
+    Fixed::from_raw(46_284_373_752_510_048_256_847),
+];
+const PROBIT_B: [Fixed; 8] = [
+    Fixed::from_raw(18_446_744_073_709_551_616),
+    Fixed::from_raw(780_543_182_358_669_033_427),
+    Fixed::from_raw(12_676_362_857_984_320_466_166),
+    Fixed::from_raw(99_505_353_490_644_669_015_488),
+    Fixed::from_raw(391_325_434_313_685_993_834_011),
+    Fixed::from_raw(725_102_693_900_352_783_282_037),
+    Fixed::from_raw(529_958_092_038_522_359_607_639),
+    Fixed::from_raw(96_411_820_811_449_845_283_384),
+];
+const PROBIT_C: [Fixed; 8] = [
+    Fixed::from_raw(26_257_780_087_019_972_219),
+    Fixed::from_raw(85_414_657_222_861_301_469),
+    Fixed::from_raw(106_428_438_678_263_733_664),
+    Fixed::from_raw(67_290_924_466_616_960_350),
+    Fixed::from_raw(23_435_818_239_321_119_882),
+    Fixed::from_raw(4_460_067_159_304_334_850),
+    Fixed::from_raw(419_180_952_887_695_820),
+    Fixed::from_raw(14_287_833_651_960_274),
+];
+const PROBIT_D: [Fixed; 8] = [
+    Fixed::from_raw(18_446_744_073_709_551_616),
+    Fixed::from_raw(37_874_700_470_870_151_902),
+    Fixed::from_raw(30_923_841_931_449_676_725),
+    Fixed::from_raw(12_723_961_498_874_824_579),
+    Fixed::from_raw(2_732_036_149_456_437_196),
+    Fixed::from_raw(280_365_912_360_679_398),
+    Fixed::from_raw(10_101_322_841_738_831),
+    Fixed::from_raw(19_382_917_657),
+];
+const PROBIT_E: [Fixed; 8] = [
+    Fixed::from_raw(122_816_663_025_827_291_070),
+    Fixed::from_raw(100_789_041_930_040_295_696),
+    Fixed::from_raw(32_924_238_397_818_818_415),
+    Fixed::from_raw(5_470_576_970_873_388_408),
+    Fixed::from_raw(489_432_509_911_906_733),
+    Fixed::from_raw(22_923_048_466_861_333),
+    Fixed::from_raw(500_193_716_182_531),
+    Fixed::from_raw(3_708_412_416_632),
+];
+const PROBIT_F: [Fixed; 8] = [
+    Fixed::from_raw(18_446_744_073_709_551_616),
+    Fixed::from_raw(11_064_951_201_504_949_469),
+    Fixed::from_raw(2_525_910_469_425_231_306),
+    Fixed::from_raw(274_401_982_736_287_045),
+    Fixed::from_raw(14_515_173_481_745_325),
+    Fixed::from_raw(340_585_614_817_100),
+    Fixed::from_raw(2_622_226_360_343),
+    Fixed::from_raw(37_710),
+];
+
+fn poly_fixed(coeffs: &[Fixed; 8], x: Fixed) -> Option<Fixed> {
+    coeffs.iter().rev().try_fold(Fixed::ZERO, |acc, &c| acc.checked_mul(x)?.checked_add(c))
+}
+
+/// Fixed-point `probit` (AS241), for p in (0, 1). Inverts the true normal CDF, not the A&S
+/// approximation behind `phi_cdf_fixed`, so `phi_cdf_fixed(probit_fixed(p))` is only within ~1e-7 of p.
+pub fn probit_fixed(p: Fixed) -> Option<Fixed> {
+    if !p.is_positive() || p >= Fixed::ONE { return None; }
+    let q = p.checked_sub(Fixed::HALF)?;
+    if q.abs() <= Fixed::from_raw(7_839_866_231_326_559_437) {
+        // r = 0.180625 - q^2
+        let r = Fixed::from_raw(3_331_943_148_313_787_761).checked_sub(q.checked_mul(q)?)?;
+        return q.checked_mul(poly_fixed(&PROBIT_A, r)?)?.checked_div(poly_fixed(&PROBIT_B, r)?);
+    }
+    let tail = p.min(Fixed::ONE.checked_sub(p)?);
+    let r = tail.ln()?.checked_neg()?.sqrt()?;
+    let z = if r <= Fixed::from_int(5) {
+        let r = r.checked_sub(Fixed::from_raw(29_514_790_517_935_282_586))?; // r - 1.6
+        poly_fixed(&PROBIT_C, r)?.checked_div(poly_fixed(&PROBIT_D, r)?)?
+    } else {
+        let r = r.checked_sub(Fixed::from_int(5))?;
+        poly_fixed(&PROBIT_E, r)?.checked_div(poly_fixed(&PROBIT_F, r)?)?
+    };
+    if q.is_negative() { z.checked_neg() } else { Some(z) }
+}
+
+/// Fixed-point `discretized_l1_distance`. The grid is walked with exact additions so there is
+/// no accumulated drift; `None` if the grid exceeds `MAX_GRID_POINTS` or arithmetic overflows.
+pub fn discretized_l1_distance_fixed<F, G>(min: Fixed, max: Fixed, step: Fixed, f: F, g: G) -> Option<Fixed>
+where
+    F: Fn(Fixed) -> Option<Fixed>,
+    G: Fn(Fixed) -> Option<Fixed>,
+{
+    grid_sum(min, max, step, |x| Some(g(x)?.checked_sub(f(x)?)?.abs()))
+}
+
+/// Fixed-point `discretized_l2_sq`; same grid and bounds as `discretized_l1_distance_fixed`.
+pub fn discretized_l2_sq_fixed<F, G>(min: Fixed, max: Fixed, step: Fixed, f: F, g: G) -> Option<Fixed>
+where
+    F: Fn(Fixed) -> Option<Fixed>,
+    G: Fn(Fixed) -> Option<Fixed>,
+{
+    grid_sum(min, max, step, |x| {
+        let d = g(x)?.checked_sub(f(x)?)?;
+        d.checked_mul(d)
+    })
+}
+
+fn grid_sum<H>(min: Fixed, max: Fixed, step: Fixed, h: H) -> Option<Fixed>
+where
+    H: Fn(Fixed) -> Option<Fixed>,
+{
+    if !step.is_positive() || max <= min { return Some(Fixed::ZERO); }
+    let points = max.checked_sub(min)?.checked_div(step)?.floor_to_i64()?.checked_add(1)?;
+    if points > MAX_GRID_POINTS as i64 { return None; }
+    let mut acc = Fixed::ZERO;
+    let mut x = min;
+    for _ in 0..points {
+        acc = acc.checked_add(h(x)?.checked_mul(step)?)?;
+        x = x.checked_add(step)?;
+    }
+    Some(acc)
+}
+
+/// Fixed-point `effective_liquidity`. `None` for an unknown schedule, or if the decayed
+/// liquidity overflows before the ceiling applies.
+pub fn effective_liquidity_fixed(l0: u64, schedule: u8, expiry_ts: i64, now_ts: i64, floor: u64, ceiling: u64) -> Option<Fixed> {
+    let int = |v: u64| Some(Fixed::from_int(i64::try_from(v).ok()?));
+    let base = int(l0)?;
+    let dt = Fixed::from_int(expiry_ts.saturating_sub(now_ts).max(1));
+    let l = match schedule {
+        LIQUIDITY_STATIC => base,
+        LIQUIDITY_SQRT_DECAY => base.checked_mul(dt.sqrt()?)?,
+        LIQUIDITY_LINEAR_DECAY => base.checked_mul(dt)?,
+        _ => return None,
+    };
+    Some(l.max(int(floor)?).min(int(ceiling)?))
+}
+
+// ====== pm-AMM invariant ======
+// Paradigm's pm-AMM keeps the reserves (x, y) of the two outcome tokens on
+// (y - x)·Φ((y - x)/L) + L·φ((y - x)/L) - y = 0. Writing z = (y - x)/L, that is
+// y = L·ŷ(z) and x = L·ŷ(-z) with ŷ(z) = z·Φ(z) + φ(z): the price of X is Φ(z) and the
+// pool is worth L·φ(z) at that price, which is what makes its LVR uniform in the price.
+
+/// |z| the solver searches within; Φ is within 1e-9 of 0 or 1 beyond it.
+pub const PMAMM_MAX_Z: i64 = 6;
+/// Bisection steps of `pmamm_z_fixed`: pins z to 12 / 2^48 ≈ 4e-14, far below a reserve unit.
+pub const PMAMM_SOLVER_STEPS: u32 = 48;
+
+/// ŷ(z) = z·Φ(z) + φ(z), the Y reserve per unit of liquidity at z. Floored at zero, since
+/// the A&S Φ's absolute error outweighs the true value far in the left tail.
+pub fn pmamm_reserve_fixed(z: Fixed) -> Option<Fixed> {
+    let y = z.checked_mul(phi_cdf_fixed(z)?)?.checked_add(std_normal_pdf_fixed(z)?)?;
+    Some(y.max(Fixed::ZERO))
+}
+
+/// Residual of the invariant at reserves (x, y) and liquidity `l`; zero on the curve.
+pub fn pmamm_invariant_fixed(x: Fixed, y: Fixed, l: Fixed) -> Option<Fixed> {
+    let d = y.checked_sub(x)?;
+    let z = d.checked_div(l)?;
+    d.checked_mul(phi_cdf_fixed(z)?)?
+        .checked_add(l.checked_mul(std_normal_pdf_fixed(z)?)?)?
+        .checked_sub(y)
+}
+
+/// Where reserves (x, y) sit on the curve: the z with x·ŷ(z) = y·ŷ(-z), whatever L they
+/// were left at. Since ŷ(-z) = ŷ(z) - z this is the root of (x - y)·ŷ(z) + y·z, which
+/// increases in z; it is found by bisection over [-PMAMM_MAX_Z, PMAMM_MAX_Z] in a fixed
+/// number of steps, so every validator lands on the same z. Clamps to the bounds when the
+/// root lies beyond them.
+pub fn pmamm_z_fixed(x: u64, y: u64) -> Option<Fixed> {
+    let (x, y) = (Fixed::from_int(i64::try_from(x).ok()?), Fixed::from_int(i64::try_from(y).ok()?));
+    let h = |z: Fixed| x.checked_sub(y)?.checked_mul(pmamm_reserve_fixed(z)?)?.checked_add(y.checked_mul(z)?);
+    let (mut lo, mut hi) = (Fixed::from_int(-PMAMM_MAX_Z), Fixed::from_int(PMAMM_MAX_Z));
+    if !h(lo)?.is_negative() { return Some(lo); }
+    if h(hi)?.is_negative() { return Some(hi); }
+    for _ in 0..PMAMM_SOLVER_STEPS {
+        let mid = lo.checked_add(hi)?.checked_div(Fixed::TWO)?;
+        let at = h(mid)?;
+        if at == Fixed::ZERO { return Some(mid); }
+        if at.is_negative() { lo = mid; } else { hi = mid; }
+    }
+    lo.checked_add(hi)?.checked_div(Fixed::TWO)
+}
+
+// ====== Gaussian L2 geometry (closed form) ======
+// Distribution-market positions are L2-normalised: the market holds f = k * p / ||p||_2 and a
+// trader moving it to g = k * q / ||q||_2 is paid g(x) - f(x) at the outcome x.
+
+/// ||p||_2 for N(mu, sigma^2): sqrt(1 / (2 * sigma * sqrt(pi))).
+pub fn gauss_l2_norm_fixed(sigma: Fixed) -> Option<Fixed> {
+    if !sigma.is_positive() { return None; }
+    Fixed::FRAC_1_SQRT_PI.checked_div(sigma.checked_mul_int(2)?)?.sqrt()
+}
+
+/// <p, q> = integral of p(x) q(x) dx = N(mu_a - mu_b; 0, sigma_a^2 + sigma_b^2).
+pub fn gauss_inner_product_fixed(mu_a: Fixed, sigma_a: Fixed, mu_b: Fixed, sigma_b: Fixed) -> Option<Fixed> {
+    if !sigma_a.is_positive() || !sigma_b.is_positive() { return None; }
+    gauss_pdf_fixed(mu_a, mu_b, hypot_fixed(sigma_a, sigma_b)?)
+}
+
+/// sqrt(a^2 + b^2) for positive a, b. Scales by the larger argument instead of squaring both:
+/// sigma^2 underflows Q64.64 below ~2^-32 and overflows it above ~2^31.
+fn hypot_fixed(a: Fixed, b: Fixed) -> Option<Fixed> {
+    let (big, small) = if a >= b { (a, b) } else { (b, a) };
+    let r = small.checked_div(big)?;
+    big.checked_mul(Fixed::ONE.checked_add(r.checked_mul(r)?)?.sqrt()?)
+}
+
+/// ||p - q||_2 between two normal pdfs.
+pub fn gauss_l2_distance_fixed(mu_a: Fixed, sigma_a: Fixed, mu_b: Fixed, sigma_b: Fixed) -> Option<Fixed> {
+    let na = gauss_l2_norm_fixed(sigma_a)?;
+    let nb = gauss_l2_norm_fixed(sigma_b)?;
+    let cross = gauss_inner_product_fixed(mu_a, sigma_a, mu_b, sigma_b)?;
+    let d2 = na.checked_mul(na)?.checked_add(nb.checked_mul(nb)?)?.checked_sub(cross.checked_mul_int(2)?)?;
+    // rounding can push identical distributions a few ulps below zero
+    d2.max(Fixed::ZERO).sqrt()
+}
+
+/// Cosine similarity <p, q> / (||p|| ||q||) = sqrt(2 sa sb / S^2) * exp(-(mu_a - mu_b)^2 / (2 S^2)),
+/// with S^2 = sa^2 + sb^2. Dimensionless, so it stays well-conditioned for any outcome scale.
+pub fn gauss_overlap_fixed(mu_a: Fixed, sigma_a: Fixed, mu_b: Fixed, sigma_b: Fixed) -> Option<Fixed> {
+    if !sigma_a.is_positive() || !sigma_b.is_positive() { return None; }
+    let s = hypot_fixed(sigma_a, sigma_b)?;
+    let shape = sigma_a.checked_div(s)?.checked_mul(sigma_b.checked_div(s)?)?.checked_mul_int(2)?.sqrt()?;
+    let z = mu_a.checked_sub(mu_b)?.checked_div(s)?;
+    Some(shape.checked_mul(std_normal_pdf_fixed(z)?.checked_mul(Fixed::SQRT_2PI)?)?.min(Fixed::ONE))
+}
+
+/// ||f - g||_2 for the k-scaled functions: k * sqrt(2 - 2 * overlap).
+pub fn gauss_scaled_l2_distance_fixed(k: Fixed, mu_a: Fixed, sigma_a: Fixed, mu_b: Fixed, sigma_b: Fixed) -> Option<Fixed> {
+    let rho = gauss_overlap_fixed(mu_a, sigma_a, mu_b, sigma_b)?;
+    k.checked_mul(Fixed::ONE.checked_sub(rho)?.checked_mul_int(2)?.sqrt()?)
+}
+
+/// f(x) = k * p(x) / ||p||_2 for N(mu, sigma^2), with the constants hoisted out of the hot loop.
+#[derive(Clone, Copy, Debug)]
+pub struct ScaledGauss {
+    mu: Fixed,
+    inv_sigma: Fixed,
+    peak: Fixed,
+}
+
+impl ScaledGauss {
+    pub fn new(k: Fixed, mu: Fixed, sigma: Fixed) -> Option<ScaledGauss> {
+        let norm = gauss_l2_norm_fixed(sigma)?;
+        // peak = k / (||p|| * sigma * sqrt(2 pi))
+        let peak = k.checked_div(norm.checked_mul(sigma)?.checked_mul(Fixed::SQRT_2PI)?)?;
+        Some(ScaledGauss { mu, inv_sigma: Fixed::ONE.checked_div(sigma)?, peak })
+    }
+
+    pub fn peak(&self) -> Fixed { self.peak }
+
+    pub fn eval(&self, x: Fixed) -> Option<Fixed> {
+        let z = x.checked_sub(self.mu)?.checked_mul(self.inv_sigma)?;
+        if z.abs() > Fixed::from_int(40) { return Some(Fixed::ZERO); }
+        z.checked_mul(z)?.checked_div_int(2)?.checked_neg()?.exp()?.checked_mul(self.peak)
+    }
+}
+
+/// Coarse grid cells and golden-section steps used by `max_on_interval`.
+pub const MAX_SEARCH_GRID: i64 = 16;
+pub const MAX_SEARCH_REFINE: u32 = 24;
+/// (sqrt(5) - 1) / 2
+const INV_GOLDEN: Fixed = Fixed::from_raw(11_400_714_819_323_198_486);
+
+/// Bounded-cost maximum of `h` on [lo, hi]: a `MAX_SEARCH_GRID`-cell scan followed by
+/// `MAX_SEARCH_REFINE` golden-section steps around the best grid point
+/// (`MAX_SEARCH_GRID + MAX_SEARCH_REFINE + 3` evaluations in total).
+pub fn max_on_interval<H>(lo: Fixed, hi: Fixed, h: H) -> Option<Fixed>
+where
+    H: Fn(Fixed) -> Option<Fixed>,
+{
+    max_on_interval_with(lo, hi, MAX_SEARCH_GRID, MAX_SEARCH_REFINE, h)
+}
+
+/// `max_on_interval` with an explicit grid size and refinement count.
+pub fn max_on_interval_with<H>(lo: Fixed, hi: Fixed, grid: i64, refine: u32, h: H) -> Option<Fixed>
+where
+    H: Fn(Fixed) -> Option<Fixed>,
+{
+    if hi <= lo || grid <= 0 { return h(lo); }
+    let cell = hi.checked_sub(lo)?.checked_div_int(grid)?;
+    let mut best_x = lo;
+    let mut best = h(lo)?;
+    for i in 1..=grid {
+        let x = lo.checked_add(cell.checked_mul_int(i)?)?;
+        let v = h(x)?;
+        if v > best { best = v; best_x = x; }
+    }
+    let mut a = best_x.checked_sub(cell)?.max(lo);
+    let mut b = best_x.checked_add(cell)?.min(hi);
+    let mut c = b.checked_sub(b.checked_sub(a)?.checked_mul(INV_GOLDEN)?)?;
+    let mut d = a.checked_add(b.checked_sub(a)?.checked_mul(INV_GOLDEN)?)?;
+    let mut hc = h(c)?;
+    let mut hd = h(d)?;
+    for _ in 0..refine {
+        if hc > hd {
+            b = d;
+            d = c;
+            hd = hc;
+            c = b.checked_sub(b.checked_sub(a)?.checked_mul(INV_GOLDEN)?)?;
+            hc = h(c)?;
+        } else {
+            a = c;
+            c = d;
+            hc = hd;
+            d = a.checked_add(b.checked_sub(a)?.checked_mul(INV_GOLDEN)?)?;
+            hd = h(d)?;
+        }
+    }
+    Some(best.max(hc).max(hd))
+}
+
+/// Worst-case loss max_x (f(x) - g(x)) of moving the k-scaled market from N(mu_a, sigma_a^2)
+/// to N(mu_b, sigma_b^2). f - g is negligible beyond 6 sigma_a of mu_a, so the search stays there.
+pub fn gauss_max_loss_fixed(k: Fixed, mu_a: Fixed, sigma_a: Fixed, mu_b: Fixed, sigma_b: Fixed) -> Option<Fixed> {
+    let f = ScaledGauss::new(k, mu_a, sigma_a)?;
+    let g = ScaledGauss::new(k, mu_b, sigma_b)?;
+    let reach = sigma_a.checked_mul_int(6)?;
+    let loss = max_on_interval(mu_a.checked_sub(reach)?, mu_a.checked_add(reach)?, |x| {
+        f.eval(x)?.checked_sub(g.eval(x)?)
+    })?;
+    Some(loss.max(Fixed::ZERO))
+}
+
+/// Collateral (in base units, rounded up) a trader must post to move the market
+/// from (mu_a, sigma_a) to (mu_b, sigma_b) with L2 norm `k`.
+pub fn gauss_collateral_required(k: u64, mu_a: Fixed, sigma_a: Fixed, mu_b: Fixed, sigma_b: Fixed) -> Option<u64> {
+    let k = Fixed::from_int(i64::try_from(k).ok()?);
+    let loss = gauss_max_loss_fixed(k, mu_a, sigma_a, mu_b, sigma_b)?;
+    u64::try_from(loss.ceil_to_i64()?).ok()
+}
+
+// ====== Distribution families ======
+
+pub const DIST_NORMAL: u8 = 0;
+pub const DIST_LOGNORMAL: u8 = 1;
+pub const DIST_UNIFORM: u8 = 2;
+pub const DIST_STUDENT_T: u8 = 3;
+pub const DIST_BETA: u8 = 4;
+
+/// Simpson panels used when an L2 inner product has no closed form.
+pub const INNER_PRODUCT_PANELS: i64 = 64;
+/// Bisection steps for quantiles without a closed-form inverse.
+pub const QUANTILE_BISECTIONS: u32 = 64;
+/// Continued-fraction terms for the regularized incomplete beta function.
+const BETA_CF_MAX_TERMS: i64 = 200;
+
+const HALF_LN_2PI: Fixed = Fixed::from_raw(16_951_423_941_496_644_917);
+const SQRT_3: Fixed = Fixed::from_raw(31_950_697_969_885_030_203);
+
+/// Outcome distribution of a market. Every family is built from the market's (mu, sigma):
+/// - `Normal`: mean mu, std sigma.
+/// - `LogNormal`: mu and sigma of ln X (price markets).
+/// - `Uniform`: mean mu, std sigma, i.e. [mu - sqrt(3) sigma, mu + sqrt(3) sigma].
+/// - `StudentT`: location mu, scale sigma, `nu` degrees of freedom (fat tails).
+/// - `Beta`: mean mu, std sigma on [outcome_min, outcome_max] (method of moments).
+///
+/// Normalising constants are precomputed by the constructors; build through `from_params`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Distribution {
+    Normal { mu: Fixed, sigma: Fixed },
+    LogNormal { mu: Fixed, sigma: Fixed },
+    Uniform { lo: Fixed, hi: Fixed },
+    StudentT { mu: Fixed, sigma: Fixed, nu: Fixed, ln_norm: Fixed },
+    Beta { alpha: Fixed, beta: Fixed, lo: Fixed, hi: Fixed, ln_beta: Fixed },
+}
+
+impl Distribution {
+    /// `None` for an unknown `dist_type` or parameters the family can't represent
+    /// (non-positive sigma or nu, beta moments outside [lo, hi] or with alpha/beta <= 1/2).
+    pub fn from_params(dist_type: u8, mu: Fixed, sigma: Fixed, shape: Fixed, lo: Fixed, hi: Fixed) -> Option<Distribution> {
+        if !sigma.is_positive() { return None; }
+        match dist_type {
+            DIST_NORMAL => Some(Distribution::Normal { mu, sigma }),
+            DIST_LOGNORMAL => Some(Distribution::LogNormal { mu, sigma }),
+            DIST_UNIFORM => {
+                let half = sigma.checked_mul(SQRT_3)?;
+                Some(Distribution::Uniform { lo: mu.checked_sub(half)?, hi: mu.checked_add(half)? })
+            }
+            DIST_STUDENT_T => {
+                if !shape.is_positive() { return None; }
+                // ln c = lnG((nu + 1) / 2) - lnG(nu / 2) - ln(sqrt(nu * pi))
+                let half_nu = shape.checked_div_int(2)?;
+                let ln_norm = ln_gamma(half_nu.checked_add(Fixed::HALF)?)?
+                    .checked_sub(ln_gamma(half_nu)?)?
+                    .checked_sub(shape.checked_mul(Fixed::PI)?.ln()?.checked_div_int(2)?)?;
+                Some(Distribution::StudentT { mu, sigma, nu: shape, ln_norm })
+            }
+            DIST_BETA => {
+                if hi <= lo || mu <= lo || mu >= hi { return None; }
+                let width = hi.checked_sub(lo)?;
+                let m = mu.checked_sub(lo)?.checked_div(width)?;
+                let sd = sigma.checked_div(width)?;
+                // common = m (1 - m) / v - 1 must be positive for a valid beta
+                let common = m.checked_mul(Fixed::ONE.checked_sub(m)?)?.checked_div(sd.checked_mul(sd)?)?.checked_sub(Fixed::ONE)?;
+                let alpha = m.checked_mul(common)?;
+                let beta = Fixed::ONE.checked_sub(m)?.checked_mul(common)?;
+                // the L2 norm is only finite for alpha, beta > 1/2
+                if alpha <= Fixed::HALF || beta <= Fixed::HALF { return None; }
+                Some(Distribution::Beta { alpha, beta, lo, hi, ln_beta: ln_beta(alpha, beta)? })
+            }
+            _ => None,
+        }
+    }
+
+    pub fn pdf(&self, x: Fixed) -> Option<Fixed> {
+        match *self {
+            Distribution::Normal { mu, sigma } => gauss_pdf_fixed(x, mu, sigma),
+            Distribution::LogNormal { mu, sigma } => {
+                if !x.is_positive() { return Some(Fixed::ZERO); }
+                gauss_pdf_fixed(x.ln()?, mu, sigma)?.checked_div(x)
+            }
+            Distribution::Uniform { lo, hi } => {
+                if x < lo || x > hi { return Some(Fixed::ZERO); }
+                Fixed::ONE.checked_div(hi.checked_sub(lo)?)
+            }
+            Distribution::StudentT { mu, sigma, nu, ln_norm } => {
+                let t = x.checked_sub(mu)?.checked_div(sigma)?;
+                // beyond 1e9 scales the density is below Q64.64 resolution for every nu
+                if t.abs() > Fixed::from_int(1_000_000_000) { return Some(Fixed::ZERO); }
+                let base = Fixed::ONE.checked_add(t.checked_mul(t)?.checked_div(nu)?)?;
+                let exponent = nu.checked_add(Fixed::ONE)?.checked_div_int(2)?;
+                ln_norm.checked_sub(exponent.checked_mul(base.ln()?)?)?.exp()?.checked_div(sigma)
+            }
+            Distribution::Beta { alpha, beta, lo, hi, ln_beta } => {
+                if x <= lo || x >= hi { return Some(Fixed::ZERO); }
+                let width = hi.checked_sub(lo)?;
+                let u = x.checked_sub(lo)?.checked_div(width)?;
+                let ln_p = alpha.checked_sub(Fixed::ONE)?.checked_mul(u.ln()?)?
+                    .checked_add(beta.checked_sub(Fixed::ONE)?.checked_mul(Fixed::ONE.checked_sub(u)?.ln()?)?)?
+                    .checked_sub(ln_beta)?;
+                ln_p.exp()?.checked_div(width)
+            }
+        }
+    }
+
+    /// ln p(x), computed directly so tails don't underflow through `pdf`. `None` where the
+    /// density is zero.
+    pub fn ln_pdf(&self, x: Fixed) -> Option<Fixed> {
+        match *self {
+            Distribution::Normal { mu, sigma } => {
+                let z = x.checked_sub(mu)?.checked_div(sigma)?;
+                z.checked_mul(z)?.checked_div_int(2)?.checked_add(sigma.ln()?)?.checked_add(HALF_LN_2PI)?.checked_neg()
+            }
+            Distribution::LogNormal { mu, sigma } => {
+                if !x.is_positive() { return None; }
+                let ln_x = x.ln()?;
+                Distribution::Normal { mu, sigma }.ln_pdf(ln_x)?.checked_sub(ln_x)
+            }
+            Distribution::Uniform { lo, hi } => {
+                if x < lo || x > hi { return None; }
+                hi.checked_sub(lo)?.ln()?.checked_neg()
+            }
+            Distribution::StudentT { mu, sigma, nu, ln_norm } => {
+                let t = x.checked_sub(mu)?.checked_div(sigma)?;
+                let base = Fixed::ONE.checked_add(t.checked_mul(t)?.checked_div(nu)?)?;
+                let exponent = nu.checked_add(Fixed::ONE)?.checked_div_int(2)?;
+                ln_norm.checked_sub(exponent.checked_mul(base.ln()?)?)?.checked_sub(sigma.ln()?)
+            }
+            Distribution::Beta { alpha, beta, lo, hi, ln_beta } => {
+                if x <= lo || x >= hi { return None; }
+                let width = hi.checked_sub(lo)?;
+                let u = x.checked_sub(lo)?.checked_div(width)?;
+                alpha.checked_sub(Fixed::ONE)?.checked_mul(u.ln()?)?
+                    .checked_add(beta.checked_sub(Fixed::ONE)?.checked_mul(Fixed::ONE.checked_sub(u)?.ln()?)?)?
+                    .checked_sub(ln_beta)?
+                    .checked_sub(width.ln()?)
+            }
+        }
+    }
+
+    pub fn cdf(&self, x: Fixed) -> Option<Fixed> {
+        match *self {
+            Distribution::Normal { mu, sigma } => phi_cdf_fixed(x.checked_sub(mu)?.checked_div(sigma)?),
+            Distribution::LogNormal { mu, sigma } => {
+                if !x.is_positive() { return Some(Fixed::ZERO); }
+                phi_cdf_fixed(x.ln()?.checked_sub(mu)?.checked_div(sigma)?)
+            }
+            Distribution::Uniform { lo, hi } => {
+                if x <= lo { return Some(Fixed::ZERO); }
+                if x >= hi { return Some(Fixed::ONE); }
+                x.checked_sub(lo)?.checked_div(hi.checked_sub(lo)?)
+            }
+            Distribution::StudentT { mu, sigma, nu, .. } => {
+                let t = x.checked_sub(mu)?.checked_div(sigma)?;
+                if t.abs() > Fixed::from_int(1_000_000_000) {
+                    return Some(if t.is_negative() { Fixed::ZERO } else { Fixed::ONE });
+                }
+                // P(|T| > |t|) / 2 = I_{nu / (nu + t^2)}(nu / 2, 1 / 2) / 2
+                let xb = nu.checked_div(nu.checked_add(t.checked_mul(t)?)?)?;
+                let tail = reg_inc_beta(xb, nu.checked_div_int(2)?, Fixed::HALF)?.checked_div_int(2)?;
+                if t.is_negative() { Some(tail) } else { Fixed::ONE.checked_sub(tail) }
+            }
+            Distribution::Beta { alpha, beta, lo, hi, .. } => {
+                if x <= lo { return Some(Fixed::ZERO); }
+                if x >= hi { return Some(Fixed::ONE); }
+                reg_inc_beta(x.checked_sub(lo)?.checked_div(hi.checked_sub(lo)?)?, alpha, beta)
+            }
+        }
+    }
+
+    /// Inverse CDF for p in (0, 1). Normal and lognormal go through `probit_fixed`, uniform is
+    /// closed form, and the rest bisect the CDF over a bracket widened until it contains p.
+    pub fn quantile(&self, p: Fixed) -> Option<Fixed> {
+        if !p.is_positive() || p >= Fixed::ONE { return None; }
+        match *self {
+            Distribution::Normal { mu, sigma } => mu.checked_add(sigma.checked_mul(probit_fixed(p)?)?),
+            Distribution::LogNormal { mu, sigma } => mu.checked_add(sigma.checked_mul(probit_fixed(p)?)?)?.exp(),
+            Distribution::Uniform { lo, hi } => lo.checked_add(hi.checked_sub(lo)?.checked_mul(p)?),
+            Distribution::StudentT { mu, sigma, nu, ln_norm } => {
+                let standard = Distribution::StudentT { mu: Fixed::ZERO, sigma: Fixed::ONE, nu, ln_norm };
+                let mut half_width = Fixed::ONE;
+                // widen until the bracket holds p; 2^30 standard scales covers p >= ~1e-18 for nu >= 1
+                for _ in 0..30 {
+                    let inside = standard.cdf(half_width.checked_neg()?)? <= p && standard.cdf(half_width)? >= p;
+                    if inside { break; }
+                    half_width = half_width.checked_mul_int(2)?;
+                }
+                let t = bisect_cdf(p, half_width.checked_neg()?, half_width, |x| standard.cdf(x))?;
+                mu.checked_add(sigma.checked_mul(t)?)
+            }
+            Distribution::Beta { lo, hi, .. } => bisect_cdf(p, lo, hi, |x| self.cdf(x)),
+        }
+    }
+
+    /// Squared L2 norm, the integral of p(x)^2 dx.
+    pub fn l2_norm_sq(&self) -> Option<Fixed> {
+        match *self {
+            Distribution::Normal { sigma, .. } => Fixed::FRAC_1_SQRT_PI.checked_div(sigma.checked_mul_int(2)?),
+            Distribution::LogNormal { mu, sigma } => {
+                // exp(-mu + sigma^2 / 4) / (2 sigma sqrt(pi))
+                let growth = sigma.checked_mul(sigma)?.checked_div_int(4)?.checked_sub(mu)?.exp()?;
+                growth.checked_mul(Fixed::FRAC_1_SQRT_PI)?.checked_div(sigma.checked_mul_int(2)?)
+            }
+            Distribution::Uniform { lo, hi } => Fixed::ONE.checked_div(hi.checked_sub(lo)?),
+            Distribution::StudentT { sigma, nu, ln_norm, .. } => {
+                // c^2 sqrt(nu) B(1/2, nu + 1/2) / sigma
+                let ln_int = ln_norm.checked_mul_int(2)?
+                    .checked_add(nu.ln()?.checked_div_int(2)?)?
+                    .checked_add(ln_beta(Fixed::HALF, nu.checked_add(Fixed::HALF)?)?)?;
+                ln_int.exp()?.checked_div(sigma)
+            }
+            Distribution::Beta { alpha, beta, lo, hi, ln_beta: ln_b } => {
+                // B(2a - 1, 2b - 1) / (B(a, b)^2 (hi - lo))
+                let a2 = alpha.checked_mul_int(2)?.checked_sub(Fixed::ONE)?;
+                let b2 = beta.checked_mul_int(2)?.checked_sub(Fixed::ONE)?;
+                ln_beta(a2, b2)?.checked_sub(ln_b.checked_mul_int(2)?)?.exp()?.checked_div(hi.checked_sub(lo)?)
+            }
+        }
+    }
+
+    pub fn l2_norm(&self) -> Option<Fixed> { self.l2_norm_sq()?.sqrt() }
+}
+
+/// Smallest x in [lo, hi] (to bisection precision) with cdf(x) >= p.
+fn bisect_cdf<C>(p: Fixed, mut lo: Fixed, mut hi: Fixed, cdf: C) -> Option<Fixed>
+where
+    C: Fn(Fixed) -> Option<Fixed>,
+{
+    for _ in 0..QUANTILE_BISECTIONS {
+        let mid = lo.checked_add(hi.checked_sub(lo)?.checked_div_int(2)?)?;
+        if mid == lo || mid == hi { break; }
+        if cdf(mid)? < p { lo = mid; } else { hi = mid; }
+    }
+    Some(hi)
+}
+
+/// ln Gamma(x) for x > 0: shift to x >= 10 with the recurrence, then Stirling's series
+/// (truncation error below 1e-15 there).
+pub fn ln_gamma(x: Fixed) -> Option<Fixed> {
+    if !x.is_positive() { return None; }
+    let mut z = x;
+    let mut ln_shift = Fixed::ZERO;
+    while z < Fixed::from_int(10) {
+        ln_shift = ln_shift.checked_add(z.ln()?)?;
+        z = z.checked_add(Fixed::ONE)?;
+    }
+    let inv = Fixed::ONE.checked_div(z)?;
+    let inv2 = inv.checked_mul(inv)?;
+    // 1/(12z) - 1/(360z^3) + 1/(1260z^5) - 1/(1680z^7)
+    let series = inv.checked_mul(
+        Fixed::ONE.checked_div_int(12)?.checked_sub(inv2.checked_mul(
+            Fixed::ONE.checked_div_int(360)?.checked_sub(inv2.checked_mul(
+                Fixed::ONE.checked_div_int(1260)?.checked_sub(inv2.checked_div_int(1680)?)?,
+            )?)?,
+        )?)?,
+    )?;
+    z.checked_sub(Fixed::HALF)?.checked_mul(z.ln()?)?
+        .checked_sub(z)?
+        .checked_add(HALF_LN_2PI)?
+        .checked_add(series)?
+        .checked_sub(ln_shift)
+}
+
+/// ln B(a, b) = ln Gamma(a) + ln Gamma(b) - ln Gamma(a + b).
+pub fn ln_beta(a: Fixed, b: Fixed) -> Option<Fixed> {
+    ln_gamma(a)?.checked_add(ln_gamma(b)?)?.checked_sub(ln_gamma(a.checked_add(b)?)?)
+}
+
+/// Regularized incomplete beta I_x(a, b) via the Lentz continued fraction, using the
+/// symmetry I_x(a, b) = 1 - I_{1-x}(b, a) so the fraction always converges quickly.
+pub fn reg_inc_beta(x: Fixed, a: Fixed, b: Fixed) -> Option<Fixed> {
+    if !x.is_positive() { return Some(Fixed::ZERO); }
+    if x >= Fixed::ONE { return Some(Fixed::ONE); }
+    let one_minus = Fixed::ONE.checked_sub(x)?;
+    let ln_front = a.checked_mul(x.ln()?)?.checked_add(b.checked_mul(one_minus.ln()?)?)?.checked_sub(ln_beta(a, b)?)?;
+    let front = ln_front.exp()?;
+    let pivot = a.checked_add(Fixed::ONE)?.checked_div(a.checked_add(b)?.checked_add(Fixed::TWO)?)?;
+    if x < pivot {
+        front.checked_mul(beta_cf(x, a, b)?)?.checked_div(a)
+    } else {
+        Fixed::ONE.checked_sub(front.checked_mul(beta_cf(one_minus, b, a)?)?.checked_div(b)?)
+    }
+}
+
+fn beta_cf(x: Fixed, a: Fixed, b: Fixed) -> Option<Fixed> {
+    let tiny = Fixed::from_raw(1 << 20);
+    let eps = Fixed::from_raw(1 << 24);
+    let guard = |v: Fixed| if v.abs() < tiny { tiny } else { v };
+    let qab = a.checked_add(b)?;
+    let qap = a.checked_add(Fixed::ONE)?;
+    let qam = a.checked_sub(Fixed::ONE)?;
+    let mut c = Fixed::ONE;
+    let mut d = Fixed::ONE.checked_div(guard(Fixed::ONE.checked_sub(qab.checked_mul(x)?.checked_div(qap)?)?))?;
+    let mut h = d;
+    for m in 1..=BETA_CF_MAX_TERMS {
+        let mf = Fixed::from_int(m);
+        let m2 = Fixed::from_int(2 * m);
+        // even step
+        let aa = mf.checked_mul(b.checked_sub(mf)?)?.checked_mul(x)?
+            .checked_div(qam.checked_add(m2)?.checked_mul(a.checked_add(m2)?)?)?;
+        d = Fixed::ONE.checked_div(guard(Fixed::ONE.checked_add(aa.checked_mul(d)?)?))?;
+        c = guard(Fixed::ONE.checked_add(aa.checked_div(c)?)?);
+        h = h.checked_mul(d.checked_mul(c)?)?;
+        // odd step
+        let aa = a.checked_add(mf)?.checked_mul(qab.checked_add(mf)?)?.checked_mul(x)?
+            .checked_div(a.checked_add(m2)?.checked_mul(qap.checked_add(m2)?)?)?
+            .checked_neg()?;
+        d = Fixed::ONE.checked_div(guard(Fixed::ONE.checked_add(aa.checked_mul(d)?)?))?;
+        c = guard(Fixed::ONE.checked_add(aa.checked_div(c)?)?);
+        let del = d.checked_mul(c)?;
+        h = h.checked_mul(del)?;
+        if del.checked_sub(Fixed::ONE)?.abs() < eps { break; }
+    }
+    Some(h)
+}
+
+/// f(x) = k * p(x) / ||p||_2 for any family.
+#[derive(Clone, Copy, Debug)]
+pub struct ScaledDistribution {
+    dist: Distribution,
+    scale: Fixed,
+}
+
+impl ScaledDistribution {
+    pub fn new(k: Fixed, dist: Distribution) -> Option<ScaledDistribution> {
+        Some(ScaledDistribution { dist, scale: k.checked_div(dist.l2_norm()?)? })
+    }
+
+    pub fn eval(&self, x: Fixed) -> Option<Fixed> { self.dist.pdf(x)?.checked_mul(self.scale) }
+}
+
+/// <p, q> over [lo, hi] by composite Simpson with `INNER_PRODUCT_PANELS` panels.
+pub fn inner_product_fixed(p: &Distribution, q: &Distribution, lo: Fixed, hi: Fixed) -> Option<Fixed> {
+    if hi <= lo { return Some(Fixed::ZERO); }
+    let n = INNER_PRODUCT_PANELS * 2;
+    let h = hi.checked_sub(lo)?.checked_div_int(n)?;
+    let mut acc = Fixed::ZERO;
+    for i in 0..=n {
+        let x = if i == n { hi } else { lo.checked_add(h.checked_mul_int(i)?)? };
+        let w = if i == 0 || i == n { 1 } else if i % 2 == 1 { 4 } else { 2 };
+        acc = acc.checked_add(p.pdf(x)?.checked_mul(q.pdf(x)?)?.checked_mul_int(w)?)?;
+    }
+    acc.checked_mul(h)?.checked_div_int(3)
+}
+
+/// ||f - g||_2 for the k-scaled pair. Closed form for two normals; otherwise the cross term is
+/// integrated over the market's outcome range [lo, hi].
+pub fn scaled_l2_distance_fixed(k: Fixed, from: &Distribution, to: &Distribution, lo: Fixed, hi: Fixed) -> Option<Fixed> {
+    if let (Distribution::Normal { mu: ma, sigma: sa }, Distribution::Normal { mu: mb, sigma: sb }) = (*from, *to) {
+        return gauss_scaled_l2_distance_fixed(k, ma, sa, mb, sb);
+    }
+    let rho = inner_product_fixed(from, to, lo, hi)?.checked_div(from.l2_norm()?.checked_mul(to.l2_norm()?)?)?;
+    let gap = Fixed::ONE.checked_sub(rho.min(Fixed::ONE))?;
+    k.checked_mul(gap.checked_mul_int(2)?.sqrt()?)
+}
+
+/// Worst-case loss max_x (f(x) - g(x)) over the outcome range [lo, hi].
+pub fn max_loss_fixed(k: Fixed, from: &Distribution, to: &Distribution, lo: Fixed, hi: Fixed) -> Option<Fixed> {
+    if let (Distribution::Normal { mu: ma, sigma: sa }, Distribution::Normal { mu: mb, sigma: sb }) = (*from, *to) {
+        return gauss_max_loss_fixed(k, ma, sa, mb, sb);
+    }
+    let f = ScaledDistribution::new(k, *from)?;
+    let g = ScaledDistribution::new(k, *to)?;
+    let loss = max_on_interval(lo, hi, |x| f.eval(x)?.checked_sub(g.eval(x)?))?;
+    Some(loss.max(Fixed::ZERO))
+}
+
+/// `max_loss_fixed` rounded up to whole collateral base units.
+pub fn collateral_required(k: u64, from: &Distribution, to: &Distribution, lo: Fixed, hi: Fixed) -> Option<u64> {
+    let k = Fixed::from_int(i64::try_from(k).ok()?);
+    u64::try_from(max_loss_fixed(k, from, to, lo, hi)?.ceil_to_i64()?).ok()
+}
+
+// ====== Gaussian mixtures ======
+
+/// Mixture size cap: a mixture trade evaluates 2K Gaussians at each of K * (MIXTURE_SEARCH_GRID +
+/// MIXTURE_SEARCH_REFINE + 3) search points, so compute grows with K^2.
+pub const MAX_MIXTURE_COMPONENTS: usize = 4;
+pub const MIXTURE_SEARCH_GRID: i64 = 8;
+pub const MIXTURE_SEARCH_REFINE: u32 = 12;
+
+/// One weighted component of a Gaussian mixture; weights of a mixture sum to one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GaussComponent {
+    pub weight: Fixed,
+    pub mu: Fixed,
+    pub sigma: Fixed,
+}
+
+pub fn mixture_pdf_fixed(mix: &[GaussComponent], x: Fixed) -> Option<Fixed> {
+    mix.iter().try_fold(Fixed::ZERO, |acc, c| acc.checked_add(c.weight.checked_mul(gauss_pdf_fixed(x, c.mu, c.sigma)?)?))
+}
+
+pub fn mixture_cdf_fixed(mix: &[GaussComponent], x: Fixed) -> Option<Fixed> {
+    mix.iter().try_fold(Fixed::ZERO, |acc, c| {
+        let z = x.checked_sub(c.mu)?.checked_div(c.sigma)?;
+        acc.checked_add(c.weight.checked_mul(phi_cdf_fixed(z)?)?)
+    })
+}
+
+/// Mixture inverse CDF for p in (0, 1), bisecting over the components' +/- 40 sigma envelope.
+pub fn mixture_quantile_fixed(mix: &[GaussComponent], p: Fixed) -> Option<Fixed> {
+    if mix.is_empty() || !p.is_positive() || p >= Fixed::ONE { return None; }
+    let mut lo = Fixed::MAX;
+    let mut hi = Fixed::MIN;
+    for c in mix {
+        let reach = c.sigma.checked_mul_int(40)?;
+        lo = lo.min(c.mu.checked_sub(reach)?);
+        hi = hi.max(c.mu.checked_add(reach)?);
+    }
+    bisect_cdf(p, lo, hi, |x| mixture_cdf_fixed(mix, x))
+}
+
+/// <p, q> for two mixtures: sum over component pairs of w_i v_j <N_i, N_j>, all closed form.
+pub fn mixture_inner_product_fixed(a: &[GaussComponent], b: &[GaussComponent]) -> Option<Fixed> {
+    let mut acc = Fixed::ZERO;
+    for ca in a {
+        for cb in b {
+            let cross = gauss_inner_product_fixed(ca.mu, ca.sigma, cb.mu, cb.sigma)?;
+            acc = acc.checked_add(ca.weight.checked_mul(cb.weight)?.checked_mul(cross)?)?;
+        }
+    }
+    Some(acc)
+}
+
+pub fn mixture_l2_norm_fixed(mix: &[GaussComponent]) -> Option<Fixed> {
+    mixture_inner_product_fixed(mix, mix)?.sqrt()
+}
+
+/// ||f - g||_2 for the k-scaled mixtures: k * sqrt(2 - 2 <p, q> / (||p|| ||q||)).
+pub fn mixture_scaled_l2_distance_fixed(k: Fixed, a: &[GaussComponent], b: &[GaussComponent]) -> Option<Fixed> {
+    let norms = mixture_l2_norm_fixed(a)?.checked_mul(mixture_l2_norm_fixed(b)?)?;
+    let rho = mixture_inner_product_fixed(a, b)?.checked_div(norms)?.min(Fixed::ONE);
+    k.checked_mul(Fixed::ONE.checked_sub(rho)?.checked_mul_int(2)?.sqrt()?)
+}
+
+/// k * w_i * N(mu_i, sigma_i^2) / ||p|| for every component, so evaluation is one exp per component.
+fn scaled_components(k: Fixed, mix: &[GaussComponent]) -> Option<Vec<ScaledGauss>> {
+    let scale = k.checked_div(mixture_l2_norm_fixed(mix)?)?;
+    mix.iter()
+        .map(|c| {
+            // ScaledGauss::new(k', ..) has peak k' / (||N|| sigma sqrt(2 pi)); pick k' so the
+            // component reads scale * w * pdf(x)
+            let k_c = scale.checked_mul(c.weight)?.checked_mul(gauss_l2_norm_fixed(c.sigma)?)?;
+            ScaledGauss::new(k_c, c.mu, c.sigma)
+        })
+        .collect()
+}
+
+fn eval_scaled(parts: &[ScaledGauss], x: Fixed) -> Option<Fixed> {
+    parts.iter().try_fold(Fixed::ZERO, |acc, g| acc.checked_add(g.eval(x)?))
+}
+
+/// f(x) = k * p(x) / ||p||_2 for the mixture, evaluated like `mixture_max_loss_fixed` does.
+pub fn mixture_scaled_density_fixed(k: Fixed, mix: &[GaussComponent], x: Fixed) -> Option<Fixed> {
+    eval_scaled(&scaled_components(k, mix)?, x)
+}
+
+/// Worst-case loss max_x (f(x) - g(x)) of replacing mixture `a` with `b`. f - g can only be
+/// positive where some component of `a` has mass, so each of a's components is searched
+/// over mu_i +/- 6 sigma_i.
+pub fn mixture_max_loss_fixed(k: Fixed, a: &[GaussComponent], b: &[GaussComponent]) -> Option<Fixed> {
+    let f = scaled_components(k, a)?;
+    let g = scaled_components(k, b)?;
+    let mut worst = Fixed::ZERO;
+    for c in a {
+        let reach = c.sigma.checked_mul_int(6)?;
+        let loss = max_on_interval_with(
+            c.mu.checked_sub(reach)?,
+            c.mu.checked_add(reach)?,
+            MIXTURE_SEARCH_GRID,
+            MIXTURE_SEARCH_REFINE,
+            |x| eval_scaled(&f, x)?.checked_sub(eval_scaled(&g, x)?),
+        )?;
+        worst = worst.max(loss);
+    }
+    Some(worst)
+}
+
+/// `mixture_max_loss_fixed` rounded up to whole collateral base units.
+pub fn mixture_collateral_required(k: u64, a: &[GaussComponent], b: &[GaussComponent]) -> Option<u64> {
+    let k = Fixed::from_int(i64::try_from(k).ok()?);
+    u64::try_from(mixture_max_loss_fixed(k, a, b)?.ceil_to_i64()?).ok()
+}
+
+// ====== Histograms ======
+
+/// Bin cap for histogram markets, bounding both the zero-copy accounts and the per-trade O(bins) scan.
+pub const MAX_HISTOGRAM_BINS: usize = 128;
+
+// Bin masses are integers summing to a fixed scale, so bin i holds probability mass[i] / scale
+// and density mass[i] / (scale * width). The scale cancels out of everything except ||p||.
+
+/// sqrt(sum mass^2); at most scale, so a 1e9 scale stays far from the Q64.64 limit.
+fn mass_norm(mass: &[u32]) -> Option<Fixed> {
+    let sum_sq = mass.iter().try_fold(0u128, |acc, &m| acc.checked_add(m as u128 * m as u128))?;
+    Fixed::from_ratio(i128::try_from(sum_sq).ok()?, 1)?.sqrt()
+}
+
+/// Per-bin factor c with f_i = c * mass[i] for f = k * p / ||p||_2, i.e. k / (sqrt(sum mass^2) * sqrt(width)).
+fn histogram_factor(k: Fixed, mass: &[u32], width: Fixed) -> Option<Fixed> {
+    let norm = mass_norm(mass)?.checked_mul(width.sqrt()?)?;
+    if !norm.is_positive() { return None; }
+    k.checked_div(norm)
+}
+
+/// Index of the bin containing `x`; the top edge belongs to the last bin and values outside
+/// [lo, lo + bins * width] are clamped.
+pub fn histogram_bin(x: Fixed, lo: Fixed, width: Fixed, bins: usize) -> Option<usize> {
+    if bins == 0 || !width.is_positive() { return None; }
+    let i = x.checked_sub(lo)?.checked_div(width)?.floor_to_i64()?;
+    Some(i.clamp(0, bins as i64 - 1) as usize)
+}
+
+/// P(X <= x) for the histogram on [lo, lo + bins * width]: full bins below x plus the
+/// covered fraction of x's own bin.
+pub fn histogram_cdf_fixed(mass: &[u32], scale: u32, lo: Fixed, width: Fixed, x: Fixed) -> Option<Fixed> {
+    if scale == 0 || !width.is_positive() || mass.is_empty() { return None; }
+    if x <= lo { return Some(Fixed::ZERO); }
+    let pos = x.checked_sub(lo)?.checked_div(width)?;
+    let full = pos.floor_to_i64()?;
+    if full >= mass.len() as i64 { return Some(Fixed::ONE); }
+    let below: u64 = mass[..full as usize].iter().map(|&m| m as u64).sum();
+    let partial = pos.checked_sub(pos.floor())?.checked_mul_int(mass[full as usize] as i64)?;
+    Fixed::from_int(below as i64).checked_add(partial)?.checked_div_int(scale as i64)
+}
+
+/// Histogram inverse CDF for p in (0, 1), interpolating linearly inside the bin that crosses p.
+pub fn histogram_quantile_fixed(mass: &[u32], scale: u32, lo: Fixed, width: Fixed, p: Fixed) -> Option<Fixed> {
+    if scale == 0 || !width.is_positive() || !p.is_positive() || p >= Fixed::ONE { return None; }
+    let target = p.checked_mul_int(scale as i64)?;
+    let mut below = Fixed::ZERO;
+    for (i, &m) in mass.iter().enumerate() {
+        let next = below.checked_add(Fixed::from_int(m as i64))?;
+        if next >= target && m > 0 {
+            let frac = target.checked_sub(below)?.checked_div_int(m as i64)?;
+            return lo.checked_add(width.checked_mul(frac.checked_add(Fixed::from_int(i as i64))?)?);
+        }
+        below = next;
+    }
+    // masses summing to less than the scale leave p unreachable
+    None
+}
+
+/// ||p||_2 of the piecewise-constant density with probability `mass[i] / scale` in bin i.
+pub fn histogram_l2_norm_fixed(mass: &[u32], scale: u32, width: Fixed) -> Option<Fixed> {
+    if scale == 0 { return None; }
+    mass_norm(mass)?.checked_div(width.sqrt()?)?.checked_div_int(scale as i64)
+}
+
+/// f = k * p / ||p||_2 on bin `bin`.
+pub fn histogram_scaled_density_fixed(k: Fixed, mass: &[u32], width: Fixed, bin: usize) -> Option<Fixed> {
+    histogram_factor(k, mass, width)?.checked_mul_int(*mass.get(bin)? as i64)
+}
+
+/// ||f - g||_2 = k * sqrt(2 - 2 <p, q> / (||p|| ||q||)); the bin width cancels in the cosine.
+pub fn histogram_scaled_l2_distance_fixed(k: Fixed, from: &[u32], to: &[u32]) -> Option<Fixed> {
+    if from.len() != to.len() { return None; }
+    let cross = from.iter().zip(to).try_fold(0u128, |acc, (&p, &q)| acc.checked_add(p as u128 * q as u128))?;
+    let norms = mass_norm(from)?.checked_mul(mass_norm(to)?)?;
+    let rho = Fixed::from_ratio(i128::try_from(cross).ok()?, 1)?.checked_div(norms)?.min(Fixed::ONE);
+    k.checked_mul(Fixed::ONE.checked_sub(rho)?.checked_mul_int(2)?.sqrt()?)
+}
+
+/// Worst-case loss max_i (f_i - g_i) of moving the histogram from `from` to `to`. Exact: both
+/// functions are constant on each bin, so the maximum is attained on one of them.
+pub fn histogram_max_loss_fixed(k: Fixed, from: &[u32], to: &[u32], width: Fixed) -> Option<Fixed> {
+    if from.len() != to.len() { return None; }
+    let cf = histogram_factor(k, from, width)?;
+    let cg = histogram_factor(k, to, width)?;
+    from.iter().zip(to).try_fold(Fixed::ZERO, |worst, (&p, &q)| {
+        Some(worst.max(cf.checked_mul_int(p as i64)?.checked_sub(cg.checked_mul_int(q as i64)?)?))
+    })
+}
+
+/// `histogram_max_loss_fixed` rounded up to whole collateral base units.
+pub fn histogram_collateral_required(k: u64, from: &[u32], to: &[u32], width: Fixed) -> Option<u64> {
+    let k = Fixed::from_int(i64::try_from(k).ok()?);
+    u64::try_from(histogram_max_loss_fixed(k, from, to, width)?.ceil_to_i64()?).ok()
 }
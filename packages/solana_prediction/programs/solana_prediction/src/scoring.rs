@@ -0,0 +1,181 @@
+// Proper scoring rules used to settle distribution positions.
+//
+// A position that moved the market from P to Q is paid its collateral plus
+// k * (S(Q, y) - S(P, y)) at the resolved outcome y. Scores are positively oriented, and all
+// but the spherical rule are made dimensionless with the outcome-range width W so that a
+// single `k` means the same thing whatever the market's unit:
+// - spherical: p(y) / ||p||_2, i.e. the L2-normalised payoff f = k p / ||p|| (the default).
+// - log: ln p(y). Rewards sharp, confident beliefs most; unbounded below, so every
+//   distribution traded must have positive density on the whole outcome range.
+// - quadratic: W * (2 p(y) - ||p||_2^2).
+// - CRPS: -CRPS(P, y) / W. Distance-sensitive: near misses lose less than far ones.
+
+use crate::fixed::Fixed;
+use crate::math::{max_loss_fixed, max_on_interval, phi_cdf_fixed, std_normal_pdf_fixed, Distribution, INNER_PRODUCT_PANELS};
+
+pub const SCORING_SPHERICAL: u8 = 0;
+pub const SCORING_LOG: u8 = 1;
+pub const SCORING_QUADRATIC: u8 = 2;
+pub const SCORING_CRPS: u8 = 3;
+
+/// Search windows are mu +/- this many sigmas, where a family has a location and scale.
+const FOCUS_SIGMAS: i64 = 6;
+
+pub fn spherical_score(dist: &Distribution, y: Fixed) -> Option<Fixed> {
+    dist.pdf(y)?.checked_div(dist.l2_norm()?)
+}
+
+pub fn log_score(dist: &Distribution, y: Fixed) -> Option<Fixed> {
+    dist.ln_pdf(y)
+}
+
+pub fn quadratic_score(dist: &Distribution, y: Fixed) -> Option<Fixed> {
+    dist.pdf(y)?.checked_mul_int(2)?.checked_sub(dist.l2_norm_sq()?)
+}
+
+/// CRPS(P, y) = integral of (F(x) - 1{x >= y})^2 dx, in outcome units (lower is better).
+/// Closed form for normal, lognormal and uniform. Student-t and beta are integrated over the
+/// outcome range [lo, hi], which is exact for beta (its support) and a threshold-weighted CRPS
+/// for Student-t; both are still proper.
+pub fn crps(dist: &Distribution, y: Fixed, lo: Fixed, hi: Fixed) -> Option<Fixed> {
+    match *dist {
+        Distribution::Normal { mu, sigma } => {
+            // sigma * (z (2 Phi(z) - 1) + 2 phi(z) - 1 / sqrt(pi))
+            let z = y.checked_sub(mu)?.checked_div(sigma)?;
+            let spread = phi_cdf_fixed(z)?.checked_mul_int(2)?.checked_sub(Fixed::ONE)?;
+            let inner = z.checked_mul(spread)?
+                .checked_add(std_normal_pdf_fixed(z)?.checked_mul_int(2)?)?
+                .checked_sub(Fixed::FRAC_1_SQRT_PI)?;
+            sigma.checked_mul(inner)
+        }
+        Distribution::LogNormal { mu, sigma } => {
+            // y (2 Phi(w) - 1) - 2 m (Phi(w - sigma) + Phi(sigma / sqrt 2) - 1), w = (ln y - mu) / sigma,
+            // m = exp(mu + sigma^2 / 2); for y <= 0 both w terms vanish
+            let m = mu.checked_add(sigma.checked_mul(sigma)?.checked_div_int(2)?)?.exp()?;
+            let (phi_w, phi_w_sigma) = if y.is_positive() {
+                let w = y.ln()?.checked_sub(mu)?.checked_div(sigma)?;
+                (phi_cdf_fixed(w)?, phi_cdf_fixed(w.checked_sub(sigma)?)?)
+            } else {
+                (Fixed::ZERO, Fixed::ZERO)
+            };
+            let pair = phi_cdf_fixed(sigma.checked_div(Fixed::SQRT_2)?)?;
+            let first = y.checked_mul(phi_w.checked_mul_int(2)?.checked_sub(Fixed::ONE)?)?;
+            first.checked_sub(m.checked_mul_int(2)?.checked_mul(phi_w_sigma.checked_add(pair)?.checked_sub(Fixed::ONE)?)?)
+        }
+        Distribution::Uniform { lo: a, hi: b } => {
+            let w = b.checked_sub(a)?;
+            if y < a { return a.checked_sub(y)?.checked_add(w.checked_div_int(3)?); }
+            if y > b { return y.checked_sub(b)?.checked_add(w.checked_div_int(3)?); }
+            // ((y - a)^3 + (b - y)^3) / (3 w^2), dividing by w early to stay in range
+            let l = y.checked_sub(a)?.checked_div(w)?;
+            let r = b.checked_sub(y)?.checked_div(w)?;
+            l.checked_powi(3)?.checked_add(r.checked_powi(3)?)?.checked_mul(w)?.checked_div_int(3)
+        }
+        Distribution::StudentT { .. } | Distribution::Beta { .. } => {
+            let split = y.max(lo).min(hi);
+            let below = simpson(lo, split, |x| {
+                let f = dist.cdf(x)?;
+                f.checked_mul(f)
+            })?;
+            let above = simpson(split, hi, |x| {
+                let s = Fixed::ONE.checked_sub(dist.cdf(x)?)?;
+                s.checked_mul(s)
+            })?;
+            below.checked_add(above)
+        }
+    }
+}
+
+/// Composite Simpson over [a, b] with `INNER_PRODUCT_PANELS` panels.
+fn simpson<G>(a: Fixed, b: Fixed, g: G) -> Option<Fixed>
+where
+    G: Fn(Fixed) -> Option<Fixed>,
+{
+    if b <= a { return Some(Fixed::ZERO); }
+    let n = INNER_PRODUCT_PANELS * 2;
+    let h = b.checked_sub(a)?.checked_div_int(n)?;
+    let mut acc = Fixed::ZERO;
+    for i in 0..=n {
+        let x = if i == n { b } else { a.checked_add(h.checked_mul_int(i)?)? };
+        let w = if i == 0 || i == n { 1 } else if i % 2 == 1 { 4 } else { 2 };
+        acc = acc.checked_add(g(x)?.checked_mul_int(w)?)?;
+    }
+    acc.checked_mul(h)?.checked_div_int(3)
+}
+
+/// Positively oriented score of `dist` at `y` under `rule`, normalised as described in the
+/// module docs. `None` for an unknown rule or a score that doesn't exist at `y`.
+pub fn score(rule: u8, dist: &Distribution, y: Fixed, lo: Fixed, hi: Fixed) -> Option<Fixed> {
+    let width = hi.checked_sub(lo)?;
+    if !width.is_positive() { return None; }
+    match rule {
+        SCORING_SPHERICAL => spherical_score(dist, y),
+        SCORING_LOG => log_score(dist, y),
+        SCORING_QUADRATIC => quadratic_score(dist, y)?.checked_mul(width),
+        SCORING_CRPS => crps(dist, y, lo, hi)?.checked_div(width)?.checked_neg(),
+        _ => None,
+    }
+}
+
+/// k * (S(to, y) - S(from, y)): what a position that moved the market from `from` to `to` gains
+/// (or loses, if negative) at outcome y. Outcomes outside [lo, hi] score at the nearest edge.
+pub fn payout_delta(rule: u8, k: Fixed, from: &Distribution, to: &Distribution, y: Fixed, lo: Fixed, hi: Fixed) -> Option<Fixed> {
+    let y = y.max(lo).min(hi);
+    k.checked_mul(score(rule, to, y, lo, hi)?.checked_sub(score(rule, from, y, lo, hi)?)?)
+}
+
+/// Where most of the family's mass sits, for focusing the loss search.
+fn focus(dist: &Distribution) -> Option<(Fixed, Fixed)> {
+    match *dist {
+        Distribution::Normal { mu, sigma } | Distribution::StudentT { mu, sigma, .. } => {
+            let reach = sigma.checked_mul_int(FOCUS_SIGMAS)?;
+            Some((mu.checked_sub(reach)?, mu.checked_add(reach)?))
+        }
+        Distribution::LogNormal { mu, sigma } => {
+            let reach = sigma.checked_mul_int(FOCUS_SIGMAS)?;
+            Some((mu.checked_sub(reach)?.exp()?, mu.checked_add(reach)?.exp()?))
+        }
+        Distribution::Uniform { lo, hi } => Some((lo, hi)),
+        Distribution::Beta { .. } => None,
+    }
+}
+
+/// Worst-case loss max_y k * (S(from, y) - S(to, y)) over [lo, hi]. The spherical rule reuses
+/// `math::max_loss_fixed`; the others search the whole range plus the windows where either
+/// distribution concentrates, so narrow peaks aren't stepped over by the coarse grid.
+pub fn max_loss(rule: u8, k: Fixed, from: &Distribution, to: &Distribution, lo: Fixed, hi: Fixed) -> Option<Fixed> {
+    if rule == SCORING_SPHERICAL { return max_loss_fixed(k, from, to, lo, hi); }
+    let loss = |y: Fixed| payout_delta(rule, k, from, to, y, lo, hi)?.checked_neg();
+    let mut worst = max_on_interval(lo, hi, loss)?;
+    for (a, b) in [focus(from), focus(to)].into_iter().flatten() {
+        let (a, b) = (a.max(lo), b.min(hi));
+        if a < b {
+            worst = worst.max(max_on_interval(a, b, loss)?);
+        }
+    }
+    Some(worst.max(Fixed::ZERO))
+}
+
+/// Most a market can owe its distribution traders beyond the collateral they posted. Payouts
+/// telescope to k * (S(last, y) - S(first, y)), so this is max_y k * (S(sharpest(y), y) -
+/// S(first, y)) over [lo, hi], where `sharpest(y)` is the best-scoring distribution a trade can
+/// reach at y. Outcomes where either score doesn't exist can't be settled and count as zero.
+pub fn max_subsidy<D>(rule: u8, k: Fixed, first: &Distribution, sharpest: D, lo: Fixed, hi: Fixed) -> Option<Fixed>
+where
+    D: Fn(Fixed) -> Option<Distribution>,
+{
+    let gain = |y: Fixed| {
+        let best = sharpest(y).and_then(|g| score(rule, &g, y, lo, hi));
+        match (best, score(rule, first, y, lo, hi)) {
+            (Some(s), Some(s0)) => k.checked_mul(s.checked_sub(s0)?),
+            _ => Some(Fixed::ZERO),
+        }
+    };
+    Some(max_on_interval(lo, hi, gain)?.max(Fixed::ZERO))
+}
+
+/// `max_loss` rounded up to whole collateral base units.
+pub fn collateral_required(rule: u8, k: u64, from: &Distribution, to: &Distribution, lo: Fixed, hi: Fixed) -> Option<u64> {
+    let k = Fixed::from_int(i64::try_from(k).ok()?);
+    u64::try_from(max_loss(rule, k, from, to, lo, hi)?.ceil_to_i64()?).ok()
+}
@@ -0,0 +1,26 @@
+// Input checks shared by the trading instructions, so every entry point rejects the same bad
+// input with the same error. Sides, market types and statuses are enums in the IDL, so a
+// client can't send an out-of-range value for those in the first place.
+
+use anchor_lang::prelude::*;
+
+use crate::{ErrorCode, Market, MarketStatus};
+
+/// Order prices are probabilities in basis points: (0, 10_000].
+pub const MAX_PRICE_BPS: u64 = 10_000;
+
+pub fn price(price_bps: u64) -> Result<()> {
+    require!(price_bps > 0 && price_bps <= MAX_PRICE_BPS, ErrorCode::PriceOutOfRange);
+    Ok(())
+}
+
+pub fn size(size: u64) -> Result<()> {
+    require!(size > 0, ErrorCode::ZeroSize);
+    Ok(())
+}
+
+/// The market is `Active` and its resolution time hasn't come.
+pub fn market_open(market: &Market, now: i64) -> Result<()> {
+    require!(market.status == MarketStatus::Active && now < market.resolution_time, ErrorCode::MarketClosed);
+    Ok(())
+}
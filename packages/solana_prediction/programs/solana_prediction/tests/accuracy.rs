@@ -0,0 +1,515 @@
+// Accuracy harness for math.rs against arbitrary-precision references.
+//
+// The tables below are generated by scripts/math_reference.py (mpmath, 50 digits) and pasted
+// as-is. Every input is dyadic, so it is exact both as an f64 and in Q64.64 and the measured
+// error is the function's own. Each check records its worst error against a tolerance model,
+// prints it (`cargo test --test accuracy -- --nocapture`) and asserts the model documented in
+// MATH_ACCURACY.md.
+// Reference digits are pasted as generated, some of which land on std constants.
+#![allow(clippy::excessive_precision, clippy::approx_constant, clippy::type_complexity)]
+
+use solana_prediction::fixed::Fixed;
+use solana_prediction::math::*;
+
+fn fx(v: f64) -> Fixed { Fixed::from_f64(v) }
+
+/// Q64.64 resolution, 2^-64.
+const ULP: f64 = 1.0 / 18_446_744_073_709_551_616.0;
+
+/// sigma = 2^e for each of these, from ~1e-12 (the finest `MAX_DECIMALS` step) to ~4e9.
+const SIGMA_EXPONENTS: [i32; 19] = [-40, -36, -32, -28, -24, -20, -16, -12, -8, -4, 0, 4, 8, 12, 16, 20, 24, 28, 32];
+
+/// Worst error seen by one check relative to its tolerance, with the input that produced it.
+struct Worst {
+    what: &'static str,
+    ratio: f64,
+    err: f64,
+    at: String,
+}
+
+impl Worst {
+    fn new(what: &'static str) -> Worst { Worst { what, ratio: 0.0, err: 0.0, at: String::from("-") } }
+
+    /// Records |got - want| against `tol`; a `None` result counts as an unbounded error.
+    fn record(&mut self, got: Option<f64>, want: f64, tol: f64, at: impl FnOnce() -> String) {
+        let err = got.map_or(f64::INFINITY, |g| (g - want).abs());
+        let ratio = if err == 0.0 { 0.0 } else { err / tol };
+        if ratio > self.ratio || ratio.is_nan() {
+            self.ratio = ratio;
+            self.err = err;
+            self.at = at();
+        }
+    }
+
+    fn check(self) {
+        println!("{:<36} worst {:>9.2e} ({:>5.1}% of tolerance) at {}", self.what, self.err, self.ratio * 100.0, self.at);
+        assert!(self.ratio <= 1.0, "{}: error {:e} at {} exceeds its tolerance", self.what, self.err, self.at);
+    }
+}
+
+const PHI_CDF: &[(f64, f64)] = &[
+    (-10.0, 7.619853024160525e-24),
+    (-9.75, 9.223413524939418e-23),
+    (-9.5, 1.0494515075362608e-21),
+    (-9.25, 1.1224633591327982e-20),
+    (-9.0, 1.1285884059538405e-19),
+    (-8.75, 1.0667637375474858e-18),
+    (-8.5, 9.479534822203318e-18),
+    (-8.25, 7.919726314642477e-17),
+    (-8.0, 6.220960574271784e-16),
+    (-7.75, 4.5946274357785954e-15),
+    (-7.5, 3.1908916729108963e-14),
+    (-7.25, 2.0838581586720695e-13),
+    (-7.0, 1.279812543885835e-12),
+    (-6.75, 7.392257778017822e-12),
+    (-6.5, 4.016000583859118e-11),
+    (-6.25, 2.0522634252189388e-10),
+    (-6.0, 9.86587645037698e-10),
+    (-5.75, 4.462172453901612e-09),
+    (-5.5, 1.8989562465887718e-08),
+    (-5.25, 7.604960516488715e-08),
+    (-5.0, 2.866515718791939e-07),
+    (-4.75, 1.0170832425687032e-06),
+    (-4.5, 3.3976731247300603e-06),
+    (-4.25, 1.068852577493442e-05),
+    (-4.0, 3.1671241833119924e-05),
+    (-3.75, 8.841728520080387e-05),
+    (-3.5, 0.00023262907903552504),
+    (-3.25, 0.000577025042390767),
+    (-3.0, 0.0013498980316300946),
+    (-2.75, 0.002979763235054557),
+    (-2.5, 0.006209665325776135),
+    (-2.25, 0.012224472655044703),
+    (-2.0, 0.02275013194817921),
+    (-1.75, 0.04005915686381709),
+    (-1.5, 0.06680720126885807),
+    (-1.25, 0.10564977366685525),
+    (-1.0, 0.15865525393145705),
+    (-0.75, 0.2266273523768682),
+    (-0.5, 0.3085375387259869),
+    (-0.25, 0.4012936743170763),
+    (0.0, 0.5),
+    (0.25, 0.5987063256829237),
+    (0.5, 0.6914624612740131),
+    (0.75, 0.7733726476231318),
+    (1.0, 0.8413447460685429),
+    (1.25, 0.8943502263331448),
+    (1.5, 0.9331927987311419),
+    (1.75, 0.9599408431361829),
+    (2.0, 0.9772498680518208),
+    (2.25, 0.9877755273449553),
+    (2.5, 0.9937903346742238),
+    (2.75, 0.9970202367649454),
+    (3.0, 0.9986501019683699),
+    (3.25, 0.9994229749576092),
+    (3.5, 0.9997673709209645),
+    (3.75, 0.9999115827147992),
+    (4.0, 0.9999683287581669),
+    (4.25, 0.9999893114742251),
+    (4.5, 0.9999966023268753),
+    (4.75, 0.9999989829167575),
+    (5.0, 0.9999997133484281),
+    (5.25, 0.9999999239503948),
+    (5.5, 0.9999999810104375),
+    (5.75, 0.9999999955378276),
+    (6.0, 0.9999999990134123),
+    (6.25, 0.9999999997947736),
+    (6.5, 0.99999999995984),
+    (6.75, 0.9999999999926077),
+    (7.0, 0.9999999999987201),
+    (7.25, 0.9999999999997916),
+    (7.5, 0.9999999999999681),
+    (7.75, 0.9999999999999954),
+    (8.0, 0.9999999999999993),
+    (8.25, 0.9999999999999999),
+    (8.5, 1.0),
+    (8.75, 1.0),
+    (9.0, 1.0),
+    (9.25, 1.0),
+    (9.5, 1.0),
+    (9.75, 1.0),
+    (10.0, 1.0),
+];
+
+const STD_PDF: &[(f64, f64)] = &[
+    (0.0, 0.3989422804014327),
+    (0.25, 0.3866681168028492),
+    (0.5, 0.35206532676429947),
+    (0.75, 0.30113743215480443),
+    (1.0, 0.24197072451914334),
+    (1.25, 0.18264908538902191),
+    (1.5, 0.12951759566589172),
+    (1.75, 0.08627731882651152),
+    (2.0, 0.05399096651318805),
+    (2.25, 0.03173965183566742),
+    (2.5, 0.017528300493568537),
+    (2.75, 0.009093562501591053),
+    (3.0, 0.0044318484119380075),
+    (3.25, 0.0020290480572997677),
+    (3.5, 0.00087268269504576),
+    (3.75, 0.0003525956823674454),
+    (4.0, 0.00013383022576488534),
+    (4.25, 4.7718636541204945e-05),
+    (4.5, 1.5983741106905475e-05),
+    (4.75, 5.029507288592445e-06),
+    (5.0, 1.4867195147342977e-06),
+    (5.25, 4.1284709886299984e-07),
+    (5.5, 1.0769760042543276e-07),
+    (5.75, 2.6392432035705732e-08),
+    (6.0, 6.075882849823285e-09),
+    (6.25, 1.3140018181558838e-09),
+    (6.5, 2.669556614762852e-10),
+    (6.75, 5.0949379588436835e-11),
+    (7.0, 9.134720408364594e-12),
+    (7.25, 1.538537950561275e-12),
+    (7.5, 2.4343205330290096e-13),
+    (7.75, 3.618294451112517e-14),
+    (8.0, 5.052271083536892e-15),
+    (8.25, 6.627137455968751e-16),
+    (8.5, 8.16623563166955e-17),
+    (8.75, 9.453103881902853e-18),
+    (9.0, 1.0279773571668915e-18),
+    (9.25, 1.050144982997037e-19),
+    (9.5, 1.007793539430001e-20),
+    (9.75, 9.085534311976665e-22),
+    (10.0, 7.694598626706419e-23),
+];
+
+/// (2 sqrt(pi))^(-1/2), i.e. ||N(0, 1)||_2.
+const UNIT_L2_NORM: f64 = 0.5311259660135984;
+
+const GAUSS_PAIRS: &[(i32, i32, f64, f64, f64, f64)] = &[
+    (-36, -36, 0.0, 19385406480.651783, 0.0, 1.0),
+    (-36, -36, 0.5, 18210904075.873295, 48466.53288153567, 0.9394130628134758),
+    (-36, -36, 3.0, 2043206810.8772376, 186237.48102771657, 0.10539922456186433),
+    (-36, -35, 0.0, 12260407569.40969, 67507.73720217774, 0.8944271909999159),
+    (-36, -35, 0.5, 11093675529.1732, 83010.59367714023, 0.80931119012552),
+    (-36, -35, 3.0, 334999973.5172352, 168547.0550734815, 0.024439080316193233),
+    (-36, -30, 0.0, 428308731.2390442, 137228.5902952948, 0.17675512006066263),
+    (-36, -30, 0.5, 377992661.0974539, 137594.76056419103, 0.15599060519044422),
+    (-36, -30, 3.0, 4763309.190911316, 140281.06372041148, 0.001965729919840675),
+    (-28, -28, 0.0, 75724244.06504603, 0.0, 1.0),
+    (-28, -28, 0.5, 71136344.04638006, 3029.1583050959794, 0.9394130628134758),
+    (-28, -28, 3.0, 7981276.604989209, 11639.842564232285, 0.10539922456186433),
+    (-28, -27, 0.0, 47892217.068006605, 4219.233575136109, 0.8944271909999159),
+    (-28, -27, 0.5, 43334670.035832815, 5188.162104821265, 0.80931119012552),
+    (-28, -27, 3.0, 1308593.6465517, 10534.190942092593, 0.024439080316193233),
+    (-28, -22, 0.0, 1673080.9814025164, 8576.786893455925, 0.17675512006066263),
+    (-28, -22, 0.5, 1476533.8324119293, 8599.67253526194, 0.15599060519044422),
+    (-28, -22, 3.0, 18606.67652699733, 8767.566482525717, 0.001965729919840675),
+    (-20, -20, 0.0, 295797.82837908604, 0.0, 1.0),
+    (-20, -20, 0.5, 277876.3439311721, 189.3223940684987, 0.9394130628134758),
+    (-20, -20, 3.0, 31176.8617382391, 727.4901602645178, 0.10539922456186433),
+    (-20, -19, 0.0, 187078.9729219008, 263.7020984460068, 0.8944271909999159),
+    (-20, -19, 0.5, 169276.05482747193, 324.26013155132904, 0.80931119012552),
+    (-20, -19, 3.0, 5111.693931842578, 658.3869338807871, 0.024439080316193233),
+    (-20, -14, 0.0, 6535.47258360358, 536.0491808409953, 0.17675512006066263),
+    (-20, -14, 0.5, 5767.710282859099, 537.4795334538712, 0.15599060519044422),
+    (-20, -14, 3.0, 72.68233018358332, 547.9729051578573, 0.001965729919840675),
+    (-12, -12, 0.0, 1155.4602671058049, 0.0, 1.0),
+    (-12, -12, 0.5, 1085.454468481141, 11.83264962928117, 0.9394130628134758),
+    (-12, -12, 3.0, 121.78461616499648, 45.468135016532365, 0.10539922456186433),
+    (-12, -11, 0.0, 730.777237976175, 16.481381152875425, 0.8944271909999159),
+    (-12, -11, 0.5, 661.2345891698122, 20.266258221958065, 0.80931119012552),
+    (-12, -11, 3.0, 19.967554421260072, 41.14918336754919, 0.024439080316193233),
+    (-12, -6, 0.0, 25.529189779701483, 33.503073802562206, 0.17675512006066263),
+    (-12, -6, 0.5, 22.530118292418354, 33.59247084086695, 0.15599060519044422),
+    (-12, -6, 3.0, 0.28391535227962234, 34.24830657236608, 0.001965729919840675),
+    (0, 0, 0.0, 0.28209479177387814, 0.0, 1.0),
+    (0, 0, 0.5, 0.26500353234402857, 0.18488515045751827, 0.9394130628134758),
+    (0, 0, 3.0, 0.029732572305907343, 0.7104396096333182, 0.10539922456186433),
+    (0, 1, 0.0, 0.1784124116152771, 0.2575215805136785, 0.8944271909999159),
+    (0, 1, 0.5, 0.1614342258715362, 0.31666028471809476, 0.80931119012552),
+    (0, 1, 3.0, 0.004874891216127947, 0.6429559901179561, 0.024439080316193233),
+    (0, 6, 0.0, 0.006232712348559932, 0.5234855281650345, 0.17675512006066263),
+    (0, 6, 0.5, 0.00550051716123495, 0.5248823568885461, 0.15599060519044422),
+    (0, 6, 3.0, 6.931527155264217e-05, 0.53512979019322, 0.001965729919840675),
+    (12, 12, 0.0, 6.887079877291947e-05, 0.0, 1.0),
+    (12, 12, 0.5, 6.469812801367885e-05, 0.002888830475898723, 0.9394130628134758),
+    (12, 12, 3.0, 7.25892878562191e-06, 0.011100618900520597, 0.10539922456186433),
+    (12, 13, 0.0, 4.355771767951101e-05, 0.004023774695526227, 0.8944271909999159),
+    (12, 13, 0.5, 3.9412652800668015e-05, 0.004947816948720231, 0.80931119012552),
+    (12, 13, 3.0, 1.1901589883124871e-06, 0.010046187345593064, 0.024439080316193233),
+    (12, 18, 0.0, 1.5216582882226397e-06, 0.008179461377578663, 0.17675512006066263),
+    (12, 18, 0.5, 1.3428996975671264e-06, 0.008201286826383533, 0.15599060519044422),
+    (12, 18, 3.0, 1.692267371890678e-08, 0.008361402971769063, 0.001965729919840675),
+    (24, 24, 0.0, 1.6814159856669792e-08, 0.0, 1.0),
+    (24, 24, 0.5, 1.5795441409589563e-08, 4.513797618591755e-05, 0.9394130628134758),
+    (24, 24, 3.0, 1.7721994105522241e-09, 0.00017344717032063433, 0.10539922456186433),
+    (24, 25, 0.0, 1.0634208417849368e-08, 6.28714796175973e-05, 0.8944271909999159),
+    (24, 25, 0.5, 9.62222968766309e-09, 7.73096398237536e-05, 0.80931119012552),
+    (24, 25, 3.0, 2.905661592559783e-10, 0.00015697167727489163, 0.024439080316193233),
+];
+
+const GRID_SUMS: &[(f64, f64, f64, f64, f64, f64, f64, f64)] = &[
+    (1.0, 1.0, -8.0, 8.0, 0.0078125, 0.12479829408003389, 0.7658462636910842, 0.12479829408003389),
+    (1.0, 1.0, -8.0, 8.0, 0.0625, 0.12479829408003389, 0.7656205948436762, 0.12479829408003389),
+    (1.0, 1.0, -8.0, 8.0, 1.0, 0.12490211088076501, 0.7978845608028603, 0.12479829408003389),
+    (1.0, 1.0, -8.0, 8.0, 2.0, 0.12490211088076501, 0.7978845608028654, 0.12479829408003389),
+    (0.0009765625, 0.00048828125, -0.0078125, 0.0078125, 6.103515625e-05, 35.00289931233195, 0.39469941012156234, 35.00289931233195),
+    (1024.0, 512.0, -8192.0, 8192.0, 8.0, 3.338136607392497e-05, 0.3948233360300432, 3.338136607392497e-05),
+];
+
+const LN_GAMMA: &[(f64, f64)] = &[
+    (0.015625, 4.150063373653939),
+    (0.25, 1.2880225246980774),
+    (0.5, 0.5723649429247001),
+    (1.0, 0.0),
+    (1.5, -0.12078223763524522),
+    (2.0, 0.0),
+    (3.0, 0.6931471805599453),
+    (7.5, 7.534364236758733),
+    (10.0, 12.801827480081469),
+    (33.0, 81.55795945611504),
+    (100.0, 359.1342053695754),
+    (1000.0, 5905.220423209181),
+    (1048576.0, 13487767.94752331),
+];
+
+const REG_INC_BETA: &[(f64, f64, f64, f64)] = &[
+    (0.0078125, 0.5, 0.5, 0.05634329647599925),
+    (0.25, 0.5, 0.5, 0.3333333333333333),
+    (0.5, 0.5, 0.5, 0.5),
+    (0.90625, 0.5, 0.5, 0.8018939572436735),
+    (0.9990234375, 0.5, 0.5, 0.9801023926741228),
+    (0.0078125, 0.5, 1.0, 0.08838834764831845),
+    (0.25, 0.5, 1.0, 0.5),
+    (0.5, 0.5, 1.0, 0.7071067811865476),
+    (0.90625, 0.5, 1.0, 0.9519716382329886),
+    (0.9990234375, 0.5, 1.0, 0.9995115994824673),
+    (0.0078125, 0.5, 2.5, 0.14946726345166347),
+    (0.25, 0.5, 2.5, 0.7468300048996773),
+    (0.5, 0.5, 2.5, 0.9244131815783876),
+    (0.90625, 0.5, 2.5, 0.9990539119362132),
+    (0.9990234375, 0.5, 2.5, 0.99999998987767),
+    (0.0078125, 0.5, 10.0, 0.30431023546260805),
+    (0.25, 0.5, 10.0, 0.9821934413921554),
+    (0.5, 0.5, 10.0, 0.9997665525652514),
+    (0.90625, 0.5, 10.0, 0.9999999999903381),
+    (0.9990234375, 0.5, 10.0, 1.0),
+    (0.0078125, 0.5, 50.0, 0.62298378724804),
+    (0.25, 0.5, 50.0, 0.9999999123030591),
+    (0.5, 0.5, 50.0, 0.9999999999999999),
+    (0.90625, 0.5, 50.0, 1.0),
+    (0.9990234375, 0.5, 50.0, 1.0),
+    (0.0078125, 1.0, 0.5, 0.003913909343173289),
+    (0.25, 1.0, 0.5, 0.13397459621556135),
+    (0.5, 1.0, 0.5, 0.2928932188134525),
+    (0.90625, 1.0, 0.5, 0.6938137821521028),
+    (0.9990234375, 1.0, 0.5, 0.96875),
+    (0.0078125, 1.0, 1.0, 0.0078125),
+    (0.25, 1.0, 1.0, 0.25),
+    (0.5, 1.0, 1.0, 0.5),
+    (0.90625, 1.0, 1.0, 0.90625),
+    (0.9990234375, 1.0, 1.0, 0.9990234375),
+    (0.0078125, 1.0, 2.5, 0.019416958239504515),
+    (0.25, 1.0, 2.5, 0.5128607103712532),
+    (0.5, 1.0, 2.5, 0.8232233047033631),
+    (0.90625, 1.0, 2.5, 0.9973089101946963),
+    (0.9990234375, 1.0, 2.5, 0.9999999701976776),
+    (0.0078125, 1.0, 10.0, 0.07543486340340104),
+    (0.25, 1.0, 10.0, 0.9436864852905273),
+    (0.5, 1.0, 10.0, 0.9990234375),
+    (0.90625, 1.0, 10.0, 0.999999999947554),
+    (0.9990234375, 1.0, 10.0, 1.0),
+    (0.0078125, 1.0, 50.0, 0.3244032286865785),
+    (0.25, 1.0, 50.0, 0.9999994336783435),
+    (0.5, 1.0, 50.0, 0.9999999999999991),
+    (0.90625, 1.0, 50.0, 1.0),
+    (0.9990234375, 1.0, 50.0, 1.0),
+    (0.0078125, 2.5, 0.5, 1.8368324155670812e-06),
+    (0.25, 2.5, 0.5, 0.011724811003954639),
+    (0.5, 2.5, 0.5, 0.07558681842161244),
+    (0.90625, 2.5, 0.5, 0.5042210844725843),
+    (0.9990234375, 2.5, 0.5, 0.946974252632563),
+    (0.0078125, 2.5, 1.0, 5.3947966093944364e-06),
+    (0.25, 2.5, 1.0, 0.03125),
+    (0.5, 2.5, 1.0, 0.1767766952966369),
+    (0.90625, 2.5, 1.0, 0.7818438942909604),
+    (0.9990234375, 2.5, 1.0, 0.9975603815982694),
+    (0.0078125, 2.5, 2.5, 2.9062228080045716e-05),
+    (0.25, 2.5, 2.5, 0.1265849975501613),
+    (0.5, 2.5, 2.5, 0.5),
+    (0.90625, 2.5, 2.5, 0.9868220213647552),
+    (0.9990234375, 2.5, 2.5, 0.9999998382685871),
+    (0.0078125, 2.5, 10.0, 0.0005821443927195247),
+    (0.25, 2.5, 10.0, 0.7093308712010185),
+    (0.5, 2.5, 10.0, 0.9888162481447345),
+    (0.90625, 2.5, 10.0, 0.9999999986982762),
+    (0.9990234375, 2.5, 10.0, 1.0),
+    (0.0078125, 2.5, 50.0, 0.022712682548973673),
+    (0.25, 2.5, 50.0, 0.9999786903810222),
+    (0.5, 2.5, 50.0, 0.9999999999999107),
+    (0.90625, 2.5, 50.0, 1.0),
+    (0.9990234375, 2.5, 50.0, 1.0),
+    (0.0078125, 10.0, 0.5, 1.4977756039857968e-22),
+    (0.25, 10.0, 0.5, 1.9120504044554146e-07),
+    (0.5, 10.0, 0.5, 0.0002334474347486224),
+    (0.90625, 10.0, 0.5, 0.16578891486617606),
+    (0.9990234375, 10.0, 0.5, 0.8901987140103782),
+    (0.0078125, 10.0, 1.0, 8.470329472543003e-22),
+    (0.25, 10.0, 1.0, 9.5367431640625e-07),
+    (0.5, 10.0, 1.0, 0.0009765625),
+    (0.90625, 10.0, 1.0, 0.3736630856289844),
+    (0.9990234375, 10.0, 1.0, 0.9902771787762996),
+    (0.0078125, 10.0, 2.5, 2.3772872532682906e-20),
+    (0.25, 10.0, 2.5, 1.8381484417622205e-05),
+    (0.5, 10.0, 2.5, 0.011183751855265597),
+    (0.90625, 10.0, 2.5, 0.8335488507286704),
+    (0.9990234375, 10.0, 2.5, 0.9999966394691894),
+    (0.0078125, 10.0, 10.0, 7.338650248474706e-17),
+    (0.25, 10.0, 10.0, 0.008903279303922318),
+    (0.5, 10.0, 10.0, 0.5),
+    (0.90625, 10.0, 10.0, 0.9999978209594815),
+    (0.9990234375, 10.0, 10.0, 1.0),
+    (0.0078125, 10.0, 50.0, 3.7549011240710295e-11),
+    (0.25, 10.0, 50.0, 0.9480459701193763),
+    (0.5, 10.0, 50.0, 0.9999999736758395),
+    (0.90625, 10.0, 50.0, 1.0),
+    (0.9990234375, 10.0, 50.0, 1.0),
+    (0.0078125, 50.0, 0.5, 3.483577411070348e-107),
+    (0.25, 50.0, 0.5, 7.22630085426761e-32),
+    (0.5, 50.0, 0.5, 9.901688984594139e-17),
+    (0.90625, 50.0, 0.5, 0.0017495707632443902),
+    (0.9990234375, 50.0, 0.5, 0.7551956050068894),
+    (0.0078125, 50.0, 1.0, 4.3601508761683463e-106),
+    (0.25, 50.0, 1.0, 7.888609052210118e-31),
+    (0.5, 50.0, 1.0, 8.881784197001252e-16),
+    (0.90625, 50.0, 1.0, 0.007284520653099022),
+    (0.9990234375, 50.0, 1.0, 0.9523220797036269),
+    (0.0078125, 50.0, 2.5, 1.1895546674569974e-103),
+    (0.25, 50.0, 2.5, 1.428020824563332e-28),
+    (0.5, 50.0, 2.5, 8.923890603183414e-14),
+    (0.90625, 50.0, 2.5, 0.07553024559644239),
+    (0.9990234375, 50.0, 2.5, 0.9998410191792142),
+    (0.0078125, 50.0, 10.0, 5.112516834889558e-96),
+    (0.25, 50.0, 10.0, 7.90411075178333e-22),
+    (0.5, 50.0, 10.0, 2.6324160511126404e-08),
+    (0.90625, 50.0, 10.0, 0.953451644403535),
+    (0.9990234375, 50.0, 10.0, 1.0),
+    (0.0078125, 50.0, 50.0, 1.5090939750182674e-77),
+    (0.25, 50.0, 50.0, 4.38484704593346e-08),
+    (0.5, 50.0, 50.0, 0.5),
+    (0.90625, 50.0, 50.0, 1.0),
+    (0.9990234375, 50.0, 50.0, 1.0),
+];
+
+
+#[test]
+fn normal_cdf_within_as_bound() {
+    let mut f = Worst::new("phi_cdf_as");
+    let mut q = Worst::new("phi_cdf_fixed");
+    let mut track = Worst::new("phi_cdf_fixed vs phi_cdf_as");
+    for &(z, want) in PHI_CDF {
+        let got = phi_cdf_fixed(fx(z)).map(Fixed::to_f64);
+        f.record(Some(phi_cdf_as(z)), want, 7.5e-8, || format!("z = {z}"));
+        q.record(got, want, 7.5e-8, || format!("z = {z}"));
+        track.record(got, phi_cdf_as(z), 1e-15, || format!("z = {z}"));
+    }
+    f.check();
+    q.check();
+    track.check();
+}
+
+#[test]
+fn normal_pdf_across_sigma() {
+    let mut std = Worst::new("std_normal_pdf_fixed");
+    for &(z, want) in STD_PDF {
+        for z in [z, -z] {
+            std.record(std_normal_pdf_fixed(fx(z)).map(Fixed::to_f64), want, want * 5e-12 + 16.0 * ULP, || format!("z = {z}"));
+        }
+    }
+    std.check();
+
+    // p(mu + z sigma) = phi(z) / sigma; mu = 1000 so x - mu is a real subtraction
+    let mut f = Worst::new("gauss_pdf");
+    let mut q = Worst::new("gauss_pdf_fixed");
+    for e in SIGMA_EXPONENTS {
+        let sigma = 2f64.powi(e);
+        for &(z, phi) in STD_PDF {
+            let (x, want) = (1000.0 + z * sigma, phi / sigma);
+            let at = || format!("sigma = 2^{e}, z = {z}");
+            f.record(Some(gauss_pdf(x, 1000.0, sigma)), want, want * 1e-15, at);
+            let got = gauss_pdf_fixed(fx(x), fx(1000.0), fx(sigma)).map(Fixed::to_f64);
+            q.record(got, want, (phi * 5e-12 + 16.0 * ULP) / sigma + 2.0 * ULP, at);
+        }
+    }
+    f.check();
+    q.check();
+}
+
+#[test]
+fn gauss_l2_geometry_across_sigma() {
+    let mut norm = Worst::new("gauss_l2_norm_fixed");
+    for e in SIGMA_EXPONENTS {
+        let sigma = 2f64.powi(e);
+        let want = UNIT_L2_NORM / sigma.sqrt();
+        norm.record(gauss_l2_norm_fixed(fx(sigma)).map(Fixed::to_f64), want, want * (1e-15 + 2.0 * ULP * sigma), || format!("sigma = 2^{e}"));
+    }
+    norm.check();
+
+    // Closed forms lose ~2^-64 / sigma relative: a sigma that small has few significant bits
+    let mut inner = Worst::new("gauss_inner_product_fixed");
+    let mut dist = Worst::new("gauss_l2_distance_fixed");
+    let mut overlap = Worst::new("gauss_overlap_fixed");
+    for &(ea, eb, d, want_inner, want_dist, want_overlap) in GAUSS_PAIRS {
+        let (sa, sb) = (fx(2f64.powi(ea)), fx(2f64.powi(eb)));
+        let mu_b = fx(d * 2f64.powi(ea.max(eb)));
+        let rel = 1e-11 + 4.0 * ULP / 2f64.powi(ea.min(eb));
+        let largest_norm = UNIT_L2_NORM / 2f64.powi(ea.min(eb)).sqrt();
+        let at = || format!("sigma 2^{ea} vs 2^{eb}, d = {d}");
+        inner.record(gauss_inner_product_fixed(Fixed::ZERO, sa, mu_b, sb).map(Fixed::to_f64), want_inner, want_inner * rel + 4.0 * ULP, at);
+        dist.record(gauss_l2_distance_fixed(Fixed::ZERO, sa, mu_b, sb).map(Fixed::to_f64), want_dist, largest_norm * rel, at);
+        overlap.record(gauss_overlap_fixed(Fixed::ZERO, sa, mu_b, sb).map(Fixed::to_f64), want_overlap, rel, at);
+    }
+    inner.check();
+    dist.check();
+    overlap.check();
+}
+
+#[test]
+fn grid_sums_match_reference() {
+    let mut f2 = Worst::new("discretized_l2_sq");
+    let mut f1 = Worst::new("discretized_l1_distance");
+    let mut q2 = Worst::new("discretized_l2_sq_fixed");
+    let mut q1 = Worst::new("discretized_l1_distance_fixed");
+    let mut fine = Worst::new("grid l2 vs integral, h <= sigma/16");
+    let mut coarse = Worst::new("grid l2 vs integral, h <= 2 sigma");
+    for &(s, m, lo, hi, h, l2, l1, exact) in GRID_SUMS {
+        let at = || format!("sigma = {s}, h = {h}");
+        let p = |x: f64| gauss_pdf(x, 0.0, s);
+        let q = |x: f64| gauss_pdf(x, m, s);
+        f2.record(Some(discretized_l2_sq(lo, hi, h, p, q)), l2, l2 * 1e-14, at);
+        f1.record(Some(discretized_l1_distance(lo, hi, h, p, q)), l1, l1 * 1e-14, at);
+        let pf = |x: Fixed| gauss_pdf_fixed(x, Fixed::ZERO, fx(s));
+        let qf = |x: Fixed| gauss_pdf_fixed(x, fx(m), fx(s));
+        q2.record(discretized_l2_sq_fixed(fx(lo), fx(hi), fx(h), pf, qf).map(Fixed::to_f64), l2, l2 * 2e-11, at);
+        q1.record(discretized_l1_distance_fixed(fx(lo), fx(hi), fx(h), pf, qf).map(Fixed::to_f64), l1, l1 * 2e-11, at);
+        // the sums themselves against the integral they approximate
+        if h <= s / 16.0 {
+            fine.record(Some(l2), exact, exact * 1e-15, at);
+        } else {
+            coarse.record(Some(l2), exact, exact * 1e-3, at);
+        }
+    }
+    f2.check();
+    f1.check();
+    q2.check();
+    q1.check();
+    fine.check();
+    coarse.check();
+}
+
+#[test]
+fn special_functions_match_reference() {
+    let mut g = Worst::new("ln_gamma");
+    for &(x, want) in LN_GAMMA {
+        g.record(ln_gamma(fx(x)).map(Fixed::to_f64), want, 1e-12 * want.abs().max(1.0), || format!("x = {x}"));
+    }
+    g.check();
+    let mut b = Worst::new("reg_inc_beta");
+    for &(x, a, bb, want) in REG_INC_BETA {
+        b.record(reg_inc_beta(fx(x), fx(a), fx(bb)).map(Fixed::to_f64), want, 1e-12, || format!("x = {x}, a = {a}, b = {bb}"));
+    }
+    b.check();
+}
@@ -0,0 +1,99 @@
+// Reference digits are pasted from mpmath as-is.
+#![allow(clippy::excessive_precision)]
+
+use solana_prediction::fixed::Fixed;
+use solana_prediction::math::*;
+
+// Reference values computed with mpmath at 30 significant digits.
+
+fn fx(v: f64) -> Fixed { Fixed::from_f64(v) }
+
+fn assert_close(actual: Option<Fixed>, expected: f64, tol: f64, what: &str) {
+    let actual = actual.unwrap_or_else(|| panic!("{what}: None")).to_f64();
+    let err = (actual - expected).abs();
+    assert!(err <= tol, "{what}: got {actual} expected {expected} (err {err:e} > {tol:e})");
+}
+
+fn dist(dist_type: u8, mu: f64, sigma: f64, shape: f64, lo: f64, hi: f64) -> Distribution {
+    Distribution::from_params(dist_type, fx(mu), fx(sigma), fx(shape), fx(lo), fx(hi)).unwrap()
+}
+
+#[test]
+fn ln_gamma_matches_reference() {
+    assert_close(ln_gamma(fx(0.3)), 1.095_797_994_818_075_5, 1e-12, "lnG(0.3)");
+    assert_close(ln_gamma(fx(7.5)), 7.534_364_236_758_733, 1e-12, "lnG(7.5)");
+    assert_close(ln_gamma(Fixed::from_int(150)), 600.009_470_555_327_4, 1e-11, "lnG(150)");
+}
+
+#[test]
+fn student_t_matches_reference() {
+    let t3 = dist(DIST_STUDENT_T, 0.0, 1.0, 3.0, -100.0, 100.0);
+    assert_close(t3.pdf(fx(1.3)), 0.150_389_085_907_535_98, 1e-12, "t3 pdf");
+    assert_close(t3.cdf(fx(1.3)), 0.857_766_245_636_051_3, 1e-9, "t3 cdf");
+    assert_close(t3.l2_norm_sq(), 0.229_720_373_092_413_35, 1e-12, "t3 l2");
+    assert_close(t3.quantile(fx(0.95)), 2.353_363_434_801_824, 1e-8, "t3 q95");
+    let cauchy = dist(DIST_STUDENT_T, 0.0, 1.0, 1.0, -100.0, 100.0);
+    assert_close(cauchy.cdf(fx(-2.0)), 0.147_583_617_650_433_27, 1e-9, "t1 cdf");
+    let shifted = dist(DIST_STUDENT_T, 10.0, 2.0, 2.5, 0.0, 20.0);
+    assert_close(shifted.pdf(fx(12.0)), 0.100_397_980_335_460_06, 1e-12, "t2.5 pdf");
+    assert_close(shifted.cdf(fx(12.0)), 0.797_969_486_360_863_3, 1e-9, "t2.5 cdf");
+    let shifted = dist(DIST_STUDENT_T, 5.0, 2.0, 2.5, 0.0, 20.0);
+    assert_close(shifted.l2_norm_sq(), 0.110_389_254_709_473_53, 1e-12, "t2.5 l2");
+}
+
+#[test]
+fn beta_matches_reference() {
+    // mean 40, std 15 on [0, 100] -> alpha = 3.8667, beta = 5.8
+    let b = dist(DIST_BETA, 40.0, 15.0, 0.0, 0.0, 100.0);
+    assert_close(b.pdf(fx(30.0)), 0.022_635_976_909_715_45, 1e-12, "beta pdf");
+    assert_close(b.cdf(fx(30.0)), 0.274_436_196_844_062_1, 1e-9, "beta cdf");
+    assert_close(b.l2_norm_sq(), 0.018_364_976_113_558_32, 1e-12, "beta l2");
+    assert_close(b.quantile(fx(0.05)), 16.543_783_067_540_19, 1e-7, "beta q05");
+}
+
+#[test]
+fn lognormal_uniform_and_normal_match_reference() {
+    let ln = dist(DIST_LOGNORMAL, 0.5, 0.4, 0.0, 0.0, 10.0);
+    assert_close(ln.pdf(fx(2.0)), 0.443_802_466_358_175_27, 1e-12, "lognormal pdf");
+    assert_close(ln.cdf(fx(2.0)), 0.685_405_250_588_589_9, 1e-7, "lognormal cdf");
+    assert_close(ln.l2_norm_sq(), 0.445_204_571_323_827_46, 1e-12, "lognormal l2");
+
+    // std sqrt(3) -> [7, 13]
+    let u = dist(DIST_UNIFORM, 10.0, 3f64.sqrt(), 0.0, 0.0, 20.0);
+    assert_close(u.pdf(fx(10.5)), 1.0 / 6.0, 1e-12, "uniform pdf");
+    assert_close(u.pdf(fx(13.5)), 0.0, 0.0, "uniform pdf outside");
+    assert_close(u.cdf(fx(8.5)), 0.25, 1e-12, "uniform cdf");
+    assert_close(u.quantile(fx(0.75)), 11.5, 1e-12, "uniform q");
+    assert_close(u.l2_norm_sq(), 1.0 / 6.0, 1e-12, "uniform l2");
+
+    let n = dist(DIST_NORMAL, 100.0, 20.0, 0.0, 0.0, 200.0);
+    assert_close(n.quantile(fx(0.975)), 100.0 + 20.0 * 1.959_963_984_540_054, 1e-12, "normal q");
+}
+
+#[test]
+fn rejects_unknown_and_ill_parameterised_families() {
+    let p = |t, mu, sigma, shape| Distribution::from_params(t, fx(mu), fx(sigma), fx(shape), fx(0.0), fx(100.0));
+    assert!(p(9, 50.0, 10.0, 0.0).is_none());
+    assert!(p(DIST_NORMAL, 50.0, 0.0, 0.0).is_none());
+    assert!(p(DIST_STUDENT_T, 50.0, 10.0, 0.0).is_none());
+    assert!(p(DIST_BETA, 120.0, 10.0, 0.0).is_none());
+    // std too wide for a beta with this mean
+    assert!(p(DIST_BETA, 50.0, 45.0, 0.0).is_none());
+    assert!(p(DIST_BETA, 50.0, 10.0, 0.0).is_some());
+}
+
+#[test]
+fn generic_collateral_agrees_with_gaussian_path() {
+    let from = dist(DIST_NORMAL, 0.0, 1.0, 0.0, -10.0, 10.0);
+    let to = dist(DIST_NORMAL, 0.5, 0.8, 0.0, -10.0, 10.0);
+    let k = Fixed::from_int(1_000);
+    let closed = scaled_l2_distance_fixed(k, &from, &to, fx(-10.0), fx(10.0)).unwrap();
+    let t_from = dist(DIST_STUDENT_T, 0.0, 1.0, 1_000_000.0, -10.0, 10.0);
+    let t_to = dist(DIST_STUDENT_T, 0.5, 0.8, 1_000_000.0, -10.0, 10.0);
+    // a Student-t with huge nu is a normal; the quadrature path must land on the closed form
+    let numeric = scaled_l2_distance_fixed(k, &t_from, &t_to, fx(-10.0), fx(10.0)).unwrap();
+    assert!((closed.to_f64() - numeric.to_f64()).abs() < 1e-2, "{} vs {}", closed.to_f64(), numeric.to_f64());
+    let c_closed = collateral_required(1_000, &from, &to, fx(-10.0), fx(10.0)).unwrap();
+    let c_numeric = collateral_required(1_000, &t_from, &t_to, fx(-10.0), fx(10.0)).unwrap();
+    assert!(c_closed.abs_diff(c_numeric) <= 1, "{c_closed} vs {c_numeric}");
+}
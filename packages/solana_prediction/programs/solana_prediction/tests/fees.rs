@@ -0,0 +1,89 @@
+use anchor_lang::prelude::Pubkey;
+use solana_prediction::{Market, MarketFees, MarketStatus, MarketType, PmAmmQuote, Side, MARKET_RESERVED};
+
+fn market(fee_bps_platform: u16, fee_bps_creator: u16) -> Market {
+    Market {
+        version: 1,
+        authority: Pubkey::new_unique(),
+        slug: [0; 32],
+        market_type: MarketType::Distributional,
+        decimals: 2,
+        outcome_min: 0,
+        outcome_max: 100_000,
+        unit: [0; 12],
+        dist_type: 0,
+        mu: 50_000,
+        sigma: 10_000,
+        sigma_min: 1_000,
+        step: 100,
+        resolution_time: 1_000_000,
+        oracle_config: [0; 64],
+        fee_bps_platform,
+        fee_bps_creator,
+        liquidity_pool: Pubkey::new_unique(),
+        order_book: Pubkey::new_unique(),
+        collateral_vault: Pubkey::new_unique(),
+        status: MarketStatus::Active,
+        bump: 255,
+        l2_norm: 1_000_000,
+        dist_shape: 0,
+        resolved_outcome: 0,
+        scoring_rule: 0,
+        strike: 50_000,
+        fee_bps_lp: 20,
+        distribution_balance: 0,
+        reserved: [0; MARKET_RESERVED],
+    }
+}
+
+#[test]
+fn book_fees_round_up_on_the_notional() {
+    let m = market(25, 10);
+    // 3 @ 5000 + 1 @ 5100 bps is 2.01 units of collateral
+    let notional = 5_000 * 3 + 5_100;
+    assert_eq!(m.trade_fees(notional, u64::MAX).unwrap(), (1, 1));
+    // 1_000_000 units at 50%: 0.25% and 0.10% of 500_000
+    assert_eq!(m.trade_fees(5_000 * 1_000_000, u64::MAX).unwrap(), (1_250, 500));
+    assert_eq!(market(0, 0).trade_fees(5_000 * 1_000_000, u64::MAX).unwrap(), (0, 0));
+    // a cap is filled by the platform first
+    assert_eq!(m.trade_fees(5_000 * 1_000_000, 1_500).unwrap(), (1_250, 250));
+    // the LP's share, 0.20%, rounds up the same way
+    assert_eq!(m.book_lp_fee(notional).unwrap(), 1);
+    assert_eq!(m.book_lp_fee(5_000 * 1_000_000).unwrap(), 1_000);
+    assert_eq!(Market { fee_bps_lp: 0, ..m }.book_lp_fee(5_000 * 1_000_000).unwrap(), 0);
+}
+
+#[test]
+fn pmamm_quotes_carry_every_fee_on_the_collateral_side() {
+    let m = market(25, 10);
+    // a buy of 1_000 shares for 500_000 on the curve plus a 2_500 LP fee
+    let buy = PmAmmQuote::new(Side::Buy, 1_000, 502_500, 2_500).charge(Side::Buy, &m).unwrap();
+    assert_eq!((buy.platform_fee, buy.creator_fee), (1_250, 500));
+    assert_eq!((buy.amount_in, buy.amount_out, buy.collateral(Side::Buy)), (504_250, 1_000, 504_250));
+
+    // and the sale back receives the curve's 500_000 less all three
+    let sell = PmAmmQuote::new(Side::Sell, 1_000, 497_500, 2_500).charge(Side::Sell, &m).unwrap();
+    assert_eq!((sell.platform_fee, sell.creator_fee), (1_250, 500));
+    assert_eq!((sell.amount_in, sell.amount_out), (1_000, 495_750));
+
+    // a seller is never charged more than they receive
+    let dust = PmAmmQuote::new(Side::Sell, 1, 1, 0).charge(Side::Sell, &market(5_000, 5_000)).unwrap();
+    assert_eq!((dust.platform_fee, dust.creator_fee, dust.amount_out), (1, 0, 0));
+}
+
+#[test]
+fn the_ledger_owes_platform_and_creator_but_only_counts_lp_fees() {
+    let mut fees = MarketFees {
+        market: Pubkey::new_unique(),
+        platform: Pubkey::new_unique(),
+        platform_fees: 0,
+        creator_fees: 0,
+        lp_fees: 0,
+        bump: 255,
+    };
+    fees.accrue(1_250, 500, 2_500).unwrap();
+    fees.accrue(1, 1, 0).unwrap();
+    assert_eq!((fees.platform_fees, fees.creator_fees, fees.lp_fees), (1_251, 501, 2_500));
+    assert_eq!(fees.held(), 1_752);
+    assert!(fees.accrue(u64::MAX, 0, 0).is_err());
+}
@@ -0,0 +1,110 @@
+use solana_prediction::fixed::Fixed;
+use solana_prediction::math::*;
+
+fn fx(v: f64) -> Fixed { Fixed::from_f64(v) }
+
+fn assert_close(actual: Fixed, expected: f64, tol: f64, what: &str) {
+    let err = (actual.to_f64() - expected).abs();
+    assert!(err <= tol, "{what}: got {} expected {expected} (err {err:e} > {tol:e})", actual.to_f64());
+}
+
+#[test]
+fn arithmetic_round_trips() {
+    let a = Fixed::from_int(7);
+    let b = Fixed::from_int(-3);
+    assert_eq!(a.checked_add(b), Some(Fixed::from_int(4)));
+    assert_eq!(a.checked_mul(b), Some(Fixed::from_int(-21)));
+    assert_close(a.checked_div(b).unwrap(), -7.0 / 3.0, 1e-18, "div");
+    assert_eq!(Fixed::from_scaled(12_345, 2).unwrap().to_scaled_round(2), Some(12_345));
+    assert_eq!(Fixed::from_scaled(-12_345, 2).unwrap().to_scaled_round(2), Some(-12_345));
+    assert_eq!(Fixed::from_scaled(12_350, 2).unwrap().to_scaled_floor(1), Some(1_235));
+    assert_eq!(Fixed::from_scaled(12_345, 3).unwrap().to_scaled_floor(2), Some(1_234));
+    assert_eq!(Fixed::from_scaled(12_345, 3).unwrap().to_scaled_ceil(2), Some(1_235));
+    assert_eq!(fx(2.5).conservative_to_i64(), Some(2));
+    assert_eq!(fx(-2.5).conservative_to_i64(), Some(-2));
+    assert_eq!(Fixed::MAX.checked_mul(Fixed::TWO), None);
+    assert_eq!(Fixed::ONE.checked_div(Fixed::ZERO), None);
+}
+
+#[test]
+fn elementary_functions_match_f64() {
+    for &x in &[-40.0, -10.0, -1.0, -0.25, 0.0, 0.5, 1.0, 3.3, 20.0, 43.0] {
+        let got = fx(x).exp().unwrap();
+        assert_close(got, x.exp(), x.exp() * 1e-14 + 1e-18, "exp");
+    }
+    assert_eq!(Fixed::from_int(50).exp(), None);
+    for &x in &[1e-12, 1e-6, 0.1, 0.5, 1.0, 2.0, 10.0, 12_345.678, 1e15] {
+        // from_f64 quantizes the input to 2^-64, a relative shift of up to 2^-64 / x
+        let quant = 1.0 / (x * 2f64.powi(64));
+        assert_close(fx(x).ln().unwrap(), x.ln(), 1e-15 + quant, "ln");
+        assert_close(fx(x).sqrt().unwrap(), x.sqrt(), x.sqrt() * (1e-15 + quant) + 1e-18, "sqrt");
+    }
+    assert_eq!(Fixed::ZERO.ln(), None);
+    assert_eq!(fx(-1.0).sqrt(), None);
+}
+
+#[test]
+fn gaussian_functions_match_f64() {
+    for &(x, mu, sigma) in &[(0.0, 0.0, 1.0), (1.5, 0.0, 1.0), (52_000.0, 50_000.0, 10_000.0), (0.01, 0.0, 0.002)] {
+        let expected = gauss_pdf(x, mu, sigma);
+        let got = gauss_pdf_fixed(fx(x), fx(mu), fx(sigma)).unwrap();
+        assert_close(got, expected, expected * 1e-12 + 1e-18, "gauss_pdf");
+    }
+    assert_eq!(gauss_pdf_fixed(Fixed::ONE, Fixed::ZERO, Fixed::ZERO), Some(Fixed::ZERO));
+
+    let mut z = -8.0;
+    while z <= 8.0 {
+        let got = phi_cdf_fixed(fx(z)).unwrap();
+        assert_close(got, phi_cdf_as(z), 1e-14, "phi_cdf");
+        z += 0.125;
+    }
+}
+
+#[test]
+fn discretized_integrals_match_f64() {
+    let f = |x: f64| gauss_pdf(x, 0.0, 1.0);
+    let g = |x: f64| gauss_pdf(x, 0.5, 1.2);
+    let ff = |x: Fixed| gauss_pdf_fixed(x, Fixed::ZERO, Fixed::ONE);
+    let gf = |x: Fixed| gauss_pdf_fixed(x, fx(0.5), fx(1.2));
+    let (min, max, step) = (-8.0, 8.0, 0.0625);
+    let l1 = discretized_l1_distance_fixed(fx(min), fx(max), fx(step), ff, gf).unwrap();
+    assert_close(l1, discretized_l1_distance(min, max, step, f, g), 1e-12, "l1");
+    let l2 = discretized_l2_sq_fixed(fx(min), fx(max), fx(step), ff, gf).unwrap();
+    assert_close(l2, discretized_l2_sq(min, max, step, f, g), 1e-12, "l2");
+    // grids beyond the compute bound are refused rather than silently truncated
+    assert_eq!(discretized_l2_sq_fixed(fx(min), fx(max), fx(1e-4), ff, gf), None);
+}
+
+#[test]
+fn effective_liquidity_matches_f64() {
+    let cases: &[(u64, u8, i64, i64, u64, u64)] = &[
+        (1_000, LIQUIDITY_STATIC, 100, 0, 0, u32::MAX as u64),
+        (1_000, LIQUIDITY_SQRT_DECAY, 10_000, 0, 0, u32::MAX as u64),
+        (5, LIQUIDITY_SQRT_DECAY, 0, 10, 0, 1_000),
+        (7, LIQUIDITY_SQRT_DECAY, i64::MAX, 0, 0, 7_000_000),
+        (3, LIQUIDITY_LINEAR_DECAY, 10_000, 4_000, 0, u32::MAX as u64),
+        (3, LIQUIDITY_LINEAR_DECAY, 10_000, 9_990, 500, u32::MAX as u64),
+    ];
+    for &(l0, schedule, exp, now, floor, ceiling) in cases {
+        let got = effective_liquidity_fixed(l0, schedule, exp, now, floor, ceiling).unwrap();
+        let expected = effective_liquidity(l0, schedule, exp, now, floor, ceiling);
+        assert_close(got, expected, expected * 1e-12, "effective_liquidity");
+    }
+}
+
+#[test]
+fn liquidity_schedules_decay_into_their_bounds() {
+    let l = |schedule, now| effective_liquidity_fixed(100, schedule, 10_000, now, 1_000, 500_000).unwrap().to_f64();
+    // static ignores the clock but still respects the bounds
+    assert_eq!(l(LIQUIDITY_STATIC, 0), 1_000.0);
+    assert_eq!(effective_liquidity_fixed(100, LIQUIDITY_STATIC, 10_000, 0, 0, 50).unwrap(), Fixed::from_int(50));
+    // sqrt: 100 * sqrt(10_000) at the start, a quarter of the way the time left is 3/4
+    assert_close(Fixed::from_f64(l(LIQUIDITY_SQRT_DECAY, 0)), 10_000.0, 1e-9, "sqrt start");
+    assert_close(Fixed::from_f64(l(LIQUIDITY_SQRT_DECAY, 2_500)), 10_000.0 * 0.75f64.sqrt(), 1e-9, "sqrt quarter");
+    // linear starts above the ceiling and ends on the floor
+    assert_eq!(l(LIQUIDITY_LINEAR_DECAY, 0), 500_000.0);
+    assert_eq!(l(LIQUIDITY_LINEAR_DECAY, 7_500), 250_000.0);
+    assert_eq!(l(LIQUIDITY_LINEAR_DECAY, 9_995), 1_000.0);
+    assert_eq!(l(LIQUIDITY_LINEAR_DECAY, 20_000), 1_000.0);
+    assert_eq!(effective_liquidity_fixed(100, 3, 10_000, 0, 0, 1_000), None);
+}
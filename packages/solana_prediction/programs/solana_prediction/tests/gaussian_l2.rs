@@ -0,0 +1,74 @@
+use solana_prediction::fixed::Fixed;
+use solana_prediction::math::*;
+
+fn fx(v: f64) -> Fixed { Fixed::from_f64(v) }
+
+fn assert_close(actual: Fixed, expected: f64, tol: f64, what: &str) {
+    let err = (actual.to_f64() - expected).abs();
+    assert!(err <= tol, "{what}: got {} expected {expected} (err {err:e} > {tol:e})", actual.to_f64());
+}
+
+const CASES: &[(f64, f64, f64, f64)] = &[
+    (0.0, 1.0, 0.0, 1.0),
+    (0.0, 1.0, 0.5, 1.0),
+    (0.0, 1.0, 0.0, 0.4),
+    (50_000.0, 10_000.0, 52_000.0, 8_000.0),
+    (0.25, 0.01, 0.3, 0.05),
+];
+
+#[test]
+fn closed_form_l2_matches_quadrature() {
+    for &(ma, sa, mb, sb) in CASES {
+        let lo = (ma - 10.0 * sa).min(mb - 10.0 * sb);
+        let hi = (ma + 10.0 * sa).max(mb + 10.0 * sb);
+        let step = (hi - lo) / 200_000.0;
+        let reference = discretized_l2_sq(lo, hi, step, |x| gauss_pdf(x, ma, sa), |x| gauss_pdf(x, mb, sb)).sqrt();
+        let got = gauss_l2_distance_fixed(fx(ma), fx(sa), fx(mb), fx(sb)).unwrap();
+        assert_close(got, reference, reference * 1e-4 + 1e-9, "l2 distance");
+
+        let na = gauss_l2_norm_fixed(fx(sa)).unwrap().to_f64();
+        let nb = gauss_l2_norm_fixed(fx(sb)).unwrap().to_f64();
+        let inner = gauss_inner_product_fixed(fx(ma), fx(sa), fx(mb), fx(sb)).unwrap().to_f64();
+        assert_close(gauss_overlap_fixed(fx(ma), fx(sa), fx(mb), fx(sb)).unwrap(), inner / (na * nb), 1e-12, "overlap");
+    }
+}
+
+#[test]
+fn scaled_functions_have_norm_k() {
+    let k = 1_000.0;
+    for &(ma, sa, mb, sb) in CASES {
+        let f = ScaledGauss::new(fx(k), fx(ma), fx(sa)).unwrap();
+        let g = ScaledGauss::new(fx(k), fx(mb), fx(sb)).unwrap();
+        let lo = (ma - 10.0 * sa).min(mb - 10.0 * sb);
+        let hi = (ma + 10.0 * sa).max(mb + 10.0 * sb);
+        let step = (hi - lo) / 100_000.0;
+        let fv = |x: f64| f.eval(fx(x)).unwrap().to_f64();
+        let gv = |x: f64| g.eval(fx(x)).unwrap().to_f64();
+        let norm = discretized_l2_sq(lo, hi, step, |_| 0.0, fv).sqrt();
+        assert!((norm - k).abs() < k * 1e-4, "||f|| = {norm}");
+        let dist = discretized_l2_sq(lo, hi, step, fv, gv).sqrt();
+        let got = gauss_scaled_l2_distance_fixed(fx(k), fx(ma), fx(sa), fx(mb), fx(sb)).unwrap();
+        assert_close(got, dist, k * 1e-4, "scaled distance");
+    }
+}
+
+#[test]
+fn max_loss_matches_dense_scan() {
+    let k = 1_000.0;
+    for &(ma, sa, mb, sb) in CASES {
+        let f = ScaledGauss::new(fx(k), fx(ma), fx(sa)).unwrap();
+        let g = ScaledGauss::new(fx(k), fx(mb), fx(sb)).unwrap();
+        let mut dense: f64 = 0.0;
+        for i in 0..=20_000 {
+            let x = ma - 6.0 * sa + 12.0 * sa * i as f64 / 20_000.0;
+            dense = dense.max(f.eval(fx(x)).unwrap().to_f64() - g.eval(fx(x)).unwrap().to_f64());
+        }
+        let got = gauss_max_loss_fixed(fx(k), fx(ma), fx(sa), fx(mb), fx(sb)).unwrap();
+        assert!(got.to_f64() >= dense - f.peak().to_f64() * 1e-6, "max loss {} < dense {dense}", got.to_f64());
+        assert!(got.to_f64() <= dense + f.peak().to_f64() * 1e-6, "max loss {} > dense {dense}", got.to_f64());
+        let collateral = gauss_collateral_required(k as u64, fx(ma), fx(sa), fx(mb), fx(sb)).unwrap();
+        assert_eq!(collateral, got.ceil_to_i64().unwrap() as u64);
+    }
+    // no move, no risk
+    assert_eq!(gauss_collateral_required(1_000, fx(1.0), fx(2.0), fx(1.0), fx(2.0)), Some(0));
+}
@@ -0,0 +1,73 @@
+use solana_prediction::fixed::Fixed;
+use solana_prediction::math::*;
+
+fn fx(v: f64) -> Fixed { Fixed::from_f64(v) }
+
+fn assert_close(actual: Fixed, expected: f64, tol: f64, what: &str) {
+    let err = (actual.to_f64() - expected).abs();
+    assert!(err <= tol, "{what}: got {} expected {expected} (err {err:e} > {tol:e})", actual.to_f64());
+}
+
+const SCALE: u32 = 1_000_000_000;
+
+/// f64 reference for f = k * p / ||p||_2 on each bin.
+fn scaled_f64(k: f64, mass: &[u32], width: f64) -> Vec<f64> {
+    let density: Vec<f64> = mass.iter().map(|&m| m as f64 / SCALE as f64 / width).collect();
+    let norm = density.iter().map(|d| d * d * width).sum::<f64>().sqrt();
+    density.iter().map(|d| k * d / norm).collect()
+}
+
+#[test]
+fn bins_cover_the_grid() {
+    let (lo, width) = (fx(10.0), fx(2.5));
+    assert_eq!(histogram_bin(fx(10.0), lo, width, 4), Some(0));
+    assert_eq!(histogram_bin(fx(12.49), lo, width, 4), Some(0));
+    assert_eq!(histogram_bin(fx(12.5), lo, width, 4), Some(1));
+    // the top edge and anything outside the range land in the end bins
+    assert_eq!(histogram_bin(fx(20.0), lo, width, 4), Some(3));
+    assert_eq!(histogram_bin(fx(1e6), lo, width, 4), Some(3));
+    assert_eq!(histogram_bin(fx(-1e6), lo, width, 4), Some(0));
+    assert_eq!(histogram_bin(fx(11.0), lo, Fixed::ZERO, 4), None);
+}
+
+#[test]
+fn norm_and_density_match_f64() {
+    let uniform = [SCALE / 4; 4];
+    // uniform density 1 / range has norm 1 / sqrt(range)
+    assert_close(histogram_l2_norm_fixed(&uniform, SCALE, fx(2.5)).unwrap(), 1.0 / 10f64.sqrt(), 1e-15, "uniform norm");
+
+    let mass = [100_000_000, 450_000_000, 400_000_000, 50_000_000];
+    let expected = scaled_f64(1_000.0, &mass, 2.5);
+    for (bin, &e) in expected.iter().enumerate() {
+        let got = histogram_scaled_density_fixed(fx(1_000.0), &mass, fx(2.5), bin).unwrap();
+        assert_close(got, e, e * 1e-12, "scaled density");
+    }
+    assert_eq!(histogram_scaled_density_fixed(fx(1_000.0), &mass, fx(2.5), 4), None);
+    assert_eq!(histogram_l2_norm_fixed(&[0, 0], SCALE, fx(1.0)), Some(Fixed::ZERO));
+    assert_eq!(histogram_scaled_density_fixed(fx(1.0), &[0, 0], fx(1.0), 0), None);
+}
+
+#[test]
+fn distance_and_max_loss_match_f64() {
+    let k = 1_000_000.0;
+    let width = 0.25;
+    let from: Vec<u32> = vec![SCALE / 8; 8];
+    let mut to = from.clone();
+    to[2] -= 100_000_000;
+    to[6] += 60_000_000;
+    to[7] += 40_000_000;
+
+    let f = scaled_f64(k, &from, width);
+    let g = scaled_f64(k, &to, width);
+    let l2 = f.iter().zip(&g).map(|(a, b)| (a - b) * (a - b) * width).sum::<f64>().sqrt();
+    assert_close(histogram_scaled_l2_distance_fixed(fx(k), &from, &to).unwrap(), l2, l2 * 1e-9, "distance");
+
+    let worst = f.iter().zip(&g).map(|(a, b)| a - b).fold(0.0, f64::max);
+    let got = histogram_max_loss_fixed(fx(k), &from, &to, fx(width)).unwrap();
+    assert_close(got, worst, worst * 1e-12, "max loss");
+    assert_eq!(histogram_collateral_required(k as u64, &from, &to, fx(width)), Some(got.ceil_to_i64().unwrap() as u64));
+
+    // no move, no risk; mismatched grids are refused
+    assert_eq!(histogram_collateral_required(k as u64, &from, &from, fx(width)), Some(0));
+    assert_eq!(histogram_max_loss_fixed(fx(k), &from, &to[..7], fx(width)), None);
+}
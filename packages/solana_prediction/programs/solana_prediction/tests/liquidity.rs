@@ -0,0 +1,64 @@
+use anchor_lang::prelude::Pubkey;
+use solana_prediction::{LiquidityPool, LpPosition, MarketStatus, LP_COOLDOWN_SECS, LP_RESOLUTION_LOCK_SECS};
+
+fn pool(total_liquidity: u64) -> LiquidityPool {
+    LiquidityPool {
+        market: Pubkey::new_unique(),
+        vault: Pubkey::new_unique(),
+        lp_mint: Pubkey::new_unique(),
+        total_liquidity,
+        fees_accrued: 0,
+        bump: 255,
+    }
+}
+
+#[test]
+fn first_deposit_mints_one_share_per_unit() {
+    let p = pool(0);
+    assert_eq!(p.shares_for(1_000, 0).unwrap(), 1_000);
+    assert_eq!(p.amount_for(1_000, 1_000).unwrap(), 0);
+}
+
+#[test]
+fn shares_redeem_pro_rata() {
+    // two providers with 1_000 and 3_000 shares on a 4_400 pool
+    let p = pool(4_400);
+    assert_eq!(p.amount_for(1_000, 4_000).unwrap(), 1_100);
+    assert_eq!(p.amount_for(3_000, 4_000).unwrap(), 3_300);
+
+    // a later deposit buys in at the same price and doesn't dilute them
+    assert_eq!(p.shares_for(1_100, 4_000).unwrap(), 1_000);
+}
+
+#[test]
+fn rounding_favours_the_pool() {
+    let p = pool(3_000);
+    // 1 unit into 3 units per 2 shares: 0.66 share rounds down to nothing
+    assert_eq!(p.shares_for(1, 2_000).unwrap(), 0);
+    // 1 share of 2_000 is 1.5 units, paid as 1
+    assert_eq!(p.amount_for(1, 2_000).unwrap(), 1);
+    assert!(p.amount_for(2_001, 2_000).is_err());
+}
+
+#[test]
+fn withdrawals_wait_out_the_cooldown_and_the_resolution_lock() {
+    let pos = LpPosition {
+        market: Pubkey::new_unique(),
+        provider: Pubkey::new_unique(),
+        deposited: 1_000,
+        shares: 1_000,
+        deposit_ts: 1_000,
+        bump: 255,
+    };
+    let resolution = 1_000_000;
+    let lock_start = resolution - LP_RESOLUTION_LOCK_SECS;
+    let cooled = 1_000 + LP_COOLDOWN_SECS;
+
+    assert!(!pos.unlocked(MarketStatus::Active, resolution, cooled - 1));
+    assert!(pos.unlocked(MarketStatus::Active, resolution, cooled));
+    assert!(pos.unlocked(MarketStatus::Active, resolution, lock_start - 1));
+    // locked from the window's start, through resolution_time, until the market resolves
+    assert!(!pos.unlocked(MarketStatus::Active, resolution, lock_start));
+    assert!(!pos.unlocked(MarketStatus::Closed, resolution, resolution + 10));
+    assert!(pos.unlocked(MarketStatus::Resolved, resolution, resolution + 10));
+}
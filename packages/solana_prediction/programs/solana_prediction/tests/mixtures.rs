@@ -0,0 +1,95 @@
+use solana_prediction::fixed::Fixed;
+use solana_prediction::math::*;
+
+fn fx(v: f64) -> Fixed { Fixed::from_f64(v) }
+
+fn assert_close(actual: Fixed, expected: f64, tol: f64, what: &str) {
+    let err = (actual.to_f64() - expected).abs();
+    assert!(err <= tol, "{what}: got {} expected {expected} (err {err:e} > {tol:e})", actual.to_f64());
+}
+
+fn mix(parts: &[(f64, f64, f64)]) -> Vec<GaussComponent> {
+    parts.iter().map(|&(w, mu, sigma)| GaussComponent { weight: fx(w), mu: fx(mu), sigma: fx(sigma) }).collect()
+}
+
+fn pdf_f64(parts: &[(f64, f64, f64)], x: f64) -> f64 {
+    parts.iter().map(|&(w, mu, sigma)| w * gauss_pdf(x, mu, sigma)).sum()
+}
+
+// "ships in Q2 or slips to Q4" against a single-peaked view, and a reweighting of the same modes
+const BIMODAL: &[(f64, f64, f64)] = &[(0.6, 4.0, 0.5), (0.4, 10.0, 1.0)];
+const UNIMODAL: &[(f64, f64, f64)] = &[(1.0, 6.0, 2.0)];
+const REWEIGHTED: &[(f64, f64, f64)] = &[(0.3, 4.0, 0.5), (0.5, 10.0, 1.0), (0.2, 7.0, 3.0)];
+
+#[test]
+fn pdf_and_cdf_match_f64() {
+    let m = mix(BIMODAL);
+    for i in 0..=56 {
+        let x = i as f64 * 0.25;
+        assert_close(mixture_pdf_fixed(&m, fx(x)).unwrap(), pdf_f64(BIMODAL, x), 1e-12, "mixture pdf");
+        let cdf: f64 = BIMODAL.iter().map(|&(w, mu, sigma)| w * phi_cdf_as((x - mu) / sigma)).sum();
+        assert_close(mixture_cdf_fixed(&m, fx(x)).unwrap(), cdf, 1e-13, "mixture cdf");
+    }
+}
+
+#[test]
+fn closed_form_inner_product_matches_quadrature() {
+    for (a, b) in [(BIMODAL, UNIMODAL), (BIMODAL, REWEIGHTED), (REWEIGHTED, REWEIGHTED)] {
+        let (lo, hi, step) = (-20.0, 30.0, 1e-3);
+        let mut reference = 0.0;
+        let mut x = lo;
+        while x < hi {
+            reference += pdf_f64(a, x) * pdf_f64(b, x) * step;
+            x += step;
+        }
+        let got = mixture_inner_product_fixed(&mix(a), &mix(b)).unwrap();
+        assert_close(got, reference, reference * 1e-6, "inner product");
+    }
+}
+
+#[test]
+fn single_component_reduces_to_gaussian() {
+    let k = fx(1_000.0);
+    let a = mix(&[(1.0, 50_000.0, 10_000.0)]);
+    let b = mix(&[(1.0, 52_000.0, 8_000.0)]);
+    let expected = gauss_scaled_l2_distance_fixed(k, fx(50_000.0), fx(10_000.0), fx(52_000.0), fx(8_000.0)).unwrap();
+    assert_close(mixture_scaled_l2_distance_fixed(k, &a, &b).unwrap(), expected.to_f64(), 1e-6, "distance");
+    let expected = gauss_max_loss_fixed(k, fx(50_000.0), fx(10_000.0), fx(52_000.0), fx(8_000.0)).unwrap();
+    assert_close(mixture_max_loss_fixed(k, &a, &b).unwrap(), expected.to_f64(), 1e-6, "max loss");
+}
+
+#[test]
+fn max_loss_matches_dense_scan() {
+    let k = 1_000.0;
+    for (a, b) in [(BIMODAL, UNIMODAL), (UNIMODAL, BIMODAL), (BIMODAL, REWEIGHTED), (REWEIGHTED, BIMODAL)] {
+        let norm = |parts| mixture_l2_norm_fixed(&mix(parts)).unwrap().to_f64();
+        let (na, nb) = (norm(a), norm(b));
+        let mut dense: f64 = 0.0;
+        for i in 0..=40_000 {
+            let x = -4.0 + 22.0 * i as f64 / 40_000.0;
+            dense = dense.max(k * (pdf_f64(a, x) / na - pdf_f64(b, x) / nb));
+        }
+        let got = mixture_max_loss_fixed(fx(k), &mix(a), &mix(b)).unwrap().to_f64();
+        assert!((got - dense).abs() <= dense * 1e-6 + 1e-9, "max loss {got} vs dense {dense}");
+        let collateral = mixture_collateral_required(k as u64, &mix(a), &mix(b)).unwrap();
+        assert_eq!(collateral, got.ceil() as u64);
+    }
+    // no move, no risk
+    assert_eq!(mixture_collateral_required(1_000, &mix(BIMODAL), &mix(BIMODAL)), Some(0));
+}
+
+#[test]
+fn settlement_density_stays_within_the_posted_collateral() {
+    let k = 1_000.0;
+    let (a, b) = (mix(BIMODAL), mix(REWEIGHTED));
+    let norm = mixture_l2_norm_fixed(&a).unwrap().to_f64();
+    let collateral = mixture_collateral_required(k as u64, &a, &b).unwrap() as f64;
+    for i in 0..=96 {
+        let x = -2.0 + i as f64 * 0.25;
+        let f = mixture_scaled_density_fixed(fx(k), &a, fx(x)).unwrap();
+        assert_close(f, k * pdf_f64(BIMODAL, x) / norm, 1e-9, "scaled density");
+        // collateral + g - f, what the position is paid at outcome x, never goes negative
+        let g = mixture_scaled_density_fixed(fx(k), &b, fx(x)).unwrap();
+        assert!(collateral + g.to_f64() - f.to_f64() >= 0.0, "payout at {x}");
+    }
+}
@@ -0,0 +1,252 @@
+use anchor_lang::prelude::Pubkey;
+use solana_prediction::{BookSide, OpenOrders, OrderNode, Side, BOOK_NIL, MAX_BOOK_ORDERS, MAX_PRICE_LEVELS};
+
+fn book(side: Side) -> Box<BookSide> {
+    let mut b: Box<BookSide> = Box::new(bytemuck::Zeroable::zeroed());
+    b.init(Pubkey::new_unique(), side, 255);
+    b
+}
+
+fn order(order_id: u64, price_bps: u64, size: u64) -> OrderNode {
+    OrderNode {
+        owner: Pubkey::new_unique(),
+        order_id,
+        client_order_id: 1_000 + order_id,
+        price_bps,
+        size,
+        placed_ts: 0,
+        expiry_ts: 0,
+        next: BOOK_NIL,
+        _padding: [0; 6],
+    }
+}
+
+fn open_orders(owner: Pubkey) -> OpenOrders {
+    OpenOrders {
+        market: Pubkey::new_unique(),
+        owner,
+        base_locked: 0,
+        quote_locked: 0,
+        base_free: 0,
+        quote_free: 0,
+        yes_shares: 0,
+        no_shares: 0,
+        bump: 255,
+    }
+}
+
+fn queued_ids(b: &BookSide, level: usize) -> Vec<u64> {
+    b.queue(&b.levels()[level]).map(|o| o.order_id).collect()
+}
+
+#[test]
+fn levels_sort_best_first_and_queue_fifo() {
+    let mut bids = book(Side::Buy);
+    for (id, price) in [(0, 5_000), (1, 5_200), (2, 4_900), (3, 5_200), (4, 5_000), (5, 5_200)] {
+        bids.insert(order(id, price, 10 + id)).unwrap();
+    }
+    let prices: Vec<u64> = bids.levels().iter().map(|l| l.price_bps).collect();
+    assert_eq!(prices, [5_200, 5_000, 4_900]);
+    assert_eq!(bids.best_price(), Some(5_200));
+    assert_eq!(queued_ids(&bids, 0), [1, 3, 5]);
+    assert_eq!(queued_ids(&bids, 1), [0, 4]);
+    assert_eq!(bids.levels()[0].total_size, 11 + 13 + 15);
+    assert_eq!(bids.levels()[0].count, 3);
+    assert_eq!(bids.order_count, 6);
+
+    let mut asks = book(Side::Sell);
+    for (id, price) in [(0, 5_300), (1, 5_250), (2, 5_400)] {
+        asks.insert(order(id, price, 1)).unwrap();
+    }
+    let prices: Vec<u64> = asks.levels().iter().map(|l| l.price_bps).collect();
+    assert_eq!(prices, [5_250, 5_300, 5_400]);
+    assert_eq!(asks.best_price(), Some(5_250));
+}
+
+#[test]
+fn capacity_limits_are_enforced() {
+    let mut bids = book(Side::Buy);
+    for i in 0..MAX_PRICE_LEVELS as u64 {
+        bids.insert(order(i, 1_000 + i, 1)).unwrap();
+    }
+    // a new price needs a level, an existing one only a slot
+    assert!(bids.insert(order(999, 10, 1)).is_err());
+    let mut id = MAX_PRICE_LEVELS as u64;
+    while (bids.order_count as usize) < MAX_BOOK_ORDERS {
+        bids.insert(order(id, 1_000, 1)).unwrap();
+        id += 1;
+    }
+    assert!(bids.insert(order(id, 1_000, 1)).is_err());
+    assert_eq!(bids.free_head, BOOK_NIL);
+    assert_eq!(bids.levels().iter().map(|l| l.count as usize).sum::<usize>(), MAX_BOOK_ORDERS);
+}
+
+#[test]
+fn take_fills_fifo_within_the_limit() {
+    let mut asks = book(Side::Sell);
+    for (id, price, size) in [(0, 5_100, 5), (1, 5_000, 3), (2, 5_000, 4), (3, 5_300, 10)] {
+        asks.insert(order(id, price, size)).unwrap();
+    }
+    // 5_000 level is drained oldest first, then 5_100 partially; 5_300 is past the limit
+    let fills = asks.take(10, 5_200, 0, |_| true).fills;
+    let got: Vec<(u64, u64, u64)> = fills.iter().map(|f| (f.order_id, f.price_bps, f.size)).collect();
+    assert_eq!(got, [(1, 5_000, 3), (2, 5_000, 4), (0, 5_100, 3)]);
+    assert_eq!(asks.best_price(), Some(5_100));
+    assert_eq!(asks.levels()[0].total_size, 2);
+    assert_eq!(asks.order_count, 2);
+
+    // nothing at or below the limit
+    assert!(asks.take(1, 5_000, 0, |_| true).fills.is_empty());
+    // filling past every level empties the side
+    let fills = asks.take(100, u64::MAX, 0, |_| true).fills;
+    assert_eq!(fills.iter().map(|f| f.size).sum::<u64>(), 12);
+    assert_eq!(asks.level_count, 0);
+    assert_eq!(asks.best_price(), None);
+
+    // freed slots are reused
+    for i in 0..MAX_BOOK_ORDERS as u64 {
+        asks.insert(order(100 + i, 6_000, 1)).unwrap();
+    }
+    assert_eq!(asks.free_head, BOOK_NIL);
+}
+
+#[test]
+fn bids_take_down_to_the_limit() {
+    let mut bids = book(Side::Buy);
+    for (id, price, size) in [(0, 4_800, 2), (1, 4_900, 2), (2, 4_700, 2)] {
+        bids.insert(order(id, price, size)).unwrap();
+    }
+    let fills = bids.take(5, 4_800, 0, |_| true).fills;
+    let got: Vec<(u64, u64)> = fills.iter().map(|f| (f.order_id, f.size)).collect();
+    assert_eq!(got, [(1, 2), (0, 2)]);
+    assert_eq!(bids.best_price(), Some(4_700));
+}
+
+#[test]
+fn remove_unlinks_from_anywhere_in_a_queue() {
+    let mut asks = book(Side::Sell);
+    for (id, price) in [(0, 5_000), (1, 5_000), (2, 5_000), (3, 5_100)] {
+        asks.insert(order(id, price, 10)).unwrap();
+    }
+    // middle, then tail: the queue stays linked and new orders still append
+    assert_eq!(asks.remove(1).map(|o| o.size), Some(10));
+    assert_eq!(asks.remove(2).map(|o| o.order_id), Some(2));
+    assert!(asks.remove(2).is_none());
+    asks.insert(order(4, 5_000, 10)).unwrap();
+    assert_eq!(queued_ids(&asks, 0), [0, 4]);
+    assert_eq!(asks.levels()[0].total_size, 20);
+
+    // removing the last order at the best price closes the level
+    asks.remove(0).unwrap();
+    asks.remove(4).unwrap();
+    assert_eq!(asks.best_price(), Some(5_100));
+    assert_eq!(asks.order_count, 1);
+
+    assert_eq!(asks.find(|o| o.client_order_id == 1_003).map(|o| o.order_id), Some(3));
+    assert!(asks.find(|o| o.client_order_id == 1_000).is_none());
+}
+
+#[test]
+fn cancel_releases_what_is_still_resting() {
+    let mut oo = open_orders(Pubkey::new_unique());
+    // a bid locks its notional rounded up, an ask its size
+    oo.lock(Side::Buy, 5_001, 3).unwrap();
+    oo.lock(Side::Sell, 6_000, 7).unwrap();
+    assert_eq!((oo.quote_locked, oo.base_locked), (2, 7));
+
+    let mut bid = order(0, 5_001, 3);
+    bid.size = 1; // two of three were filled
+    assert_eq!(oo.release(Side::Buy, &bid).unwrap(), 1);
+    assert_eq!((oo.quote_locked, oo.quote_free), (1, 1));
+    assert_eq!(oo.release(Side::Sell, &order(1, 6_000, 7)).unwrap(), 7);
+    assert_eq!((oo.base_locked, oo.base_free), (0, 7));
+}
+
+#[test]
+fn fills_move_the_makers_collateral_to_the_taker() {
+    // an ask's maker posts the whole unit for NO and is paid the taker's price for YES
+    let mut maker = open_orders(Pubkey::new_unique());
+    let mut taker = open_orders(Pubkey::new_unique());
+    let mut asks = book(Side::Sell);
+    let mut ask = order(0, 5_000, 4);
+    ask.owner = maker.owner;
+    assert_eq!(maker.lock(Side::Sell, 5_000, 4).unwrap(), 4);
+    asks.insert(ask).unwrap();
+    let fill = asks.take(3, 5_100, 0, |_| true).fills[0];
+    assert_eq!((fill.maker, fill.size, fill.remaining), (maker.owner, 3, 1));
+    // 3 @ 0.50 costs the taker 1.5, rounded up
+    assert_eq!(maker.fill(Side::Sell, &fill).unwrap(), 2);
+    taker.credit(Side::Buy, fill.size).unwrap();
+    assert_eq!((maker.base_locked, maker.quote_free, maker.no_shares), (1, 2, 3));
+    assert_eq!(taker.yes_shares, 3);
+    // the LP fee the taker paid on the fill is the maker's to settle
+    maker.earn(1).unwrap();
+    assert_eq!(maker.settle().unwrap(), 3);
+
+    // a bid's maker pays its price out of its lock for YES and the taker posts the rest for NO
+    let (mut maker, mut taker) = (open_orders(Pubkey::new_unique()), open_orders(Pubkey::new_unique()));
+    let mut bids = book(Side::Buy);
+    let mut bid = order(1, 5_001, 3);
+    bid.owner = maker.owner;
+    assert_eq!(maker.lock(Side::Buy, 5_001, 3).unwrap(), 2);
+    bids.insert(bid).unwrap();
+    let mut posted = 0;
+    for size in [2, 1] {
+        let fill = bids.take(size, 5_000, 0, |_| true).fills[0];
+        posted += maker.fill(Side::Buy, &fill).unwrap();
+        taker.credit(Side::Sell, fill.size).unwrap();
+    }
+    // both sides of the 3 pairs are fully backed: the maker's lock of 2 plus the taker's 1
+    assert_eq!((maker.quote_locked, maker.yes_shares), (0, 3));
+    assert_eq!((posted, taker.no_shares), (1, 3));
+    assert!(bids.best_price().is_none());
+}
+
+#[test]
+fn shares_redeem_in_pairs_and_for_the_winning_side() {
+    let mut oo = open_orders(Pubkey::new_unique());
+    oo.credit(Side::Buy, 5).unwrap();
+    oo.credit(Side::Sell, 2).unwrap();
+    // before resolution only matched pairs come back
+    assert_eq!(oo.redeem(None).unwrap(), 2);
+    assert_eq!((oo.yes_shares, oo.no_shares), (3, 0));
+    assert_eq!(oo.redeem(None).unwrap(), 0);
+
+    let mut lost = oo.clone();
+    assert_eq!(oo.redeem(Some(true)).unwrap(), 3);
+    assert_eq!(lost.redeem(Some(false)).unwrap(), 0);
+    assert_eq!((oo.yes_shares, lost.yes_shares), (0, 0));
+}
+
+#[test]
+fn expired_orders_are_pruned_or_stepped_over() {
+    let mut asks = book(Side::Sell);
+    let keep = Pubkey::new_unique();
+    for (id, price, expiry) in [(0, 5_000, 100), (1, 5_000, 0), (2, 5_000, 100), (3, 5_100, 100), (4, 5_100, 0)] {
+        let mut o = order(id, price, 2);
+        o.expiry_ts = expiry;
+        if id == 2 { o.owner = keep; }
+        asks.insert(o).unwrap();
+    }
+    // nothing has expired before t = 100
+    assert_eq!(asks.depth(5_100, 99), 10);
+    assert_eq!(asks.depth(5_100, 100), 4);
+    assert!(asks.crosses(5_000) && !asks.crosses(4_999));
+
+    // order 2's owner isn't prunable, so it is skipped but stays on the book
+    let take = asks.take(3, 5_100, 100, |o| o.owner != keep);
+    let filled: Vec<(u64, u64)> = take.fills.iter().map(|f| (f.order_id, f.size)).collect();
+    assert_eq!(filled, [(1, 2), (4, 1)]);
+    let pruned: Vec<u64> = take.expired.iter().map(|o| o.order_id).collect();
+    assert_eq!(pruned, [0, 3]);
+    assert_eq!(queued_ids(&asks, 0), [2]);
+    assert_eq!(queued_ids(&asks, 1), [4]);
+    assert_eq!((asks.levels()[0].total_size, asks.levels()[1].total_size), (2, 1));
+    assert_eq!(asks.order_count, 2);
+
+    // the crank sweeps what is left once it may
+    assert!(asks.prune_expired(100, |o| o.owner != keep).is_empty());
+    let swept = asks.prune_expired(100, |_| true);
+    assert_eq!(swept.iter().map(|o| o.order_id).collect::<Vec<_>>(), [2]);
+    assert_eq!(asks.best_price(), Some(5_100));
+}
@@ -0,0 +1,290 @@
+use anchor_lang::prelude::Pubkey;
+use solana_prediction::fixed::Fixed;
+use solana_prediction::math::*;
+use solana_prediction::{LiquiditySchedule, PmAmmPool, PmAmmQuote, Side};
+
+fn fx(v: f64) -> Fixed { Fixed::from_f64(v) }
+
+/// Shares takers hold in `pool`, enough for any sale the tests make.
+const OUTSTANDING: u64 = 1_000_000_000;
+
+/// A static pool of liquidity `l0` at price Φ(z), with `OUTSTANDING` shares sold and just
+/// enough collateral to back them.
+fn pool(z: f64, l0: u64, fee_bps: u16) -> PmAmmPool {
+    let (x, y) = PmAmmPool::reserves_at(fx(z), Fixed::from_int(l0 as i64)).unwrap();
+    PmAmmPool {
+        market: Pubkey::new_unique(),
+        x,
+        y,
+        l0,
+        schedule: LiquiditySchedule::Static,
+        fee_bps,
+        expiry_ts: 1_000_000,
+        fees: 0,
+        l_floor: 0,
+        l_ceiling: i64::MAX as u64,
+        handoff_ts: 0,
+        collateral: (x + OUTSTANDING).max(y),
+        shares_outstanding: OUTSTANDING,
+        bump: 255,
+    }
+}
+
+/// Average price of a swap in bps of Y per X.
+fn avg_bps(traded: u64, quote: u64) -> f64 { quote as f64 * 10_000.0 / traded as f64 }
+
+#[test]
+fn solver_recovers_z_from_reserves() {
+    let l = 1_000_000_000u64;
+    for z in [-4.0, -1.5, -0.25, 0.0, 0.25, 1.5, 4.0] {
+        let (x, y) = PmAmmPool::reserves_at(fx(z), Fixed::from_int(l as i64)).unwrap();
+        let found = pmamm_z_fixed(x, y).unwrap();
+        // the thin side of a pool far from even odds only pins z to a unit of that reserve
+        assert!((found.to_f64() - z).abs() < 1e-7 / phi_cdf_as(-z.abs()), "z = {z}: solved {}", found.to_f64());
+        // the reserves satisfy the invariant to within their own rounding
+        let residual = pmamm_invariant_fixed(Fixed::from_int(x as i64), Fixed::from_int(y as i64), Fixed::from_int(l as i64));
+        assert!(residual.unwrap().to_f64().abs() < 2.0, "z = {z}: residual {:?}", residual);
+    }
+    // the solver is deterministic and doesn't depend on the scale of the pool
+    assert_eq!(pmamm_z_fixed(300, 700), pmamm_z_fixed(300, 700));
+    assert!((pmamm_z_fixed(3_000, 7_000).unwrap().to_f64() - pmamm_z_fixed(300, 700).unwrap().to_f64()).abs() < 1e-12);
+    // reserves beyond the bounds clamp to them
+    assert_eq!(pmamm_z_fixed(0, 1_000).unwrap(), Fixed::from_int(PMAMM_MAX_Z));
+    assert_eq!(pmamm_z_fixed(1_000, 0).unwrap(), Fixed::from_int(-PMAMM_MAX_Z));
+}
+
+#[test]
+fn marginal_price_is_the_normal_cdf_and_brackets_swaps() {
+    let p = pool(0.0, 1_000_000, 0);
+    assert_eq!(p.marginal_price_bps(Side::Buy).unwrap(), 5_000);
+    assert_eq!(p.marginal_price_bps(Side::Sell).unwrap(), 5_000);
+    assert_eq!(pool(1.0, 1_000_000, 0).marginal_price_bps(Side::Sell).unwrap(), 8_413);
+
+    // a buy pays more than the marginal price on average and moves it up past that average
+    let mut buy = p.clone();
+    let (traded, quote) = buy.swap(Side::Buy, 100_000, 0).unwrap();
+    assert_eq!(traded, 100_000);
+    let avg = avg_bps(traded, quote);
+    assert!(avg > 5_000.0 && (buy.marginal_price_bps(Side::Buy).unwrap() as f64) > avg);
+    // z moved by size / L
+    assert!((pmamm_z_fixed(buy.x, buy.y).unwrap().to_f64() - 0.1).abs() < 1e-5);
+
+    // and a sell receives less, moving it down past that average
+    let mut sell = p.clone();
+    let (traded, quote) = sell.swap(Side::Sell, 100_000, 0).unwrap();
+    let avg = avg_bps(traded, quote);
+    assert!(avg < 5_000.0 && (sell.marginal_price_bps(Side::Sell).unwrap() as f64) < avg);
+}
+
+#[test]
+fn prices_stay_inside_the_unit_interval() {
+    let mut p = pool(0.0, 1_000_000, 0);
+    // buying far more than the pool holds walks the price towards 1 and stops at the bound
+    let (traded, quote) = p.swap(Side::Buy, 100_000_000, 0).unwrap();
+    assert!(traded < 100_000_000 && quote < traded);
+    assert_eq!(p.marginal_price_bps(Side::Buy).unwrap(), 10_000);
+    // rounding dust left in the reserves keeps z just inside the bound, but never past a price of 1
+    let (traded, quote) = p.swap(Side::Buy, 1_000, 0).unwrap();
+    assert!(quote <= traded);
+    assert_eq!(p.marginal_price_bps(Side::Buy).unwrap(), 10_000);
+
+    let mut p = pool(0.0, 1_000_000, 0);
+    p.swap(Side::Sell, 100_000_000, 0).unwrap();
+    assert_eq!(p.marginal_price_bps(Side::Sell).unwrap(), 0);
+}
+
+#[test]
+fn fee_widens_the_marginal_price() {
+    let p = pool(0.0, 1_000_000, 30);
+    assert_eq!(p.marginal_price_bps(Side::Buy).unwrap(), 5_015);
+    assert_eq!(p.marginal_price_bps(Side::Sell).unwrap(), 4_985);
+
+    // a single unit never beats the marginal price, whichever way it goes
+    let mut buy = p.clone();
+    let (_, paid) = buy.swap(Side::Buy, 1, 0).unwrap();
+    assert!(paid as u128 * 10_000 >= 5_015);
+    let (_, got) = p.clone().swap(Side::Sell, 1, 0).unwrap();
+    assert!((got as u128) * 10_000 <= 4_985);
+    // fees are kept outside the reserves, which grow by the rounded-up quote alone
+    assert_eq!(buy.fees, 1);
+    assert_eq!(buy.y, p.y + paid - buy.fees);
+    assert!(buy.y >= pool(1.0 / 1_000_000.0, 1_000_000, 30).y);
+}
+
+#[test]
+fn fills_are_all_or_nothing_and_quote_both_legs() {
+    let p = pool(0.0, 1_000_000, 30);
+    let mut swapped = p.clone();
+    let (_, paid) = swapped.swap(Side::Buy, 100_000, 0).unwrap();
+    let mut filled = p.clone();
+    let buy = filled.fill(Side::Buy, 100_000, 0).unwrap();
+    assert_eq!(buy, PmAmmQuote::new(Side::Buy, 100_000, paid, swapped.fees));
+    assert_eq!((filled.x, filled.y), (swapped.x, swapped.y));
+
+    // a sale takes the shares in and pays out what leaves the pool, whose fee stays behind
+    let collateral = filled.collateral;
+    let sell = filled.fill(Side::Sell, 100_000, 0).unwrap();
+    assert_eq!(sell.amount_in, 100_000);
+    assert_eq!(sell.amount_out, collateral - filled.collateral);
+    assert!(sell.amount_out < paid);
+
+    // `swap` partly fills past the price bound, where a fill fails
+    assert!(pool(0.0, 1_000_000, 30).swap(Side::Buy, 100_000_000, 0).unwrap().0 < 100_000_000);
+    assert!(pool(0.0, 1_000_000, 30).fill(Side::Buy, 100_000_000, 0).is_err());
+}
+
+#[test]
+fn round_trips_never_profit() {
+    for fee_bps in [0, 30] {
+        let mut p = pool(0.3, 2_000_000, fee_bps);
+        let (bought, paid) = p.swap(Side::Buy, 250_000, 0).unwrap();
+        let (sold, got) = p.swap(Side::Sell, bought, 0).unwrap();
+        assert_eq!(sold, bought);
+        assert!(got <= paid, "fee {fee_bps}: paid {paid}, got back {got}");
+    }
+}
+
+#[test]
+fn decaying_liquidity_keeps_the_price() {
+    let mut p = pool(0.5, 1_000, 0);
+    p.schedule = LiquiditySchedule::SqrtDecay;
+    p.expiry_ts = 10_000;
+    (p.x, p.y) = PmAmmPool::reserves_at(fx(0.5), p.liquidity(0).unwrap()).unwrap();
+    let price = p.marginal_price_bps(Side::Buy).unwrap();
+    let y0 = p.y;
+
+    // a quarter of the way to expiry L has fallen by sqrt(3/4); the next trade re-bases
+    // the reserves onto the thinner curve without moving the price
+    p.swap(Side::Buy, 1, 2_500).unwrap();
+    let l = p.liquidity(2_500).unwrap().to_f64();
+    assert!((l - 1_000.0 * 7_500f64.sqrt()).abs() < 1e-6);
+    assert!((p.y as f64 / y0 as f64 - 0.75f64.sqrt()).abs() < 1e-4);
+    assert_eq!(p.marginal_price_bps(Side::Buy).unwrap(), price);
+}
+
+/// Pool value at price `p` and liquidity `l`, marked at `p`: p·x + (1 - p)·y.
+fn pool_value(p: f64, l: f64) -> f64 {
+    let z = probit_fixed(fx(p)).unwrap();
+    let x = pmamm_reserve_fixed(z.checked_neg().unwrap()).unwrap().to_f64() * l;
+    let y = pmamm_reserve_fixed(z).unwrap().to_f64() * l;
+    p * x + (1.0 - p) * y
+}
+
+#[test]
+fn lvr_is_uniform_in_the_price() {
+    // Under Gaussian score dynamics the price's volatility is σ_P = φ(z)/sqrt(T - t), and
+    // the LP's loss-versus-rebalancing rate is ½·σ_P²·|V''(P)|. pm-AMM makes that a constant
+    // share of the pool's value, V / (2(T - t)), whatever the price.
+    let (l, tau) = (1_000_000.0, 86_400f64);
+    let h = 1e-3;
+    for p in [0.1, 0.3, 0.5, 0.7, 0.9] {
+        let v = pool_value(p, l);
+        // the marked value is L·φ(z)
+        let z = probit(p);
+        assert!((v / (l * gauss_pdf(z, 0.0, 1.0)) - 1.0).abs() < 1e-6, "p = {p}: V = {v}");
+        let curvature = (pool_value(p + h, l) - 2.0 * v + pool_value(p - h, l)) / (h * h);
+        let sigma_p = gauss_pdf(z, 0.0, 1.0) / tau.sqrt();
+        let lvr = 0.5 * sigma_p * sigma_p * curvature.abs();
+        let ratio = lvr * 2.0 * tau / v;
+        assert!((ratio - 1.0).abs() < 1e-3, "p = {p}: LVR·2τ/V = {ratio}");
+    }
+}
+
+#[test]
+fn schedules_bound_the_liquidity_and_hand_off_to_the_book() {
+    let mut p = pool(0.0, 10, 0);
+    p.schedule = LiquiditySchedule::LinearDecay;
+    p.expiry_ts = 10_000;
+    (p.l_floor, p.l_ceiling) = (5_000, 50_000);
+    assert_eq!(p.liquidity(0).unwrap(), Fixed::from_int(50_000));
+    assert_eq!(p.liquidity(7_000).unwrap(), Fixed::from_int(30_000));
+    assert_eq!(p.liquidity(9_999).unwrap(), Fixed::from_int(5_000));
+
+    assert!(p.quoting(9_999) && !p.quoting(10_000));
+    p.handoff_ts = 8_000;
+    assert!(p.quoting(7_999) && !p.quoting(8_000));
+}
+
+#[test]
+fn collateral_backs_every_share_through_trades_and_decay() {
+    let mut p = pool(0.0, 1_000_000, 30);
+    p.schedule = LiquiditySchedule::SqrtDecay;
+    p.expiry_ts = 100_000;
+    (p.x, p.y) = PmAmmPool::reserves_at(Fixed::ZERO, p.liquidity(0).unwrap()).unwrap();
+    (p.collateral, p.shares_outstanding) = (p.y, 0);
+
+    let trades = [(Side::Buy, 40_000_000), (Side::Sell, 15_000_000), (Side::Buy, 90_000_000), (Side::Sell, 100_000_000)];
+    for (i, (side, size)) in trades.into_iter().enumerate() {
+        let (collateral, outstanding) = (p.collateral, p.shares_outstanding);
+        let (traded, total) = p.swap(side, size, 20_000 * i as i64).unwrap();
+        // the pool's collateral, its fee included, and the takers' shares move by exactly what
+        // changed hands
+        if side == Side::Buy {
+            assert_eq!((p.collateral - collateral, p.shares_outstanding - outstanding), (total, traded));
+        } else {
+            assert_eq!((collateral - p.collateral, outstanding - p.shares_outstanding), (total, traded));
+        }
+        assert!(p.backed(), "trade {i}: x = {}, y = {}, shares = {}, collateral = {}", p.x, p.y, p.shares_outstanding, p.collateral);
+    }
+
+    // takers can't sell back more than they were minted
+    let mut p = pool(0.0, 1_000_000, 0);
+    p.shares_outstanding = 10;
+    assert!(p.swap(Side::Sell, 11, 0).is_err());
+}
+
+#[test]
+fn resolved_shares_redeem_for_the_winning_side_only() {
+    let mut p = pool(0.0, 1_000_000, 0);
+    p.shares_outstanding = 0;
+    let (traded, _) = p.swap(Side::Buy, 50_000, 0).unwrap();
+
+    // YES won: each share takes a unit of the pool's collateral, which still backs the rest
+    let mut won = p.clone();
+    let collateral = won.collateral;
+    assert_eq!(won.redeem(traded, true).unwrap(), traded);
+    assert_eq!((won.collateral, won.shares_outstanding), (collateral - traded, 0));
+    assert!(won.backed());
+
+    // NO won: the shares are burnt for nothing
+    let mut lost = p.clone();
+    assert_eq!(lost.redeem(traded, false).unwrap(), 0);
+    assert_eq!((lost.collateral, lost.shares_outstanding), (collateral, 0));
+
+    // nobody redeems more than takers were minted
+    assert!(p.redeem(traded + 1, true).is_err());
+}
+
+#[test]
+fn resolved_pool_releases_everything_but_the_winning_shares() {
+    let mut p = pool(0.0, 1_000_000, 0);
+    p.shares_outstanding = 0;
+    let (traded, _) = p.swap(Side::Buy, 50_000, 0).unwrap();
+    let collateral = p.collateral;
+
+    // YES won: the takers' shares keep their collateral, and redeeming them empties the pool
+    let mut won = p.clone();
+    assert_eq!(won.withdraw_residual(true).unwrap(), collateral - traded);
+    assert_eq!((won.x, won.y, won.collateral), (0, 0, traded));
+    assert_eq!(won.redeem(traded, true).unwrap(), traded);
+    assert_eq!(won.collateral, 0);
+    assert_eq!(won.withdraw_residual(true).unwrap(), 0);
+
+    // NO won: the whole pool is residual, and the losing shares still burn for nothing
+    let mut lost = p.clone();
+    assert_eq!(lost.withdraw_residual(false).unwrap(), collateral);
+    assert_eq!(lost.redeem(traded, false).unwrap(), 0);
+    assert_eq!(lost.collateral, 0);
+}
+
+#[test]
+fn the_pool_fee_is_withdrawn_with_the_residual() {
+    let (mut charged, mut free) = (pool(0.0, 1_000_000, 30), pool(0.0, 1_000_000, 0));
+    let (traded, _) = charged.swap(Side::Buy, 50_000, 0).unwrap();
+    assert_eq!(free.swap(Side::Buy, 50_000, 0).unwrap().0, traded);
+    assert!(charged.fees > 0);
+    // the fee never enters the reserves, only the collateral the authority takes back
+    assert_eq!((charged.x, charged.y), (free.x, free.y));
+    assert_eq!(charged.collateral - free.collateral, charged.fees);
+    assert_eq!(charged.withdraw_residual(true).unwrap() - free.withdraw_residual(true).unwrap(), charged.fees);
+}
@@ -0,0 +1,90 @@
+use solana_prediction::fixed::Fixed;
+use solana_prediction::math::*;
+
+fn fx(v: f64) -> Fixed { Fixed::from_f64(v) }
+
+fn assert_close(actual: Option<Fixed>, expected: f64, tol: f64, what: &str) {
+    let actual = actual.unwrap_or_else(|| panic!("{what}: got None")).to_f64();
+    let err = (actual - expected).abs();
+    assert!(err <= tol, "{what}: got {actual} expected {expected} (err {err:e} > {tol:e})");
+}
+
+// mpmath reference values of the inverse normal CDF
+const PROBIT_REFERENCE: &[(f64, f64)] = &[
+    (1e-15, -7.941_345_326_170_997),
+    (1e-6, -4.753_424_308_822_899),
+    (0.025, -1.959_963_984_540_054),
+    (0.05, -1.644_853_626_951_472_7),
+    (0.3, -0.524_400_512_708_041_2),
+    (0.5, 0.0),
+    (0.9, 1.281_551_565_544_600_4),
+    (0.999, 3.090_232_306_167_813_5),
+];
+
+#[test]
+fn probit_matches_reference() {
+    for &(p, z) in PROBIT_REFERENCE {
+        assert!((probit(p) - z).abs() <= z.abs() * 1e-14 + 1e-15, "probit({p}) = {} expected {z}", probit(p));
+        // from_f64 moves p by up to 2^-64, i.e. z by about 2^-64 / (p phi(z)) ~ 2^-64 / (p |z|) in the tail
+        let quant = 1.0 / (p * 2f64.powi(64) * z.abs().max(1.0));
+        assert_close(probit_fixed(fx(p)), z, z.abs() * 1e-14 + 1e-15 + quant, "probit_fixed");
+    }
+    // symmetric about one half
+    assert_eq!(probit_fixed(fx(0.2)).unwrap().checked_neg(), probit_fixed(Fixed::ONE.checked_sub(fx(0.2)).unwrap()));
+    assert_eq!(probit_fixed(Fixed::ZERO), None);
+    assert_eq!(probit_fixed(Fixed::ONE), None);
+    assert!(probit_fixed(Fixed::EPSILON).unwrap() < Fixed::from_int(-9));
+}
+
+#[test]
+fn quantiles_invert_cdfs() {
+    let families = [
+        (Distribution::from_params(DIST_NORMAL, fx(100.0), fx(20.0), Fixed::ZERO, fx(0.0), fx(200.0)).unwrap(), 2e-7),
+        (Distribution::from_params(DIST_LOGNORMAL, fx(10.8), fx(0.3), Fixed::ZERO, fx(0.0), fx(1e6)).unwrap(), 2e-7),
+        (Distribution::from_params(DIST_UNIFORM, fx(10.0), fx(1.0), Fixed::ZERO, fx(0.0), fx(20.0)).unwrap(), 1e-15),
+        (Distribution::from_params(DIST_STUDENT_T, fx(0.0), fx(1.0), fx(4.0), fx(-50.0), fx(50.0)).unwrap(), 1e-12),
+        (Distribution::from_params(DIST_BETA, fx(30.0), fx(10.0), Fixed::ZERO, fx(0.0), fx(100.0)).unwrap(), 1e-12),
+    ];
+    for (d, tol) in families {
+        for &p in &[0.05, 0.25, 0.5, 0.75, 0.95] {
+            assert_close(d.cdf(d.quantile(fx(p)).unwrap()), p, tol, "cdf(quantile(p))");
+        }
+        assert_eq!(d.quantile(Fixed::ONE), None);
+    }
+}
+
+#[test]
+fn mixture_quantiles_invert_cdf() {
+    let mix = [
+        GaussComponent { weight: fx(0.6), mu: fx(4.0), sigma: fx(0.5) },
+        GaussComponent { weight: fx(0.4), mu: fx(10.0), sigma: fx(1.0) },
+    ];
+    for &p in &[0.05, 0.5, 0.6, 0.95] {
+        let x = mixture_quantile_fixed(&mix, fx(p)).unwrap();
+        assert_close(mixture_cdf_fixed(&mix, x), p, 1e-12, "mixture cdf(quantile(p))");
+    }
+    // the median of this mixture sits in the first mode
+    assert!(mixture_quantile_fixed(&mix, Fixed::HALF).unwrap() < fx(5.0));
+    assert_eq!(mixture_quantile_fixed(&[], Fixed::HALF), None);
+}
+
+#[test]
+fn histogram_cdf_and_quantile() {
+    const SCALE: u32 = 1_000_000_000;
+    let mass = [100_000_000, 400_000_000, 0, 500_000_000];
+    let (lo, width) = (fx(0.0), fx(10.0));
+    let cdf = |x: f64| histogram_cdf_fixed(&mass, SCALE, lo, width, fx(x));
+    assert_close(cdf(-5.0), 0.0, 0.0, "below range");
+    assert_close(cdf(5.0), 0.05, 1e-15, "inside first bin");
+    assert_close(cdf(20.0), 0.5, 1e-15, "bin edge");
+    assert_close(cdf(25.0), 0.5, 1e-15, "empty bin");
+    assert_close(cdf(35.0), 0.75, 1e-15, "last bin");
+    assert_close(cdf(45.0), 1.0, 0.0, "above range");
+
+    let quantile = |p: f64| histogram_quantile_fixed(&mass, SCALE, lo, width, fx(p));
+    assert_close(quantile(0.05), 5.0, 1e-12, "q05");
+    assert_close(quantile(0.3), 15.0, 1e-12, "q30");
+    // the empty bin is skipped rather than stretched over
+    assert_close(quantile(0.75), 35.0, 1e-12, "q75");
+    assert_eq!(histogram_quantile_fixed(&[1, 1], 4, lo, width, fx(0.9)), None);
+}
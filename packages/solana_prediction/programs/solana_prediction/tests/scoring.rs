@@ -0,0 +1,126 @@
+use solana_prediction::fixed::Fixed;
+use solana_prediction::math::{Distribution, DIST_BETA, DIST_LOGNORMAL, DIST_NORMAL, DIST_STUDENT_T, DIST_UNIFORM};
+use solana_prediction::scoring::*;
+
+fn fx(v: f64) -> Fixed { Fixed::from_f64(v) }
+
+fn assert_close(actual: Option<Fixed>, expected: f64, tol: f64, what: &str) {
+    let actual = actual.unwrap_or_else(|| panic!("{what}: got None")).to_f64();
+    let err = (actual - expected).abs();
+    assert!(err <= tol, "{what}: got {actual} expected {expected} (err {err:e} > {tol:e})");
+}
+
+fn dist(dist_type: u8, mu: f64, sigma: f64, shape: f64, lo: f64, hi: f64) -> Distribution {
+    Distribution::from_params(dist_type, fx(mu), fx(sigma), fx(shape), fx(lo), fx(hi)).unwrap()
+}
+
+/// Midpoint-rule CRPS over [lo, hi] from the fixed-point CDF, as an independent reference.
+/// Split at y so the indicator's jump falls on a cell boundary.
+fn crps_reference(d: &Distribution, y: f64, lo: f64, hi: f64) -> f64 {
+    let midpoint = |a: f64, b: f64, g: &dyn Fn(f64) -> f64| {
+        let n = 5_000;
+        let h = (b - a) / n as f64;
+        (0..n).map(|i| g(a + (i as f64 + 0.5) * h) * h).sum::<f64>()
+    };
+    let cdf = |x: f64| d.cdf(fx(x)).unwrap().to_f64();
+    midpoint(lo, y, &|x| cdf(x).powi(2)) + midpoint(y, hi, &|x| (1.0 - cdf(x)).powi(2))
+}
+
+const RULES: [u8; 4] = [SCORING_SPHERICAL, SCORING_LOG, SCORING_QUADRATIC, SCORING_CRPS];
+
+#[test]
+fn crps_matches_quadrature() {
+    let normal = dist(DIST_NORMAL, 0.0, 1.0, 0.0, -12.0, 12.0);
+    let lognormal = dist(DIST_LOGNORMAL, 0.5, 0.4, 0.0, 0.0, 60.0);
+    let uniform = dist(DIST_UNIFORM, 10.0, 3f64.sqrt(), 0.0, 0.0, 20.0);
+    let student = dist(DIST_STUDENT_T, 0.0, 1.0, 5.0, -12.0, 12.0);
+    let beta = dist(DIST_BETA, 0.3, 0.15, 0.0, 0.0, 1.0);
+    for &(d, lo, hi, ys) in &[
+        (&normal, -12.0, 12.0, &[-2.0, 0.0, 0.7, 3.0][..]),
+        (&lognormal, 0.0, 60.0, &[0.5, 1.6, 4.0][..]),
+        (&uniform, 0.0, 20.0, &[5.0, 8.0, 13.0, 16.0][..]),
+        (&student, -12.0, 12.0, &[-1.0, 0.0, 2.5][..]),
+        (&beta, 0.0, 1.0, &[0.1, 0.3, 0.8][..]),
+    ] {
+        for &y in ys {
+            let reference = crps_reference(d, y, lo, hi);
+            assert_close(crps(d, fx(y), fx(lo), fx(hi)), reference, 1e-4 * (hi - lo) / 24.0 + 1e-6, "crps");
+        }
+    }
+    // closed form against the textbook value at the mean: sigma (sqrt 2 - 1) / sqrt(pi)
+    let expected = (2f64.sqrt() - 1.0) / std::f64::consts::PI.sqrt();
+    assert_close(crps(&normal, Fixed::ZERO, fx(-12.0), fx(12.0)), expected, 1e-13, "normal crps at mean");
+}
+
+#[test]
+fn log_score_survives_deep_tails() {
+    let normal = dist(DIST_NORMAL, 0.0, 2.0, 0.0, -1e3, 1e3);
+    for &y in &[0.0, 1.0, 40.0, -150.0] {
+        let expected = -0.5 * (y / 2.0) * (y / 2.0) - 2f64.ln() - 0.5 * (2.0 * std::f64::consts::PI).ln();
+        assert_close(log_score(&normal, fx(y)), expected, expected.abs() * 1e-14 + 1e-15, "normal log score");
+    }
+    let uniform = dist(DIST_UNIFORM, 10.0, 1.0, 0.0, 0.0, 20.0);
+    assert_eq!(log_score(&uniform, fx(2.0)), None);
+    for (d, y) in [
+        (dist(DIST_LOGNORMAL, 0.5, 0.4, 0.0, 0.0, 60.0), 2.0),
+        (dist(DIST_STUDENT_T, 1.0, 2.0, 4.0, -50.0, 50.0), -3.0),
+        (dist(DIST_BETA, 0.3, 0.15, 0.0, 0.0, 1.0), 0.42),
+    ] {
+        assert_close(log_score(&d, fx(y)), d.pdf(fx(y)).unwrap().to_f64().ln(), 1e-12, "log score");
+    }
+}
+
+#[test]
+fn truthful_report_maximises_expected_score() {
+    // E_P[S(P, Y)] >= E_P[S(Q, Y)] for every proper rule
+    let (lo, hi) = (-10.0, 10.0);
+    let truth = dist(DIST_NORMAL, 0.5, 1.2, 0.0, lo, hi);
+    let reports = [
+        dist(DIST_NORMAL, 0.0, 1.2, 0.0, lo, hi),
+        dist(DIST_NORMAL, 0.5, 2.0, 0.0, lo, hi),
+        dist(DIST_NORMAL, 0.5, 0.8, 0.0, lo, hi),
+        dist(DIST_STUDENT_T, 0.5, 1.2, 3.0, lo, hi),
+    ];
+    let expected_score = |rule: u8, report: &Distribution| -> f64 {
+        let n = 200;
+        let h = (hi - lo) / n as f64;
+        (0..n)
+            .map(|i| {
+                let y = lo + (i as f64 + 0.5) * h;
+                truth.pdf(fx(y)).unwrap().to_f64() * score(rule, report, fx(y), fx(lo), fx(hi)).unwrap().to_f64() * h
+            })
+            .sum()
+    };
+    for rule in RULES {
+        let honest = expected_score(rule, &truth);
+        for report in &reports {
+            assert!(expected_score(rule, report) < honest, "rule {rule} rewards a dishonest report");
+        }
+    }
+    assert_eq!(score(9, &truth, Fixed::ZERO, fx(lo), fx(hi)), None);
+}
+
+#[test]
+fn collateral_covers_every_outcome() {
+    let (lo, hi) = (0.0, 100_000.0);
+    let k = 1_000.0;
+    let from = dist(DIST_NORMAL, 50_000.0, 10_000.0, 0.0, lo, hi);
+    for to in [dist(DIST_NORMAL, 52_000.0, 8_000.0, 0.0, lo, hi), dist(DIST_NORMAL, 30_000.0, 2_000.0, 0.0, lo, hi)] {
+        for rule in RULES {
+            let collateral = collateral_required(rule, k as u64, &from, &to, fx(lo), fx(hi)).unwrap() as f64;
+            let mut dense: f64 = 0.0;
+            for i in 0..=4_000 {
+                let y = lo + (hi - lo) * i as f64 / 4_000.0;
+                let delta = payout_delta(rule, fx(k), &from, &to, fx(y), fx(lo), fx(hi)).unwrap().to_f64();
+                dense = dense.max(-delta);
+            }
+            assert!(collateral >= dense * (1.0 - 1e-6), "rule {rule}: collateral {collateral} < worst loss {dense}");
+            assert!(collateral <= dense * 1.01 + 1.0, "rule {rule}: collateral {collateral} far above worst loss {dense}");
+        }
+        // the spherical rule is the L2 payoff markets already use
+        assert_eq!(
+            collateral_required(SCORING_SPHERICAL, k as u64, &from, &to, fx(lo), fx(hi)),
+            solana_prediction::math::collateral_required(k as u64, &from, &to, fx(lo), fx(hi))
+        );
+    }
+}
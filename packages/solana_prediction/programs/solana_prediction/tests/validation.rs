@@ -0,0 +1,119 @@
+use anchor_lang::prelude::Pubkey;
+use solana_prediction::fixed::Fixed;
+use solana_prediction::math::Distribution;
+use solana_prediction::scoring::{payout_delta, SCORING_CRPS, SCORING_LOG};
+use solana_prediction::validation::{market_open, price, size, MAX_PRICE_BPS};
+use solana_prediction::{Market, MarketStatus, MarketType, Side, MARKET_RESERVED, MAX_DECIMALS};
+
+fn market() -> Market {
+    Market {
+        version: 1,
+        authority: Pubkey::new_unique(),
+        slug: [0; 32],
+        market_type: MarketType::Distributional,
+        decimals: 2,
+        outcome_min: 0,
+        outcome_max: 100_000,
+        unit: [0; 12],
+        dist_type: 0,
+        mu: 50_000,
+        sigma: 10_000,
+        sigma_min: 1_000,
+        step: 100,
+        resolution_time: 1_000_000,
+        oracle_config: [0; 64],
+        fee_bps_platform: 25,
+        fee_bps_creator: 25,
+        liquidity_pool: Pubkey::new_unique(),
+        order_book: Pubkey::new_unique(),
+        collateral_vault: Pubkey::new_unique(),
+        status: MarketStatus::Active,
+        bump: 255,
+        l2_norm: 1_000_000,
+        dist_shape: 0,
+        resolved_outcome: 0,
+        scoring_rule: 0,
+        strike: 50_000,
+        fee_bps_lp: 0,
+        distribution_balance: 0,
+        reserved: [0; MARKET_RESERVED],
+    }
+}
+
+#[test]
+fn prices_are_bounded_probabilities() {
+    assert!(price(0).is_err());
+    assert!(price(1).is_ok());
+    assert!(price(MAX_PRICE_BPS).is_ok());
+    assert!(price(MAX_PRICE_BPS + 1).is_err());
+    assert!(size(0).is_err());
+    assert!(size(1).is_ok());
+}
+
+#[test]
+fn stored_bytes_decode_to_enums() {
+    assert_eq!(Side::try_from(0).unwrap(), Side::Buy);
+    assert_eq!(Side::try_from(Side::Sell as u8).unwrap(), Side::Sell);
+    assert!(Side::try_from(2).is_err());
+    assert_eq!(Side::Buy.opposite(), Side::Sell);
+    assert_eq!(MarketType::try_from(2).unwrap(), MarketType::Histogram);
+    assert!(MarketType::try_from(3).is_err());
+    assert_eq!(MarketStatus::try_from(2).unwrap(), MarketStatus::Resolved);
+    assert!(MarketStatus::try_from(7).is_err());
+}
+
+#[test]
+fn market_parameters_are_checked_the_same_when_created_or_migrated() {
+    assert!(market().validate().is_ok());
+    let bad = [
+        Market { decimals: MAX_DECIMALS + 1, ..market() },
+        Market { outcome_max: 0, ..market() },
+        Market { step: 0, ..market() },
+        Market { sigma: 999, ..market() },
+        Market { strike: 100_001, ..market() },
+        Market { fee_bps_lp: 9_951, ..market() },
+        Market { dist_type: 9, ..market() },
+        // mixtures and histograms pay the spherical rule only
+        Market { market_type: MarketType::Mixture, scoring_rule: SCORING_CRPS, ..market() },
+        // a histogram's step must split the range into whole bins
+        Market { market_type: MarketType::Histogram, step: 300, ..market() },
+    ];
+    for (i, m) in bad.iter().enumerate() {
+        assert!(m.validate().is_err(), "case {i}");
+    }
+}
+
+#[test]
+fn subsidy_covers_every_belief_the_market_can_reach() {
+    let m = market();
+    let subsidy = m.subsidy().unwrap();
+    assert!(subsidy > 0);
+    // whoever moves the opening belief to the sharpest one at y wins k * (S(g, y) - S(f0, y))
+    let k = Fixed::from_int(m.l2_norm as i64);
+    let (lo, hi) = (m.fixed(m.outcome_min).unwrap(), m.fixed(m.outcome_max).unwrap());
+    let first = m.distribution(m.mu, m.sigma).unwrap();
+    for y in (0..=100_000).step_by(2_500) {
+        let y = m.fixed(y).unwrap();
+        let sharpest = Distribution::Normal { mu: y, sigma: m.fixed(m.sigma_min).unwrap() };
+        let won = payout_delta(m.scoring_rule, k, &first, &sharpest, y, lo, hi).unwrap();
+        assert!(won.ceil_to_i64().unwrap() as u64 <= subsidy, "outcome {y:?}");
+    }
+    // the log rule punishes the opening belief's tails hardest
+    assert!(Market { scoring_rule: SCORING_LOG, ..market() }.subsidy().unwrap() > subsidy);
+
+    // all mass in one of 100 bins of width 10: k / sqrt(10) - k / sqrt(1000)
+    let hist = Market { market_type: MarketType::Histogram, step: 1_000, ..market() };
+    assert!(hist.subsidy().unwrap().abs_diff(316_228 - 31_623) <= 1);
+    assert!(Market { market_type: MarketType::Mixture, ..market() }.subsidy().unwrap() > 0);
+    // markets without an L2 norm never trade their distribution
+    assert_eq!(Market { l2_norm: 0, ..market() }.subsidy().unwrap(), 0);
+}
+
+#[test]
+fn only_active_markets_before_their_resolution_time_trade() {
+    let m = market();
+    assert!(market_open(&m, m.resolution_time - 1).is_ok());
+    assert!(market_open(&m, m.resolution_time).is_err());
+    assert!(market_open(&Market { status: MarketStatus::Closed, ..market() }, 0).is_err());
+    assert!(market_open(&Market { status: MarketStatus::Resolved, ..market() }, 0).is_err());
+}
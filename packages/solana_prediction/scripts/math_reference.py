@@ -0,0 +1,88 @@
+#!/usr/bin/env python3
+"""Reference tables for programs/solana_prediction/tests/accuracy.rs.
+
+Every input is dyadic, so it is exact both as an f64 and in Q64.64, and the only error the
+harness measures is that of the functions under test. Values are evaluated with mpmath at
+50 significant digits and printed as the nearest f64.
+
+    pip install mpmath
+    python3 scripts/math_reference.py > /tmp/tables.rs
+"""
+
+from mpmath import mp, mpf, sqrt, pi, exp, erfc, loggamma, betainc
+
+mp.dps = 50
+
+
+def phi(z):
+    return exp(-z * z / 2) / sqrt(2 * pi)
+
+
+def cdf(z):
+    return erfc(-z / sqrt(2)) / 2
+
+
+def lit(v):
+    return repr(float(v))
+
+
+def table(name, ty, rows):
+    print(f"const {name}: &[{ty}] = &[")
+    for row in rows:
+        print("    (" + ", ".join(row) + "),")
+    print("];\n")
+
+
+# Standard normal CDF at z = k / 4, |z| <= 10
+table("PHI_CDF", "(f64, f64)", [(lit(mpf(k) / 4), lit(cdf(mpf(k) / 4))) for k in range(-40, 41)])
+
+# Standard normal pdf at z = k / 4, 0 <= z <= 10; the harness rescales it for every sigma
+table("STD_PDF", "(f64, f64)", [(lit(mpf(k) / 4), lit(phi(mpf(k) / 4))) for k in range(0, 41)])
+
+print(f"/// (2 sqrt(pi))^(-1/2), i.e. ||N(0, 1)||_2.\nconst UNIT_L2_NORM: f64 = {lit(1 / sqrt(2 * sqrt(pi)))};\n")
+
+# Pairs N(0, 2^ea) and N(d * 2^max(ea, eb), 2^eb): inner product, L2 distance, overlap
+rows = []
+for ea in (-36, -28, -20, -12, 0, 12, 24):
+    for step in (0, 1, 6):
+        eb = ea + step
+        if eb > 28:
+            continue
+        for d in (0, mpf(1) / 2, 3):
+            sa, sb = mpf(2) ** ea, mpf(2) ** eb
+            s = sqrt(sa * sa + sb * sb)
+            delta = d * mpf(2) ** max(ea, eb)
+            inner = phi(delta / s) / s
+            na2, nb2 = 1 / (2 * sa * sqrt(pi)), 1 / (2 * sb * sqrt(pi))
+            dist = sqrt(max(na2 + nb2 - 2 * inner, 0))
+            overlap = inner / sqrt(na2 * nb2)
+            rows.append((str(ea), str(eb), lit(d), lit(inner), lit(dist), lit(overlap)))
+table("GAUSS_PAIRS", "(i32, i32, f64, f64, f64, f64)", rows)
+
+# Grid sums of (p - q)^2 and |p - q| for p = N(0, s^2), q = N(m, s^2) over [lo, hi] in steps of h,
+# plus the exact integral of (p - q)^2 for the discretization error
+rows = []
+for (s, m, lo, hi, h) in ((1, 1, -8, 8, mpf(1) / 128), (1, 1, -8, 8, mpf(1) / 16), (1, 1, -8, 8, 1),
+                         (1, 1, -8, 8, 2), (mpf(2) ** -10, mpf(2) ** -11, -mpf(2) ** -7, mpf(2) ** -7, mpf(2) ** -14),
+                         (mpf(2) ** 10, mpf(2) ** 9, -mpf(2) ** 13, mpf(2) ** 13, 8)):
+    p = lambda x: phi(x / s) / s
+    q = lambda x: phi((x - m) / s) / s
+    n = int((hi - lo) / h)
+    xs = [lo + i * h for i in range(n + 1)]
+    l2 = sum((p(x) - q(x)) ** 2 for x in xs) * h
+    l1 = sum(abs(p(x) - q(x)) for x in xs) * h
+    exact = (1 - exp(-(m / s) ** 2 / 4)) / (s * sqrt(pi))
+    rows.append(tuple(lit(v) for v in (s, m, lo, hi, h, l2, l1, exact)))
+table("GRID_SUMS", "(f64, f64, f64, f64, f64, f64, f64, f64)", rows)
+
+# ln Gamma over the parameter range families use
+table("LN_GAMMA", "(f64, f64)", [(lit(x), lit(loggamma(x))) for x in
+      (mpf(1) / 64, mpf(1) / 4, mpf(1) / 2, 1, mpf(3) / 2, 2, 3, mpf(15) / 2, 10, 33, 100, 1000, 2 ** 20)])
+
+# Regularized incomplete beta I_x(a, b)
+rows = []
+for a in (mpf(1) / 2, 1, mpf(5) / 2, 10, 50):
+    for b in (mpf(1) / 2, 1, mpf(5) / 2, 10, 50):
+        for x in (mpf(1) / 128, mpf(1) / 4, mpf(1) / 2, mpf(29) / 32, mpf(1023) / 1024):
+            rows.append((lit(x), lit(a), lit(b), lit(betainc(a, b, 0, x, regularized=True))))
+table("REG_INC_BETA", "(f64, f64, f64, f64)", rows)
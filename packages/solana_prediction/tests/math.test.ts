@@ -1,6 +1,20 @@
 import * as anchor from "@coral-xyz/anchor";
 import { BN, Program } from "@coral-xyz/anchor";
 import { PublicKey, SystemProgram } from "@solana/web3.js";
+import {
+  ExtensionType,
+  TOKEN_2022_PROGRAM_ID,
+  TOKEN_PROGRAM_ID,
+  createInitializeMintInstruction,
+  createInitializeTransferFeeConfigInstruction,
+  createMint,
+  getAccount,
+  getAssociatedTokenAddressSync,
+  getMintLen,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+  transfer,
+} from "@solana/spl-token";
 import { SolanaPrediction } from "../target/types/solana_prediction";
 import { assert } from "chai";
 
@@ -16,11 +30,65 @@ describe("solana_prediction program e2e", () => {
   const program = anchor.workspace.SolanaPrediction as Program<SolanaPrediction>;
   const wallet = provider.wallet as anchor.Wallet;
 
-  it("init_registry", async () => {
-    const [registry] = PublicKey.findProgramAddressSync(
-      [Buffer.from("registry"), wallet.publicKey.toBuffer()],
-      program.programId
+  // every market here is collateralised in the same 6-decimal USDC mock, minted by the wallet
+  let collateralMint: PublicKey;
+  const tokensOf = (owner: PublicKey, mint = collateralMint, tokenProgram = TOKEN_PROGRAM_ID) =>
+    getAssociatedTokenAddressSync(mint, owner, false, tokenProgram);
+  const fund = async (owner: PublicKey, amount: number, mint = collateralMint, tokenProgram = TOKEN_PROGRAM_ID) => {
+    const ata = await getOrCreateAssociatedTokenAccount(
+      provider.connection, wallet.payer, mint, owner, false, undefined, undefined, tokenProgram
     );
+    await mintTo(provider.connection, wallet.payer, mint, ata.address, wallet.payer, amount, [], undefined, tokenProgram);
+    return ata.address;
+  };
+  const custody = (market: PublicKey, mint = collateralMint, tokenProgram = TOKEN_PROGRAM_ID) => ({
+    collateralVault: PublicKey.findProgramAddressSync([Buffer.from("collateral"), market.toBuffer()], program.programId)[0],
+    vaultTokens: PublicKey.findProgramAddressSync([Buffer.from("vault_tokens"), market.toBuffer()], program.programId)[0],
+    mint,
+    tokenProgram,
+  });
+  const feesOf = (market: PublicKey) =>
+    PublicKey.findProgramAddressSync([Buffer.from("fees"), market.toBuffer()], program.programId)[0];
+  // the platform's registry, a single PDA whose authority is the wallet here
+  const [registry] = PublicKey.findProgramAddressSync([Buffer.from("registry")], program.programId);
+  const initInfrastructure = async (market: PublicKey, mint = collateralMint, tokenProgram = TOKEN_PROGRAM_ID, platform = registry) => {
+    const pda = (seed: string) => PublicKey.findProgramAddressSync([Buffer.from(seed), market.toBuffer()], program.programId)[0];
+    await program.methods
+      .initializeMarketInfrastructure()
+      .accounts({
+        market,
+        liquidityPool: pda("pool"),
+        orderBook: pda("orderbook"),
+        collateralVault: pda("collateral"),
+        collateralMint: mint,
+        vaultTokens: pda("vault_tokens"),
+        lpMint: pda("lp_mint"),
+        marketFees: pda("fees"),
+        registry: platform,
+        authorityTokens: tokensOf(wallet.publicKey, mint, tokenProgram),
+        authority: wallet.publicKey,
+        tokenProgram,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+    // the opening subsidy the authority just posted for distribution payouts
+    return (await program.account.market.fetch(market)).distributionBalance.toNumber();
+  };
+  const vaultBalance = async (market: PublicKey) =>
+    Number((await provider.connection.getTokenAccountBalance(custody(market).vaultTokens)).value.amount);
+  const chainTime = async () => provider.connection.getBlockTime(await provider.connection.getSlot());
+  const waitUntil = async (ts: number) => {
+    while ((await chainTime()) < ts) await new Promise((r) => setTimeout(r, 500));
+  };
+  // `min_amount_out` and `max_amount_in` for pm-AMM trades without slippage protection
+  const [noMinOut, noMaxIn] = [new BN(0), new BN("18446744073709551615")];
+
+  before(async () => {
+    collateralMint = await createMint(provider.connection, wallet.payer, wallet.publicKey, null, 6);
+    await fund(wallet.publicKey, 1_000_000_000_000);
+  });
+
+  it("init_registry", async () => {
     await program.methods
       .initRegistry()
       .accounts({ registry, authority: wallet.publicKey, systemProgram: SystemProgram.programId })
@@ -30,10 +98,6 @@ describe("solana_prediction program e2e", () => {
   });
 
   it("upsert_strategy", async () => {
-    const [registry] = PublicKey.findProgramAddressSync(
-      [Buffer.from("registry"), wallet.publicKey.toBuffer()],
-      program.programId
-    );
     const stratKey = Buffer.alloc(32); stratKey.fill(3);
     const [strategy] = PublicKey.findProgramAddressSync(
       [Buffer.from("strategy"), registry.toBuffer(), stratKey],
@@ -55,18 +119,25 @@ describe("solana_prediction program e2e", () => {
     const oracle = padBytes("manual", 64);
     const params = {
       slug: [...slug] as any,
-      outcomeMin: 10000.0,
-      outcomeMax: 200000.0,
+      outcomeMin: new BN(10_000_00),
+      outcomeMax: new BN(200_000_00),
       unit: [...unit] as any,
+      decimals: 2,
+      marketType: { distributional: {} },
       distType: 0,
-      mu: 50000.0,
-      sigma: 10000.0,
-      sigmaMin: 1000.0,
-      step: 1000.0,
+      mu: new BN(50_000_00),
+      sigma: new BN(10_000_00),
+      sigmaMin: new BN(1_000_00),
+      step: new BN(1_000_00),
       resolutionTime: new BN(Math.floor(Date.now() / 1000) + 7 * 24 * 3600),
       oracleConfig: [...oracle] as any,
       feeBpsPlatform: 25,
       feeBpsCreator: 25,
+      l2Norm: new BN(1_000_000),
+      distShape: new BN(0),
+      scoringRule: 0,
+      strike: new BN(50_000_00),
+      feeBpsLp: 10,
     };
     const [market] = PublicKey.findProgramAddressSync(
       [Buffer.from("market"), wallet.publicKey.toBuffer(), Buffer.from(slug)],
@@ -78,17 +149,111 @@ describe("solana_prediction program e2e", () => {
       .rpc();
     const acc = await program.account.market.fetch(market);
     assert.strictEqual(acc.authority.toBase58(), wallet.publicKey.toBase58());
+    assert.strictEqual(acc.version, 1);
+    assert.strictEqual(acc.mu.toNumber(), 50_000_00);
 
-    const [pool] = PublicKey.findProgramAddressSync([Buffer.from("pool"), market.toBuffer()], program.programId);
     const [orderbook] = PublicKey.findProgramAddressSync([Buffer.from("orderbook"), market.toBuffer()], program.programId);
-    const [collateral] = PublicKey.findProgramAddressSync([Buffer.from("collateral"), market.toBuffer()], program.programId);
+    // a creator can't set up a registry of their own, nor route the platform fees to any
+    // account other than the platform's
+    const stranger = anchor.web3.Keypair.generate();
+    await provider.connection.confirmTransaction(await provider.connection.requestAirdrop(stranger.publicKey, 1_000_000_000));
+    let refused = false;
+    try {
+      await program.methods
+        .initRegistry()
+        .accounts({ registry, authority: stranger.publicKey, systemProgram: SystemProgram.programId })
+        .signers([stranger])
+        .rpc();
+    } catch {
+      refused = true;
+    }
+    assert.isTrue(refused);
+    const [strategy] = PublicKey.findProgramAddressSync(
+      [Buffer.from("strategy"), registry.toBuffer(), Buffer.alloc(32, 3)],
+      program.programId
+    );
+    refused = false;
+    try {
+      await initInfrastructure(market, collateralMint, TOKEN_PROGRAM_ID, strategy);
+    } catch {
+      refused = true;
+    }
+    assert.isTrue(refused);
+
+    const subsidy = await initInfrastructure(market);
+    const vault = await program.account.collateralVault.fetch(custody(market).collateralVault);
+    assert.strictEqual(vault.mint.toBase58(), collateralMint.toBase58());
+    assert.isAbove(subsidy, 0);
+    assert.equal(vault.balance.toNumber(), subsidy);
+    assert.strictEqual(vault.tokenAccount.toBase58(), custody(market).vaultTokens.toBase58());
+    const ob = await program.account.orderBook.fetch(orderbook);
+    assert.equal(ob.bestBidBps.toNumber(), 0);
+    assert.equal(ob.bestAskBps.toNumber(), 0);
+
+    const [bids] = PublicKey.findProgramAddressSync([Buffer.from("bids"), market.toBuffer()], program.programId);
+    const [asks] = PublicKey.findProgramAddressSync([Buffer.from("asks"), market.toBuffer()], program.programId);
     await program.methods
-      .initializeMarketInfrastructure()
-      .accounts({ market, liquidityPool: pool, orderBook: orderbook, collateralVault: collateral, authority: wallet.publicKey, systemProgram: SystemProgram.programId })
+      .initOrderBook()
+      .accounts({ market, bids, asks, authority: wallet.publicKey, systemProgram: SystemProgram.programId })
       .rpc();
-    const ob = await program.account.orderBook.fetch(orderbook);
-    assert.equal(ob.bestBid, 0.0);
-    assert.equal(ob.bestAsk, 0.0);
+    const bidSide = await program.account.bookSide.fetch(bids);
+    assert.equal(bidSide.levelCount, 0);
+    assert.equal(bidSide.freeHead, 0);
+  });
+
+  it("add/remove_liquidity mint LP shares on a provider position that starts a cooldown", async () => {
+    const slug = padBytes("BTC_DEC31_2025", 32);
+    const [market] = PublicKey.findProgramAddressSync(
+      [Buffer.from("market"), wallet.publicKey.toBuffer(), Buffer.from(slug)],
+      program.programId
+    );
+    const [liquidityPool] = PublicKey.findProgramAddressSync([Buffer.from("pool"), market.toBuffer()], program.programId);
+    const [lpMint] = PublicKey.findProgramAddressSync([Buffer.from("lp_mint"), market.toBuffer()], program.programId);
+    const [lpPosition] = PublicKey.findProgramAddressSync(
+      [Buffer.from("lp"), market.toBuffer(), wallet.publicKey.toBuffer()],
+      program.programId
+    );
+    const providerShares = (await getOrCreateAssociatedTokenAccount(provider.connection, wallet.payer, lpMint, wallet.publicKey)).address;
+    const accounts = {
+      market, lpPosition, liquidityPool, lpMint, ...custody(market), providerTokens: tokensOf(wallet.publicKey), providerShares,
+      provider: wallet.publicKey, systemProgram: SystemProgram.programId,
+    };
+    const shares = async () => Number((await provider.connection.getTokenAccountBalance(providerShares)).value.amount);
+
+    await program.methods.addLiquidity(new BN(10_000)).accounts(accounts).rpc();
+    assert.equal(await shares(), 10_000);
+    // next to the opening subsidy, which liquidity never draws on
+    const { distributionBalance } = await program.account.market.fetch(market);
+    assert.equal(await vaultBalance(market), distributionBalance.toNumber() + 10_000);
+    const pos = await program.account.lpPosition.fetch(lpPosition);
+    assert.equal(pos.deposited.toNumber(), 10_000);
+    assert.equal(pos.shares.toNumber(), 10_000);
+    const lp = await program.account.liquidityPool.fetch(liquidityPool);
+    assert.equal(lp.totalLiquidity.toNumber(), 10_000);
+
+    // the deposit just started the cooldown, so nothing can come out yet
+    let locked = false;
+    try {
+      await program.methods.removeLiquidity(new BN(4_000)).accounts(accounts).rpc();
+    } catch (e) {
+      locked = String(e).includes("LiquidityLocked");
+    }
+    assert.isTrue(locked);
+    assert.equal(await shares(), 10_000);
+
+    // nor can the fresh shares move to a wallet whose cooldown already ran: the pool keeps
+    // share accounts frozen
+    assert.isTrue((await getAccount(provider.connection, providerShares)).isFrozen);
+    const receiver = anchor.web3.Keypair.generate();
+    const receiverShares = (await getOrCreateAssociatedTokenAccount(provider.connection, wallet.payer, lpMint, receiver.publicKey)).address;
+    let moved = true;
+    try {
+      await transfer(provider.connection, wallet.payer, providerShares, receiverShares, wallet.payer, 4_000);
+    } catch {
+      moved = false;
+    }
+    assert.isFalse(moved);
+    assert.equal(await shares(), 10_000);
   });
 
   it("place_limit_order updates best bid/ask", async () => {
@@ -98,32 +263,609 @@ describe("solana_prediction program e2e", () => {
       program.programId
     );
     const [orderbook] = PublicKey.findProgramAddressSync([Buffer.from("orderbook"), market.toBuffer()], program.programId);
+    const [bids] = PublicKey.findProgramAddressSync([Buffer.from("bids"), market.toBuffer()], program.programId);
+    const [asks] = PublicKey.findProgramAddressSync([Buffer.from("asks"), market.toBuffer()], program.programId);
+    const [openOrders] = PublicKey.findProgramAddressSync(
+      [Buffer.from("open_orders"), market.toBuffer(), wallet.publicKey.toBuffer()],
+      program.programId
+    );
+    const book = {
+      market, orderBook: orderbook, bids, asks, openOrders, ...custody(market), ownerTokens: tokensOf(wallet.publicKey),
+      marketFees: feesOf(market), owner: wallet.publicKey, systemProgram: SystemProgram.programId,
+    };
+    await program.methods.placeLimitOrder({ buy: {} }, new BN(5050), new BN(1), new BN(0), new BN(0), { gtc: {} }).accounts(book).rpc();
+    let ob = await program.account.orderBook.fetch(orderbook);
+    assert.equal(ob.bestBidBps.toNumber(), 5050);
+    await program.methods.placeLimitOrder({ sell: {} }, new BN(5150), new BN(1), new BN(0), new BN(0), { gtc: {} }).accounts(book).rpc();
+    ob = await program.account.orderBook.fetch(orderbook);
+    assert.equal(ob.bestAskBps.toNumber(), 5150);
+
+    // a second bid at the same price queues behind the first; a lower one opens a new level
+    await program.methods.placeLimitOrder({ buy: {} }, new BN(5050), new BN(3), new BN(0), new BN(0), { gtc: {} }).accounts(book).rpc();
+    await program.methods.placeLimitOrder({ buy: {} }, new BN(5000), new BN(2), new BN(0), new BN(0), { gtc: {} }).accounts(book).rpc();
+    const bidSide = await program.account.bookSide.fetch(bids);
+    assert.equal(bidSide.levelCount, 2);
+    const best = bidSide.levels[0];
+    assert.equal(best.priceBps.toNumber(), 5050);
+    assert.equal(best.count, 2);
+    assert.equal(best.totalSize.toNumber(), 4);
+    const head = bidSide.orders[best.head];
+    assert.equal(head.orderId.toNumber(), 0);
+    assert.equal(bidSide.orders[head.next].orderId.toNumber(), 2);
+    assert.equal(bidSide.levels[1].priceBps.toNumber(), 5000);
+
+    // a post-only bid at the best ask would take, so it is refused
+    let crossed = false;
+    try {
+      await program.methods.placeLimitOrder({ buy: {} }, new BN(5150), new BN(1), new BN(0), new BN(0), { postOnly: {} }).accounts(book).rpc();
+    } catch {
+      crossed = true;
+    }
+    assert.isTrue(crossed);
+  });
+
+  it("cancel_order releases collateral and only the owner can cancel", async () => {
+    const slug = padBytes("BTC_DEC31_2025", 32);
+    const [market] = PublicKey.findProgramAddressSync(
+      [Buffer.from("market"), wallet.publicKey.toBuffer(), Buffer.from(slug)],
+      program.programId
+    );
+    const [orderbook] = PublicKey.findProgramAddressSync([Buffer.from("orderbook"), market.toBuffer()], program.programId);
+    const [bids] = PublicKey.findProgramAddressSync([Buffer.from("bids"), market.toBuffer()], program.programId);
+    const [asks] = PublicKey.findProgramAddressSync([Buffer.from("asks"), market.toBuffer()], program.programId);
+    const openOrdersOf = (owner: PublicKey) =>
+      PublicKey.findProgramAddressSync([Buffer.from("open_orders"), market.toBuffer(), owner.toBuffer()], program.programId)[0];
+    const book = (owner: PublicKey) => ({
+      market, orderBook: orderbook, bids, asks, openOrders: openOrdersOf(owner), ...custody(market), ownerTokens: tokensOf(owner),
+      marketFees: feesOf(market), owner, systemProgram: SystemProgram.programId,
+    });
+    const mine = book(wallet.publicKey);
+    const before = await program.account.openOrders.fetch(mine.openOrders);
+
+    // orders 4, 5 and 6 on top of the four resting from the previous test
+    await program.methods.placeLimitOrder({ buy: {} }, new BN(4900), new BN(5), new BN(0), new BN(7), { gtc: {} }).accounts(mine).rpc();
+    await program.methods.placeLimitOrder({ buy: {} }, new BN(4950), new BN(1), new BN(0), new BN(8), { gtc: {} }).accounts(mine).rpc();
+    await program.methods.placeLimitOrder({ sell: {} }, new BN(6000), new BN(2), new BN(0), new BN(9), { gtc: {} }).accounts(mine).rpc();
+
+    await program.methods.cancelOrder(new BN(4)).accounts(mine).rpc();
+    await program.methods.cancelOrderByClientId(new BN(8)).accounts(mine).rpc();
+    let oo = await program.account.openOrders.fetch(mine.openOrders);
+    // 5 @ 0.49 locked 3 and 1 @ 0.495 locked 1 (both rounded up), now free
+    assert.equal(oo.quoteFree.toNumber() - before.quoteFree.toNumber(), 4);
+    assert.equal(oo.quoteLocked.toNumber(), before.quoteLocked.toNumber());
+    assert.equal((await program.account.bookSide.fetch(bids)).levelCount, 2);
+
+    // someone else can't cancel our ask, but can cancel all of their own
+    const stranger = anchor.web3.Keypair.generate();
+    await provider.connection.confirmTransaction(
+      await provider.connection.requestAirdrop(stranger.publicKey, 1_000_000_000)
+    );
+    await fund(stranger.publicKey, 1_000);
+    const theirs = book(stranger.publicKey);
+    await program.methods.placeLimitOrder({ sell: {} }, new BN(7000), new BN(1), new BN(0), new BN(9), { gtc: {} }).accounts(theirs).signers([stranger]).rpc();
+    let refused = false;
+    try {
+      await program.methods.cancelOrder(new BN(6)).accounts(theirs).signers([stranger]).rpc();
+    } catch (e) {
+      refused = String(e).includes("NotOrderOwner");
+    }
+    assert.isTrue(refused);
+    await program.methods.cancelAllOrders().accounts(theirs).signers([stranger]).rpc();
+    assert.equal((await program.account.openOrders.fetch(theirs.openOrders)).baseFree.toNumber(), 1);
+    // and take the released ask collateral back out of the vault
+    await program.methods.settleFunds().accounts(theirs).signers([stranger]).rpc();
+    assert.equal((await program.account.openOrders.fetch(theirs.openOrders)).baseFree.toNumber(), 0);
+    assert.equal(Number((await provider.connection.getTokenAccountBalance(theirs.ownerTokens)).value.amount), 1_000);
+
+    await program.methods.cancelOrder(new BN(6)).accounts(mine).rpc();
+    const askSide = await program.account.bookSide.fetch(asks);
+    assert.equal(askSide.orderCount, 1);
+    const ob = await program.account.orderBook.fetch(orderbook);
+    assert.equal(ob.bestAskBps.toNumber(), 5150);
+  });
+
+  it("time in force: FOK and post-only refuse, IOC never rests, GTT expires and is cranked", async () => {
+    const slug = padBytes("BTC_DEC31_2025", 32);
+    const [market] = PublicKey.findProgramAddressSync(
+      [Buffer.from("market"), wallet.publicKey.toBuffer(), Buffer.from(slug)],
+      program.programId
+    );
+    const [orderBook] = PublicKey.findProgramAddressSync([Buffer.from("orderbook"), market.toBuffer()], program.programId);
+    const [bids] = PublicKey.findProgramAddressSync([Buffer.from("bids"), market.toBuffer()], program.programId);
+    const [asks] = PublicKey.findProgramAddressSync([Buffer.from("asks"), market.toBuffer()], program.programId);
+    const [openOrders] = PublicKey.findProgramAddressSync(
+      [Buffer.from("open_orders"), market.toBuffer(), wallet.publicKey.toBuffer()],
+      program.programId
+    );
+    const book = {
+      market, orderBook, bids, asks, openOrders, ...custody(market), ownerTokens: tokensOf(wallet.publicKey),
+      marketFees: feesOf(market), owner: wallet.publicKey, systemProgram: SystemProgram.programId,
+    };
+    const refused = async (tif: any) => {
+      try {
+        await program.methods.placeLimitOrder({ buy: {} }, new BN(5150), new BN(2), new BN(0), new BN(0), tif).accounts(book).rpc();
+      } catch {
+        return true;
+      }
+      return false;
+    };
+    // only 1 rests at 5150
+    assert.isTrue(await refused({ fok: {} }));
+    assert.isTrue(await refused({ postOnly: {} }));
+    await program.methods.placeLimitOrder({ buy: {} }, new BN(4000), new BN(1), new BN(0), new BN(0), { ioc: {} }).accounts(book).rpc();
+    assert.equal((await program.account.bookSide.fetch(bids)).orderCount, 3);
+
+    const expiry = (await chainTime()) + 2;
+    await program.methods.placeLimitOrder({ sell: {} }, new BN(6000), new BN(1), new BN(expiry), new BN(0), { gtt: {} }).accounts(book).rpc();
+    const before = await program.account.openOrders.fetch(openOrders);
+    await waitUntil(expiry);
     await program.methods
-      .placeLimitOrder(0, new BN(50500), new BN(1), new BN(0))
-      .accounts({ market, orderBook: orderbook, owner: wallet.publicKey })
+      .pruneExpiredOrders()
+      .accounts({ market, orderBook, bids, asks })
+      .remainingAccounts([{ pubkey: openOrders, isWritable: true, isSigner: false }])
       .rpc();
-    let ob = await program.account.orderBook.fetch(orderbook);
-    assert.isAbove(ob.bestBid, 0.0);
+    const after = await program.account.openOrders.fetch(openOrders);
+    assert.equal(after.baseFree.toNumber() - before.baseFree.toNumber(), 1);
+    const askSide = await program.account.bookSide.fetch(asks);
+    assert.equal(askSide.orderCount, 1);
+    assert.equal(askSide.levels[0].priceBps.toNumber(), 5150);
+  });
+
+  it("trade_distribution moves (mu, sigma) and locks collateral", async () => {
+    const slug = padBytes("BTC_DEC31_2025", 32);
+    const [market] = PublicKey.findProgramAddressSync(
+      [Buffer.from("market"), wallet.publicKey.toBuffer(), Buffer.from(slug)],
+      program.programId
+    );
+    const positionId = new BN(1);
+    const [position] = PublicKey.findProgramAddressSync(
+      [Buffer.from("dist_position"), market.toBuffer(), wallet.publicKey.toBuffer(), positionId.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    // sigma below sigma_min is rejected
+    let failed = false;
+    try {
+      await program.methods
+        .tradeDistribution(positionId, new BN(52_000_00), new BN(500_00), new BN(1_000_000_000))
+        .accounts({ market, position, ...custody(market), traderTokens: tokensOf(wallet.publicKey), trader: wallet.publicKey, systemProgram: SystemProgram.programId })
+        .rpc();
+    } catch {
+      failed = true;
+    }
+    assert.isTrue(failed);
+
+    const vaultBefore = await vaultBalance(market);
     await program.methods
-      .placeLimitOrder(1, new BN(51500), new BN(1), new BN(0))
-      .accounts({ market, orderBook: orderbook, owner: wallet.publicKey })
+      .tradeDistribution(positionId, new BN(52_000_00), new BN(8_000_00), new BN(1_000_000_000))
+      .accounts({ market, position, ...custody(market), traderTokens: tokensOf(wallet.publicKey), trader: wallet.publicKey, systemProgram: SystemProgram.programId })
       .rpc();
-    ob = await program.account.orderBook.fetch(orderbook);
-    assert.isAbove(ob.bestAsk, 0.0);
+    const acc = await program.account.market.fetch(market);
+    assert.strictEqual(acc.mu.toNumber(), 52_000_00);
+    assert.strictEqual(acc.sigma.toNumber(), 8_000_00);
+    const pos = await program.account.distributionPosition.fetch(position);
+    assert.strictEqual(pos.fromMu.toNumber(), 50_000_00);
+    assert.isAbove(pos.collateralLocked.toNumber(), 0);
+    assert.equal((await vaultBalance(market)) - vaultBefore, pos.collateralLocked.toNumber());
+  });
+
+  it("query_market returns interval probability and percentiles", async () => {
+    const slug = padBytes("BTC_DEC31_2025", 32);
+    const [market] = PublicKey.findProgramAddressSync(
+      [Buffer.from("market"), wallet.publicKey.toBuffer(), Buffer.from(slug)],
+      program.programId
+    );
+    // the market now sits at N(52_000, 8_000^2)
+    const q = await program.methods
+      .queryMarket(new BN(44_000_00), new BN(60_000_00))
+      .accounts({ market, mixture: null, histogram: null })
+      .view();
+    assert.approximately(q.probBetween.toNumber() / 1e9, 0.6827, 1e-4);
+    assert.strictEqual(q.p50.toNumber(), 52_000_00);
+    assert.approximately(q.p05.toNumber(), 52_000_00 - 1.6449 * 8_000_00, 10);
+    assert.approximately(q.p95.toNumber(), 52_000_00 + 1.6449 * 8_000_00, 10);
+  });
+
+  it("trade_mixture replaces a bimodal mixture, locks collateral and claims at resolution", async () => {
+    const slug = padBytes("RELEASE_Q2_OR_Q4", 32);
+    const unit = padBytes("MONTH", 12);
+    const oracle = padBytes("manual", 64);
+    const resolutionTime = (await chainTime()) + 10;
+    const params = {
+      slug: [...slug] as any,
+      outcomeMin: new BN(0),
+      outcomeMax: new BN(24_00),
+      unit: [...unit] as any,
+      decimals: 2,
+      marketType: { mixture: {} },
+      distType: 0,
+      mu: new BN(6_00),
+      sigma: new BN(2_00),
+      sigmaMin: new BN(25),
+      step: new BN(25),
+      resolutionTime: new BN(resolutionTime),
+      oracleConfig: [...oracle] as any,
+      feeBpsPlatform: 0,
+      feeBpsCreator: 0,
+      l2Norm: new BN(1_000_000),
+      distShape: new BN(0),
+      scoringRule: 0,
+      strike: new BN(6_00),
+      feeBpsLp: 0,
+    };
+    const [market] = PublicKey.findProgramAddressSync(
+      [Buffer.from("market"), wallet.publicKey.toBuffer(), Buffer.from(slug)],
+      program.programId
+    );
+    await program.methods
+      .createMarket(params as any)
+      .accounts({ market, authority: wallet.publicKey, systemProgram: SystemProgram.programId })
+      .rpc();
+    const subsidy = await initInfrastructure(market);
+    const [mixture] = PublicKey.findProgramAddressSync([Buffer.from("mixture"), market.toBuffer()], program.programId);
+    await program.methods
+      .initMixture([
+        { weightPpm: 600_000, mu: new BN(4_00), sigma: new BN(50) },
+        { weightPpm: 400_000, mu: new BN(10_00), sigma: new BN(1_00) },
+      ])
+      .accounts({ market, mixture, authority: wallet.publicKey, systemProgram: SystemProgram.programId })
+      .rpc();
+
+    const positionId = new BN(1);
+    const [position] = PublicKey.findProgramAddressSync(
+      [Buffer.from("mixture_position"), market.toBuffer(), wallet.publicKey.toBuffer(), positionId.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    const trade = {
+      market, mixture, position, ...custody(market), traderTokens: tokensOf(wallet.publicKey),
+      trader: wallet.publicKey, systemProgram: SystemProgram.programId,
+    };
+    // weights must sum to 1e6 ppm
+    let failed = false;
+    try {
+      await program.methods
+        .tradeMixture(positionId, [{ weightPpm: 500_000, mu: new BN(5_00), sigma: new BN(1_00) }], new BN(1_000_000_000))
+        .accounts(trade)
+        .rpc();
+    } catch {
+      failed = true;
+    }
+    assert.isTrue(failed);
+
+    await program.methods
+      .tradeMixture(
+        positionId,
+        [
+          { weightPpm: 300_000, mu: new BN(4_00), sigma: new BN(50) },
+          { weightPpm: 700_000, mu: new BN(10_00), sigma: new BN(1_00) },
+        ],
+        new BN(1_000_000_000)
+      )
+      .accounts(trade)
+      .rpc();
+    const state = await program.account.mixtureState.fetch(mixture);
+    assert.strictEqual(state.len, 2);
+    assert.strictEqual(state.components[1].weightPpm, 700_000);
+    let pos = await program.account.mixturePosition.fetch(position);
+    assert.strictEqual(pos.from[0].weightPpm, 600_000);
+    assert.isAbove(pos.collateralLocked.toNumber(), 0);
+    assert.equal(await vaultBalance(market), subsidy + pos.collateralLocked.toNumber());
+
+    // the release slips to month 10, the mode the trader moved weight onto
+    await program.methods
+      .fundVault(new BN(1_000_000_000))
+      .accounts({ market, ...custody(market), funderTokens: tokensOf(wallet.publicKey), funder: wallet.publicKey })
+      .rpc();
+    await waitUntil(resolutionTime);
+    await program.methods
+      .resolveMarket(new BN(10_00), Buffer.alloc(0))
+      .accounts({ market, authority: wallet.publicKey })
+      .rpc();
+    const claim = { market, position, ...custody(market), ownerTokens: tokensOf(wallet.publicKey), owner: wallet.publicKey };
+    const vaultBefore = await vaultBalance(market);
+    await program.methods.claimMixturePayout().accounts(claim).rpc();
+    pos = await program.account.mixturePosition.fetch(position);
+    assert.isTrue(pos.settled);
+    assert.isAbove(vaultBefore - (await vaultBalance(market)), pos.collateralLocked.toNumber());
+
+    // a second claim is refused
+    failed = false;
+    try {
+      await program.methods.claimMixturePayout().accounts(claim).rpc();
+    } catch {
+      failed = true;
+    }
+    assert.isTrue(failed);
+  });
+
+  it("trade_histogram shifts bin mass and settles by the outcome bin", async () => {
+    const slug = padBytes("RAINFALL_MM_HIST", 32);
+    const unit = padBytes("MM", 12);
+    const oracle = padBytes("manual", 64);
+    // trading closes and resolution opens once the chain passes this
+    const resolutionTime = (await chainTime()) + 10;
+    const params = {
+      slug: [...slug] as any,
+      outcomeMin: new BN(0),
+      outcomeMax: new BN(100),
+      unit: [...unit] as any,
+      decimals: 0,
+      marketType: { histogram: {} },
+      distType: 0,
+      mu: new BN(50),
+      sigma: new BN(20),
+      sigmaMin: new BN(1),
+      step: new BN(10),
+      resolutionTime: new BN(resolutionTime),
+      oracleConfig: [...oracle] as any,
+      feeBpsPlatform: 0,
+      feeBpsCreator: 0,
+      l2Norm: new BN(1_000_000),
+      distShape: new BN(0),
+      scoringRule: 0,
+      strike: new BN(50),
+      feeBpsLp: 0,
+    };
+    const [market] = PublicKey.findProgramAddressSync(
+      [Buffer.from("market"), wallet.publicKey.toBuffer(), Buffer.from(slug)],
+      program.programId
+    );
+    await program.methods
+      .createMarket(params as any)
+      .accounts({ market, authority: wallet.publicKey, systemProgram: SystemProgram.programId })
+      .rpc();
+    const subsidy = await initInfrastructure(market);
+    const [histogram] = PublicKey.findProgramAddressSync([Buffer.from("histogram"), market.toBuffer()], program.programId);
+    await program.methods
+      .initHistogram()
+      .accounts({ market, histogram, authority: wallet.publicKey, systemProgram: SystemProgram.programId })
+      .rpc();
+    let hist = await program.account.histogramState.fetch(histogram);
+    assert.strictEqual(hist.bins, 10);
+    assert.strictEqual(hist.mass[0], 100_000_000);
+
+    const positionId = new BN(1);
+    const [position] = PublicKey.findProgramAddressSync(
+      [Buffer.from("histogram_position"), market.toBuffer(), wallet.publicKey.toBuffer(), positionId.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    await program.methods
+      .tradeHistogram(positionId, [{ fromBin: 0, toBin: 4, mass: 50_000_000 }], new BN(1_000_000_000))
+      .accounts({
+        market, histogram, position, ...custody(market), traderTokens: tokensOf(wallet.publicKey),
+        trader: wallet.publicKey, systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+    hist = await program.account.histogramState.fetch(histogram);
+    assert.strictEqual(hist.mass[0], 50_000_000);
+    assert.strictEqual(hist.mass[4], 150_000_000);
+    let pos = await program.account.histogramPosition.fetch(position);
+    assert.isAbove(pos.collateralLocked.toNumber(), 0);
+    assert.equal(await vaultBalance(market), subsidy + pos.collateralLocked.toNumber());
+    // a winning trader is paid out of the other side's collateral; stand in for it
+    await program.methods
+      .fundVault(new BN(1_000_000_000))
+      .accounts({ market, ...custody(market), funderTokens: tokensOf(wallet.publicKey), funder: wallet.publicKey })
+      .rpc();
+
+    await waitUntil(resolutionTime);
+    await program.methods
+      .resolveMarket(new BN(42), Buffer.alloc(0))
+      .accounts({ market, authority: wallet.publicKey })
+      .rpc();
+    await program.methods
+      .settleHistogramPosition()
+      .accounts({ market, position, ...custody(market), ownerTokens: tokensOf(wallet.publicKey), owner: wallet.publicKey })
+      .rpc();
+    pos = await program.account.histogramPosition.fetch(position);
+    assert.equal(await vaultBalance(market), subsidy + pos.collateralLocked.toNumber() + 1_000_000_000 - pos.payout.toNumber());
+    assert.strictEqual(pos.settled, 1);
+    // the trader moved mass into the outcome bin, so they get back more than they posted
+    assert.isAbove(pos.payout.toNumber(), pos.collateralLocked.toNumber());
+  });
+
+  it("claim_payout settles a CRPS-scored position", async () => {
+    const slug = padBytes("CPI_YOY_CRPS", 32);
+    const unit = padBytes("PCT", 12);
+    const oracle = padBytes("manual", 64);
+    const resolutionTime = (await chainTime()) + 10;
+    const params = {
+      slug: [...slug] as any,
+      outcomeMin: new BN(0),
+      outcomeMax: new BN(10_000),
+      unit: [...unit] as any,
+      decimals: 3,
+      marketType: { distributional: {} },
+      distType: 0,
+      mu: new BN(3_000),
+      sigma: new BN(500),
+      sigmaMin: new BN(100),
+      step: new BN(100),
+      resolutionTime: new BN(resolutionTime),
+      oracleConfig: [...oracle] as any,
+      feeBpsPlatform: 0,
+      feeBpsCreator: 0,
+      l2Norm: new BN(1_000_000),
+      distShape: new BN(0),
+      scoringRule: 3,
+      strike: new BN(3_000),
+      feeBpsLp: 0,
+    };
+    const [market] = PublicKey.findProgramAddressSync(
+      [Buffer.from("market"), wallet.publicKey.toBuffer(), Buffer.from(slug)],
+      program.programId
+    );
+    await program.methods
+      .createMarket(params as any)
+      .accounts({ market, authority: wallet.publicKey, systemProgram: SystemProgram.programId })
+      .rpc();
+    await initInfrastructure(market);
+    await program.methods
+      .fundVault(new BN(1_000_000_000))
+      .accounts({ market, ...custody(market), funderTokens: tokensOf(wallet.publicKey), funder: wallet.publicKey })
+      .rpc();
+    const positionId = new BN(1);
+    const [position] = PublicKey.findProgramAddressSync(
+      [Buffer.from("dist_position"), market.toBuffer(), wallet.publicKey.toBuffer(), positionId.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    await program.methods
+      .tradeDistribution(positionId, new BN(3_400), new BN(300), new BN(1_000_000_000))
+      .accounts({ market, position, ...custody(market), traderTokens: tokensOf(wallet.publicKey), trader: wallet.publicKey, systemProgram: SystemProgram.programId })
+      .rpc();
+    // only the market's authority can resolve it, and not before its resolution time
+    const stranger = anchor.web3.Keypair.generate();
+    let refused = false;
+    try {
+      await program.methods.resolveMarket(new BN(3_450), Buffer.alloc(0)).accounts({ market, authority: stranger.publicKey }).signers([stranger]).rpc();
+    } catch {
+      refused = true;
+    }
+    assert.isTrue(refused);
+    refused = false;
+    try {
+      await program.methods.resolveMarket(new BN(3_450), Buffer.alloc(0)).accounts({ market, authority: wallet.publicKey }).rpc();
+    } catch (e) {
+      refused = String(e).includes("ResolutionTooEarly");
+    }
+    assert.isTrue(refused);
+    await waitUntil(resolutionTime);
+    // the outcome has to lie in [outcome_min, outcome_max]
+    refused = false;
+    try {
+      await program.methods.resolveMarket(new BN(10_001), Buffer.alloc(0)).accounts({ market, authority: wallet.publicKey }).rpc();
+    } catch (e) {
+      refused = String(e).includes("OutcomeOutOfRange");
+    }
+    assert.isTrue(refused);
+    // anyone can close the market once its resolution time has passed, and the authority
+    // resolves it from there
+    await program.methods.closeMarket().accounts({ market }).rpc();
+    assert.deepEqual((await program.account.market.fetch(market)).status, { closed: {} });
+    const late = new BN(2);
+    const [latePosition] = PublicKey.findProgramAddressSync(
+      [Buffer.from("dist_position"), market.toBuffer(), wallet.publicKey.toBuffer(), late.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    refused = false;
+    try {
+      await program.methods
+        .tradeDistribution(late, new BN(3_300), new BN(300), new BN(1_000_000_000))
+        .accounts({ market, position: latePosition, ...custody(market), traderTokens: tokensOf(wallet.publicKey), trader: wallet.publicKey, systemProgram: SystemProgram.programId })
+        .rpc();
+    } catch (e) {
+      refused = String(e).includes("MarketClosed");
+    }
+    assert.isTrue(refused);
+    await program.methods
+      .resolveMarket(new BN(3_450), Buffer.alloc(0))
+      .accounts({ market, authority: wallet.publicKey })
+      .rpc();
+
+    const claim = { market, position, ...custody(market), ownerTokens: tokensOf(wallet.publicKey), owner: wallet.publicKey };
+    const vaultBefore = await vaultBalance(market);
+    const listener = program.addEventListener("payoutClaimed", (e) => {
+      // the trader moved towards the outcome, so they get back more than they posted
+      assert.isAbove(e.amount.toNumber(), 0);
+    });
+    await program.methods.claimPayout().accounts(claim).rpc();
+    await program.removeEventListener(listener);
+    const pos = await program.account.distributionPosition.fetch(position);
+    assert.isTrue(pos.settled);
+    assert.isAbove(vaultBefore - (await vaultBalance(market)), 0);
+
+    // a second claim is refused
+    let failed = false;
+    try {
+      await program.methods.claimPayout().accounts(claim).rpc();
+    } catch {
+      failed = true;
+    }
+    assert.isTrue(failed);
   });
 
-  it("execute_market_order routes", async () => {
+  it("execute_market_order fills against resting orders within the limit", async () => {
     const slug = padBytes("BTC_DEC31_2025", 32);
     const [market] = PublicKey.findProgramAddressSync(
       [Buffer.from("market"), wallet.publicKey.toBuffer(), Buffer.from(slug)],
       program.programId
     );
     const [orderbook] = PublicKey.findProgramAddressSync([Buffer.from("orderbook"), market.toBuffer()], program.programId);
-    const [pool] = PublicKey.findProgramAddressSync([Buffer.from("pool"), market.toBuffer()], program.programId);
+    const [bids] = PublicKey.findProgramAddressSync([Buffer.from("bids"), market.toBuffer()], program.programId);
+    const [asks] = PublicKey.findProgramAddressSync([Buffer.from("asks"), market.toBuffer()], program.programId);
+    const openOrdersOf = (owner: PublicKey) =>
+      PublicKey.findProgramAddressSync([Buffer.from("open_orders"), market.toBuffer(), owner.toBuffer()], program.programId)[0];
+    const openOrders = openOrdersOf(wallet.publicKey);
+    const accounts = {
+      market, orderBook: orderbook, bids, asks, openOrders, pmammPool: null, pmammPosition: null,
+      ...custody(market), takerTokens: tokensOf(wallet.publicKey), marketFees: feesOf(market), taker: wallet.publicKey,
+      systemProgram: SystemProgram.programId,
+    };
+
+    // the only ask is 1 @ 5150; the rest of the buy is dropped
+    await program.methods.executeMarketOrder({ buy: {} }, new BN(2), new BN(5200)).accounts(accounts).rpc();
+    let ob = await program.account.orderBook.fetch(orderbook);
+    assert.equal(ob.bestAskBps.toNumber(), 0);
+
+    // selling 2 into bids of 1 + 3 @ 5050 partially fills the second maker
+    await program.methods.executeMarketOrder({ sell: {} }, new BN(2), new BN(5050)).accounts(accounts).rpc();
+    const bidSide = await program.account.bookSide.fetch(bids);
+    assert.equal(bidSide.levels[0].priceBps.toNumber(), 5050);
+    assert.equal(bidSide.levels[0].count, 1);
+    assert.equal(bidSide.levels[0].totalSize.toNumber(), 2);
+
+    // nothing rests at or above the seller's limit
+    let failed = false;
+    try {
+      await program.methods.executeMarketOrder({ sell: {} }, new BN(1), new BN(5100)).accounts(accounts).rpc();
+    } catch {
+      failed = true;
+    }
+    assert.isTrue(failed);
+
+    // a stranger's ask of 2 @ 5500 posts both units of collateral
+    const maker = anchor.web3.Keypair.generate();
+    await provider.connection.confirmTransaction(await provider.connection.requestAirdrop(maker.publicKey, 1_000_000_000));
+    await fund(maker.publicKey, 1_000);
+    const makerOrders = openOrdersOf(maker.publicKey);
     await program.methods
-      .executeMarketOrder(0, new BN(2))
-      .accounts({ market, orderBook: orderbook, liquidityPool: pool, taker: wallet.publicKey })
+      .placeLimitOrder({ sell: {} }, new BN(5500), new BN(2), new BN(0), new BN(0), { gtc: {} })
+      .accounts({
+        market, orderBook: orderbook, bids, asks, openOrders: makerOrders, ...custody(market), ownerTokens: tokensOf(maker.publicKey),
+        marketFees: feesOf(market), owner: maker.publicKey, systemProgram: SystemProgram.programId,
+      })
+      .signers([maker])
       .rpc();
+    // a fill can only settle against the maker's open orders
+    failed = false;
+    try {
+      await program.methods.executeMarketOrder({ buy: {} }, new BN(2), new BN(5500)).accounts(accounts).rpc();
+    } catch (e) {
+      failed = String(e).includes("InvalidOpenOrders");
+    }
+    assert.isTrue(failed);
+
+    const mine = await program.account.openOrders.fetch(openOrders);
+    const vault = await vaultBalance(market);
+    await program.methods
+      .executeMarketOrder({ buy: {} }, new BN(2), new BN(5500))
+      .accounts(accounts)
+      .remainingAccounts([{ pubkey: makerOrders, isWritable: true, isSigner: false }])
+      .rpc();
+    // the taker paid 1.1 for 2 YES (plus 1 each to the platform, the creator and the maker), and
+    // the maker's 2 locked units now back the NO side while it is owed the taker's payment and
+    // the LP fee
+    assert.equal((await vaultBalance(market)) - vault, 1_100 + 3);
+    assert.equal((await program.account.openOrders.fetch(openOrders)).yesShares.toNumber() - mine.yesShares.toNumber(), 2);
+    const theirs = await program.account.openOrders.fetch(makerOrders);
+    assert.equal(theirs.baseLocked.toNumber(), 0);
+    assert.equal(theirs.quoteFree.toNumber(), 1_100 + 1);
+    assert.equal(theirs.noShares.toNumber(), 2);
+    await program.methods
+      .settleFunds()
+      .accounts({ market, openOrders: makerOrders, ...custody(market), ownerTokens: tokensOf(maker.publicKey), owner: maker.publicKey })
+      .signers([maker])
+      .rpc();
+    assert.equal(Number((await provider.connection.getTokenAccountBalance(tokensOf(maker.publicKey))).value.amount), 1_000 - 2 + 1_100 + 1);
   });
 
   it("init_pmamm and trade buy/sell adjusts pool", async () => {
@@ -138,25 +880,189 @@ describe("solana_prediction program e2e", () => {
     try { await program.account.pmAmmPool.fetch(pmamm); } catch { needInit = true; }
     if (needInit) {
       await program.methods
-        .initPmamm(new BN(100_000), true, 50, new BN(Math.floor(Date.now() / 1000) + 3600))
-        .accounts({ market, pmammPool: pmamm, authority: wallet.publicKey, systemProgram: SystemProgram.programId })
+        .initPmamm({
+          l0: new BN(100_000),
+          schedule: { sqrtDecay: {} },
+          lFloor: new BN(0),
+          lCeiling: new BN(100_000_000_000),
+          feeBps: 50,
+          expiryTs: new BN(Math.floor(Date.now() / 1000) + 3600),
+          handoffTs: new BN(0),
+        })
+        .accounts({
+          market, pmammPool: pmamm, ...custody(market), authorityTokens: tokensOf(wallet.publicKey),
+          authority: wallet.publicKey, systemProgram: SystemProgram.programId,
+        })
         .rpc();
     }
+    const [position] = PublicKey.findProgramAddressSync(
+      [Buffer.from("pmamm_position"), market.toBuffer(), wallet.publicKey.toBuffer()],
+      program.programId
+    );
+    const trade = {
+      market, pmammPool: pmamm, pmammPosition: position, ...custody(market), takerTokens: tokensOf(wallet.publicKey),
+      marketFees: feesOf(market), taker: wallet.publicKey, systemProgram: SystemProgram.programId,
+    };
+    // L decays between trades, so compare where the reserves sit on the curve, y / x, which
+    // only a trade moves
+    const skew = (pool: any) => pool.y.toNumber() / pool.x.toNumber();
+    // platform and creator fees this market is owed
+    const owed = async () => {
+      const fees = await program.account.marketFees.fetch(feesOf(market));
+      return fees.platformFees.toNumber() + fees.creatorFees.toNumber();
+    };
     const before = await program.account.pmAmmPool.fetch(pmamm);
-    await program.methods
-      .tradePmamm(0, new BN(10))
-      .accounts({ market, pmammPool: pmamm, taker: wallet.publicKey })
-      .rpc();
-    let after = await program.account.pmAmmPool.fetch(pmamm);
-    assert.isAtMost(after.x.toNumber ? after.x.toNumber() : after.x, (before.x.toNumber ? before.x.toNumber() : before.x));
-    assert.isAtLeast(after.y.toNumber ? after.y.toNumber() : after.y, (before.y.toNumber ? before.y.toNumber() : before.y));
+    let owedBefore = await owed();
+    let vault = await vaultBalance(market);
+    await program.methods.tradePmamm({ buy: {} }, new BN(10), noMinOut, noMaxIn).accounts(trade).rpc();
+    const bought = await program.account.pmAmmPool.fetch(pmamm);
+    assert.isAbove(skew(bought), skew(before));
+    assert.isAbove(bought.fees.toNumber(), before.fees.toNumber());
+    // the buyer paid the pool's collateral, its LP fee included, and the market's fees into the
+    // vault and holds the 10 shares
+    const charged = (await owed()) - owedBefore;
+    assert.isAbove(charged, 0);
+    const paid = bought.collateral.toNumber() - before.collateral.toNumber() + charged;
+    assert.equal((await vaultBalance(market)) - vault, paid);
+    assert.equal((await program.account.pmAmmPosition.fetch(position)).shares.toNumber(), 10);
 
+    // the quote of a sale leaves the pool alone; asking for more than it pays out is refused
+    const quote = await program.methods.quotePmamm({ sell: {} }, new BN(7)).accounts({ market, pmammPool: pmamm }).view();
+    assert.equal(quote.amountIn.toNumber(), 7);
+    assert.equal((await program.account.pmAmmPool.fetch(pmamm)).x.toNumber(), bought.x.toNumber());
+    let failed = false;
+    try {
+      await program.methods.tradePmamm({ sell: {} }, new BN(7), quote.amountOut.addn(1_000), noMaxIn).accounts(trade).rpc();
+    } catch {
+      failed = true;
+    }
+    assert.isTrue(failed);
+
+    vault = await vaultBalance(market);
+    owedBefore = await owed();
+    await program.methods.tradePmamm({ sell: {} }, new BN(7), noMinOut, noMaxIn).accounts(trade).rpc();
+    const sold = await program.account.pmAmmPool.fetch(pmamm);
+    assert.isBelow(skew(sold), skew(bought));
+    const received = bought.collateral.toNumber() - sold.collateral.toNumber() - ((await owed()) - owedBefore);
+    assert.equal(vault - (await vaultBalance(market)), received);
+    // L may have decayed a little since the quote
+    assert.approximately(received, quote.amountOut.toNumber(), 1);
+    assert.equal((await program.account.pmAmmPosition.fetch(position)).shares.toNumber(), 3);
+
+    // the position can't sell shares it doesn't hold
+    failed = false;
+    try {
+      await program.methods.tradePmamm({ sell: {} }, new BN(4), noMinOut, noMaxIn).accounts(trade).rpc();
+    } catch {
+      failed = true;
+    }
+    assert.isTrue(failed);
+
+    // nor buy past the price bound, which would only partly fill
+    failed = false;
+    try {
+      await program.methods.tradePmamm({ buy: {} }, new BN(1_000_000_000_000), noMinOut, noMaxIn).accounts(trade).rpc();
+    } catch {
+      failed = true;
+    }
+    assert.isTrue(failed);
+
+    // 100_000·sqrt(T - t), with under an hour left
+    const { liquidity, quoting } = await program.methods.queryLiquidity().accounts({ market, pmammPool: pmamm }).view();
+    assert.isTrue(quoting);
+    assert.isAbove(liquidity.toNumber(), 0);
+    assert.isAtMost(liquidity.toNumber(), 6_000_000);
+  });
+
+  it("execute_market_order routes what the book can't beat to the pm-AMM", async () => {
+    const slug = padBytes("BTC_DEC31_2025", 32);
+    const [market] = PublicKey.findProgramAddressSync(
+      [Buffer.from("market"), wallet.publicKey.toBuffer(), Buffer.from(slug)],
+      program.programId
+    );
+    const [orderbook] = PublicKey.findProgramAddressSync([Buffer.from("orderbook"), market.toBuffer()], program.programId);
+    const [bids] = PublicKey.findProgramAddressSync([Buffer.from("bids"), market.toBuffer()], program.programId);
+    const [asks] = PublicKey.findProgramAddressSync([Buffer.from("asks"), market.toBuffer()], program.programId);
+    const [pmamm] = PublicKey.findProgramAddressSync([Buffer.from("pmamm"), market.toBuffer()], program.programId);
+    const [openOrders] = PublicKey.findProgramAddressSync(
+      [Buffer.from("open_orders"), market.toBuffer(), wallet.publicKey.toBuffer()],
+      program.programId
+    );
+    const [position] = PublicKey.findProgramAddressSync(
+      [Buffer.from("pmamm_position"), market.toBuffer(), wallet.publicKey.toBuffer()],
+      program.programId
+    );
+    const book = {
+      market, orderBook: orderbook, bids, asks, openOrders, ...custody(market), ownerTokens: tokensOf(wallet.publicKey),
+      marketFees: feesOf(market), owner: wallet.publicKey, systemProgram: SystemProgram.programId,
+    };
+    await program.methods.placeLimitOrder({ buy: {} }, new BN(4960), new BN(2), new BN(0), new BN(0), { gtc: {} }).accounts(book).rpc();
+    const trade = {
+      market, pmammPool: pmamm, pmammPosition: position, ...custody(market), takerTokens: tokensOf(wallet.publicKey),
+      marketFees: feesOf(market), taker: wallet.publicKey, systemProgram: SystemProgram.programId,
+    };
+    const routed = { ...trade, orderBook: orderbook, bids, asks, openOrders };
+    // the pool leg sells shares, so hold enough of them first
+    await program.methods.tradePmamm({ buy: {} }, new BN(6), noMinOut, noMaxIn).accounts(trade).rpc();
+    const before = await program.account.pmAmmPool.fetch(pmamm);
+
+    // the bids of 2 @ 5050 and 2 @ 5000 beat the pool's bid of ~4975 (even odds less its
+    // 50 bps fee); the other 6 go to the pool rather than to the bid at 4960. The limit is
+    // loose since the fee, rounded up to a whole unit, is a sixth of the pool leg here
     await program.methods
-      .tradePmamm(1, new BN(7))
-      .accounts({ market, pmammPool: pmamm, taker: wallet.publicKey })
+      .executeMarketOrder({ sell: {} }, new BN(10), new BN(3000))
+      .accounts(routed)
       .rpc();
-    after = await program.account.pmAmmPool.fetch(pmamm);
-    assert.isAtLeast(after.x.toNumber ? after.x.toNumber() : after.x, 0);
+    const bidSide = await program.account.bookSide.fetch(bids);
+    assert.equal(bidSide.levelCount, 1);
+    const ob = await program.account.orderBook.fetch(orderbook);
+    assert.equal(ob.bestBidBps.toNumber(), 4960);
+    const after = await program.account.pmAmmPool.fetch(pmamm);
+    assert.isBelow(after.y.toNumber() / after.x.toNumber(), before.y.toNumber() / before.x.toNumber());
+    assert.equal(after.sharesOutstanding.toNumber(), before.sharesOutstanding.toNumber() - 6);
+  });
+
+  it("claim_platform_fees and claim_creator_fees pay out what trading charged", async () => {
+    const slug = padBytes("BTC_DEC31_2025", 32);
+    const [market] = PublicKey.findProgramAddressSync(
+      [Buffer.from("market"), wallet.publicKey.toBuffer(), Buffer.from(slug)],
+      program.programId
+    );
+    const marketFees = feesOf(market);
+    const tokens = tokensOf(wallet.publicKey);
+    const balance = async () => Number((await provider.connection.getTokenAccountBalance(tokens)).value.amount);
+    const claim = { market, marketFees, ...custody(market), recipientTokens: tokens, claimant: wallet.publicKey };
+    const owed = await program.account.marketFees.fetch(marketFees);
+    // the book and pm-AMM trades above each paid 25 bps to the platform and to the creator, and
+    // an LP fee to the makers and the pm-AMM
+    assert.isAbove(owed.platformFees.toNumber(), 0);
+    assert.isAbove(owed.creatorFees.toNumber(), 0);
+    assert.isAbove(owed.lpFees.toNumber(), 0);
+
+    // the wallet is both the registry authority and the market's creator, but a stranger is neither
+    const stranger = anchor.web3.Keypair.generate();
+    let refused = false;
+    try {
+      await program.methods.claimPlatformFees().accounts({ ...claim, claimant: stranger.publicKey }).signers([stranger]).rpc();
+    } catch {
+      refused = true;
+    }
+    assert.isTrue(refused);
+
+    const vault = await vaultBalance(market);
+    let held = await balance();
+    await program.methods.claimPlatformFees().accounts(claim).rpc();
+    assert.equal((await balance()) - held, owed.platformFees.toNumber());
+    held = await balance();
+    await program.methods.claimCreatorFees().accounts(claim).rpc();
+    assert.equal((await balance()) - held, owed.creatorFees.toNumber());
+    assert.equal(vault - (await vaultBalance(market)), owed.platformFees.toNumber() + owed.creatorFees.toNumber());
+
+    // both buckets are empty now; LP fees went to the makers and the pm-AMM
+    const after = await program.account.marketFees.fetch(marketFees);
+    assert.equal(after.platformFees.toNumber(), 0);
+    assert.equal(after.creatorFees.toNumber(), 0);
+    assert.equal(after.lpFees.toNumber(), owed.lpFees.toNumber());
   });
 
   it("trade_pmamm rejects after expiry", async () => {
@@ -165,18 +1071,25 @@ describe("solana_prediction program e2e", () => {
     const oracle = padBytes("manual", 64);
     const params = {
       slug: [...slug] as any,
-      outcomeMin: 0.0,
-      outcomeMax: 1.0,
+      outcomeMin: new BN(0),
+      outcomeMax: new BN(1_000),
       unit: [...unit] as any,
+      decimals: 3,
+      marketType: { distributional: {} },
       distType: 0,
-      mu: 0.0,
-      sigma: 1.0,
-      sigmaMin: 0.1,
-      step: 0.1,
+      mu: new BN(0),
+      sigma: new BN(1_000),
+      sigmaMin: new BN(100),
+      step: new BN(100),
       resolutionTime: new BN(Math.floor(Date.now() / 1000) + 300),
       oracleConfig: [...oracle] as any,
       feeBpsPlatform: 0,
       feeBpsCreator: 0,
+      l2Norm: new BN(1_000),
+      distShape: new BN(0),
+      scoringRule: 0,
+      strike: new BN(0),
+      feeBpsLp: 0,
     };
     const [market] = PublicKey.findProgramAddressSync(
       [Buffer.from("market"), wallet.publicKey.toBuffer(), Buffer.from(slug)],
@@ -186,20 +1099,242 @@ describe("solana_prediction program e2e", () => {
       .createMarket(params as any)
       .accounts({ market, authority: wallet.publicKey, systemProgram: SystemProgram.programId })
       .rpc();
+    await initInfrastructure(market);
     const [pmamm] = PublicKey.findProgramAddressSync([Buffer.from("pmamm"), market.toBuffer()], program.programId);
+    const [position] = PublicKey.findProgramAddressSync(
+      [Buffer.from("pmamm_position"), market.toBuffer(), wallet.publicKey.toBuffer()],
+      program.programId
+    );
     await program.methods
-      .initPmamm(new BN(10_000), true, 50, new BN(Math.floor(Date.now() / 1000) - 5))
-      .accounts({ market, pmammPool: pmamm, authority: wallet.publicKey, systemProgram: SystemProgram.programId })
+      .initPmamm({
+        l0: new BN(10_000),
+        schedule: { sqrtDecay: {} },
+        lFloor: new BN(0),
+        lCeiling: new BN(10_000_000_000),
+        feeBps: 50,
+        expiryTs: new BN(Math.floor(Date.now() / 1000) - 5),
+        handoffTs: new BN(0),
+      })
+      .accounts({
+        market, pmammPool: pmamm, ...custody(market), authorityTokens: tokensOf(wallet.publicKey),
+        authority: wallet.publicKey, systemProgram: SystemProgram.programId,
+      })
       .rpc();
     let failed = false;
     try {
       await program.methods
-        .tradePmamm(0, new BN(1))
-        .accounts({ market, pmammPool: pmamm, taker: wallet.publicKey })
+        .tradePmamm({ buy: {} }, new BN(1), noMinOut, noMaxIn)
+        .accounts({
+          market, pmammPool: pmamm, pmammPosition: position, ...custody(market),
+          takerTokens: tokensOf(wallet.publicKey), marketFees: feesOf(market), taker: wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
         .rpc();
     } catch {
       failed = true;
     }
     assert.isTrue(failed);
   });
+
+  it("redeem_pmamm_position pays the shares out once YES wins", async () => {
+    const slug = padBytes("PMAMM_REDEEM", 32);
+    const unit = padBytes("USD", 12);
+    const oracle = padBytes("manual", 64);
+    const resolutionTime = (await chainTime()) + 15;
+    const params = {
+      slug: [...slug] as any,
+      outcomeMin: new BN(0),
+      outcomeMax: new BN(1_000),
+      unit: [...unit] as any,
+      decimals: 3,
+      marketType: { distributional: {} },
+      distType: 0,
+      mu: new BN(500),
+      sigma: new BN(100),
+      sigmaMin: new BN(10),
+      step: new BN(10),
+      resolutionTime: new BN(resolutionTime),
+      oracleConfig: [...oracle] as any,
+      feeBpsPlatform: 0,
+      feeBpsCreator: 0,
+      // no distribution side, so the pool is all the vault holds
+      l2Norm: new BN(0),
+      distShape: new BN(0),
+      scoringRule: 0,
+      strike: new BN(500),
+      feeBpsLp: 0,
+    };
+    const [market] = PublicKey.findProgramAddressSync(
+      [Buffer.from("market"), wallet.publicKey.toBuffer(), Buffer.from(slug)],
+      program.programId
+    );
+    await program.methods
+      .createMarket(params as any)
+      .accounts({ market, authority: wallet.publicKey, systemProgram: SystemProgram.programId })
+      .rpc();
+    await initInfrastructure(market);
+    const [pmamm] = PublicKey.findProgramAddressSync([Buffer.from("pmamm"), market.toBuffer()], program.programId);
+    const [position] = PublicKey.findProgramAddressSync(
+      [Buffer.from("pmamm_position"), market.toBuffer(), wallet.publicKey.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .initPmamm({
+        l0: new BN(100_000),
+        schedule: { static: {} },
+        lFloor: new BN(0),
+        lCeiling: new BN(100_000_000_000),
+        feeBps: 0,
+        expiryTs: new BN(resolutionTime),
+        handoffTs: new BN(0),
+      })
+      .accounts({
+        market, pmammPool: pmamm, ...custody(market), authorityTokens: tokensOf(wallet.publicKey),
+        authority: wallet.publicKey, systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+    await program.methods
+      .tradePmamm({ buy: {} }, new BN(100), noMinOut, noMaxIn)
+      .accounts({
+        market, pmammPool: pmamm, pmammPosition: position, ...custody(market),
+        takerTokens: tokensOf(wallet.publicKey), marketFees: feesOf(market), taker: wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+    const redeem = { market, pmammPool: pmamm, pmammPosition: position, ...custody(market), ownerTokens: tokensOf(wallet.publicKey), owner: wallet.publicKey };
+
+    // nothing pays out before resolution
+    let failed = false;
+    try {
+      await program.methods.redeemPmammPosition().accounts(redeem).rpc();
+    } catch (e) {
+      failed = String(e).includes("MarketNotResolved");
+    }
+    assert.isTrue(failed);
+
+    await waitUntil(resolutionTime);
+    await program.methods.resolveMarket(new BN(700), Buffer.alloc(0)).accounts({ market, authority: wallet.publicKey }).rpc();
+    // the authority takes back the pool's funding and gains, leaving the winning shares covered
+    await program.methods
+      .withdrawPmammResidual()
+      .accounts({ market, pmammPool: pmamm, ...custody(market), authorityTokens: tokensOf(wallet.publicKey), authority: wallet.publicKey })
+      .rpc();
+    const vault = await vaultBalance(market);
+    assert.equal(vault, 100);
+    await program.methods.redeemPmammPosition().accounts(redeem).rpc();
+    assert.equal(vault - (await vaultBalance(market)), 100);
+    assert.equal(await vaultBalance(market), 0);
+    const pos = await program.account.pmAmmPosition.fetch(position);
+    assert.equal(pos.shares.toNumber(), 0);
+    const pool = await program.account.pmAmmPool.fetch(pmamm);
+    assert.equal(pool.sharesOutstanding.toNumber(), 0);
+
+    // the position is empty now
+    failed = false;
+    try {
+      await program.methods.redeemPmammPosition().accounts(redeem).rpc();
+    } catch {
+      failed = true;
+    }
+    assert.isTrue(failed);
+  });
+
+  it("Token-2022 collateral with a transfer fee keeps the vault ledger equal to its balance", async () => {
+    // 1% fee, capped well above anything moved here
+    const mintKp = anchor.web3.Keypair.generate();
+    const mintLen = getMintLen([ExtensionType.TransferFeeConfig]);
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        SystemProgram.createAccount({
+          fromPubkey: wallet.publicKey,
+          newAccountPubkey: mintKp.publicKey,
+          space: mintLen,
+          lamports: await provider.connection.getMinimumBalanceForRentExemption(mintLen),
+          programId: TOKEN_2022_PROGRAM_ID,
+        }),
+        createInitializeTransferFeeConfigInstruction(
+          mintKp.publicKey, wallet.publicKey, wallet.publicKey, 100, BigInt(1_000_000), TOKEN_2022_PROGRAM_ID
+        ),
+        createInitializeMintInstruction(mintKp.publicKey, 6, wallet.publicKey, null, TOKEN_2022_PROGRAM_ID)
+      ),
+      [mintKp]
+    );
+    const mint = mintKp.publicKey;
+    const ownerTokens = await fund(wallet.publicKey, 1_000_000, mint, TOKEN_2022_PROGRAM_ID);
+    const balanceOf = async (account: PublicKey) =>
+      Number((await provider.connection.getTokenAccountBalance(account)).value.amount);
+
+    const slug = padBytes("EURC_FEE_MINT", 32);
+    const params = {
+      slug: [...slug] as any,
+      outcomeMin: new BN(0),
+      outcomeMax: new BN(10_000),
+      unit: [...padBytes("PCT", 12)] as any,
+      decimals: 2,
+      marketType: { distributional: {} },
+      distType: 0,
+      mu: new BN(5_000),
+      sigma: new BN(1_000),
+      sigmaMin: new BN(100),
+      step: new BN(100),
+      resolutionTime: new BN(Math.floor(Date.now() / 1000) + 7 * 24 * 3600),
+      oracleConfig: [...padBytes("manual", 64)] as any,
+      feeBpsPlatform: 0,
+      feeBpsCreator: 0,
+      // no distribution side, so no opening subsidy leaves the owner's tokens
+      l2Norm: new BN(0),
+      distShape: new BN(0),
+      scoringRule: 0,
+      strike: new BN(5_000),
+      feeBpsLp: 0,
+    };
+    const [market] = PublicKey.findProgramAddressSync(
+      [Buffer.from("market"), wallet.publicKey.toBuffer(), Buffer.from(slug)],
+      program.programId
+    );
+    await program.methods
+      .createMarket(params as any)
+      .accounts({ market, authority: wallet.publicKey, systemProgram: SystemProgram.programId })
+      .rpc();
+    await initInfrastructure(market, mint, TOKEN_2022_PROGRAM_ID);
+
+    const [userProfile] = PublicKey.findProgramAddressSync([Buffer.from("user"), wallet.publicKey.toBuffer()], program.programId);
+    const [position] = PublicKey.findProgramAddressSync(
+      [Buffer.from("position"), wallet.publicKey.toBuffer(), market.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .initUser()
+      .accounts({ userProfile, owner: wallet.publicKey, systemProgram: SystemProgram.programId })
+      .rpc();
+    const accounts = {
+      market, position, userProfile, ...custody(market, mint, TOKEN_2022_PROGRAM_ID), ownerTokens,
+      owner: wallet.publicKey, systemProgram: SystemProgram.programId,
+    };
+    const vaultTokens = accounts.vaultTokens;
+
+    // the owner pays the fee on top, so the vault receives exactly what the position locks
+    await program.methods.openPosition(new BN(10), new BN(10_000)).accounts(accounts).rpc();
+    let vault = await program.account.collateralVault.fetch(accounts.collateralVault);
+    assert.equal(vault.balance.toNumber(), 10_000);
+    assert.equal(await balanceOf(vaultTokens), 10_000);
+    assert.equal(await balanceOf(ownerTokens), 1_000_000 - 10_000 - 102);
+
+    // on the way out the fee comes off what the owner receives
+    await program.methods.adjustPosition(new BN(0), new BN(-4_000)).accounts(accounts).rpc();
+    await program.methods.closePosition().accounts(accounts).rpc();
+    assert.isNull(await provider.connection.getAccountInfo(position));
+    // the account is gone, so a second close can't pay the collateral out again
+    let closedTwice = true;
+    try {
+      await program.methods.closePosition().accounts(accounts).rpc();
+    } catch {
+      closedTwice = false;
+    }
+    assert.isFalse(closedTwice);
+    vault = await program.account.collateralVault.fetch(accounts.collateralVault);
+    assert.equal(vault.balance.toNumber(), 0);
+    assert.equal(await balanceOf(vaultTokens), 0);
+    assert.equal(await balanceOf(ownerTokens), 1_000_000 - 10_102 + (4_000 - 40) + (6_000 - 60));
+  });
 });
@@ -19,7 +19,7 @@ describe("solana_prediction", () => {
   before(async () => {
     // Derive registry PDA
     [registryPda, registryBump] = PublicKey.findProgramAddressSync(
-      [Buffer.from("registry"), authority.publicKey.toBuffer()],
+      [Buffer.from("registry")],
       program.programId
     );
   });
@@ -141,6 +141,8 @@ describe("solana_prediction", () => {
     let marketPda: PublicKey;
     let liquidityPoolPda: PublicKey;
     let orderBookPda: PublicKey;
+    let bidsPda: PublicKey;
+    let asksPda: PublicKey;
     let collateralVaultPda: PublicKey;
     const slug = Buffer.from("btc-price-2025-12-31-000000000");
 
@@ -174,23 +176,38 @@ describe("solana_prediction", () => {
         [Buffer.from("collateral"), marketPda.toBuffer()],
         program.programId
       );
+
+      [bidsPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("bids"), marketPda.toBuffer()],
+        program.programId
+      );
+
+      [asksPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("asks"), marketPda.toBuffer()],
+        program.programId
+      );
     });
 
     it("Creates a distributional market", async () => {
       const params = {
         slug: Array.from(slug),
-        outcomeMin: 50000.0,
-        outcomeMax: 150000.0,
+        outcomeMin: new anchor.BN(50000),
+        outcomeMax: new anchor.BN(150000),
         unit: Array.from(Buffer.from("USD         ")),
+        decimals: 0,
+        marketType: { distributional: {} },
         distType: 0, // Gaussian
-        mu: 100000.0,
-        sigma: 20000.0,
-        sigmaMin: 5000.0,
-        step: 1000.0,
+        mu: new anchor.BN(100000),
+        sigma: new anchor.BN(20000),
+        sigmaMin: new anchor.BN(5000),
+        step: new anchor.BN(1000),
         resolutionTime: Math.floor(Date.now() / 1000) + 86400 * 365, // 1 year
         oracleConfig: Array.from(Buffer.alloc(64)),
         feeBpsPlatform: 30,
         feeBpsCreator: 20,
+        l2Norm: new anchor.BN(1_000_000),
+        distShape: new anchor.BN(0),
+        scoringRule: 0,
       };
 
       const tx = await program.methods
@@ -211,13 +228,13 @@ describe("solana_prediction", () => {
       // Fetch and verify market
       const market = await program.account.market.fetch(marketPda);
       assert.ok(market.authority.equals(marketAuthority.publicKey));
-      assert.equal(market.outcomeMin, 50000.0);
-      assert.equal(market.outcomeMax, 150000.0);
-      assert.equal(market.mu, 100000.0);
-      assert.equal(market.sigma, 20000.0);
+      assert.equal(market.outcomeMin.toNumber(), 50000);
+      assert.equal(market.outcomeMax.toNumber(), 150000);
+      assert.equal(market.mu.toNumber(), 100000);
+      assert.equal(market.sigma.toNumber(), 20000);
       assert.equal(market.feeBpsPlatform, 30);
       assert.equal(market.feeBpsCreator, 20);
-      assert.equal(market.status, 0); // Active
+      assert.deepEqual(market.status, { active: {} });
 
       // Verify liquidity pool
       const lp = await program.account.liquidityPool.fetch(liquidityPoolPda);
@@ -227,9 +244,21 @@ describe("solana_prediction", () => {
       // Verify order book
       const ob = await program.account.orderBook.fetch(orderBookPda);
       assert.ok(ob.market.equals(marketPda));
-      assert.equal(ob.bestBid, 0.0);
-      assert.equal(ob.bestAsk, 0.0);
+      assert.equal(ob.bestBidBps.toNumber(), 0);
+      assert.equal(ob.bestAskBps.toNumber(), 0);
       assert.equal(ob.eventCounter.toNumber(), 0);
+
+      await program.methods
+        .initOrderBook()
+        .accounts({
+          market: marketPda,
+          bids: bidsPda,
+          asks: asksPda,
+          authority: marketAuthority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([marketAuthority])
+        .rpc();
     });
 
     it("Places a limit order", async () => {
@@ -240,17 +269,24 @@ describe("solana_prediction", () => {
       );
       await provider.connection.confirmTransaction(airdropSig);
 
-      const side = 0; // Buy
-      const priceBps = 95000; // 9.5 (95000 bps = 9.5 when divided by 10000)
+      const side = { buy: {} };
+      const priceBps = 9500; // 0.95
       const size = 100;
       const expiry = Math.floor(Date.now() / 1000) + 3600;
 
       const tx = await program.methods
-        .placeLimitOrder(side, new anchor.BN(priceBps), new anchor.BN(size), new anchor.BN(expiry))
+        .placeLimitOrder(side, new anchor.BN(priceBps), new anchor.BN(size), new anchor.BN(expiry), new anchor.BN(1), { gtt: {} })
         .accounts({
           market: marketPda,
           orderBook: orderBookPda,
+          bids: bidsPda,
+          asks: asksPda,
+          openOrders: PublicKey.findProgramAddressSync(
+            [Buffer.from("open_orders"), marketPda.toBuffer(), trader.publicKey.toBuffer()],
+            program.programId
+          )[0],
           owner: trader.publicKey,
+          systemProgram: SystemProgram.programId,
         })
         .signers([trader])
         .rpc();
@@ -260,7 +296,14 @@ describe("solana_prediction", () => {
       // Verify order book updated
       const ob = await program.account.orderBook.fetch(orderBookPda);
       assert.equal(ob.eventCounter.toNumber(), 1);
-      assert.equal(ob.bestBid, 9.5); // price_bps / 10000
+      assert.equal(ob.bestBidBps.toNumber(), priceBps);
+
+      // the order rests on the bid slab
+      const bids = await program.account.bookSide.fetch(bidsPda);
+      assert.equal(bids.orderCount, 1);
+      const resting = bids.orders[bids.levels[0].head];
+      assert.ok(resting.owner.equals(trader.publicKey));
+      assert.equal(resting.size.toNumber(), size);
     });
 
     it("Executes a market order", async () => {
@@ -271,25 +314,31 @@ describe("solana_prediction", () => {
       );
       await provider.connection.confirmTransaction(airdropSig);
 
-      const side = 1; // Sell
+      const side = { sell: {} };
       const size = 50;
 
       const tx = await program.methods
-        .executeMarketOrder(side, new anchor.BN(size))
+        .executeMarketOrder(side, new anchor.BN(size), new anchor.BN(9000))
         .accounts({
           market: marketPda,
           orderBook: orderBookPda,
-          liquidityPool: liquidityPoolPda,
+          bids: bidsPda,
+          asks: asksPda,
+          pmammPool: null,
           taker: taker.publicKey,
         })
         .signers([taker])
         .rpc();
 
       console.log("Market order executed:", tx);
+
+      // filled against the resting 100 @ 9500 bid
+      const bids = await program.account.bookSide.fetch(bidsPda);
+      assert.equal(bids.levels[0].totalSize.toNumber(), 50);
     });
 
     it("Resolves a market", async () => {
-      const outcomeValue = 105000.0;
+      const outcomeValue = new anchor.BN(105000); // scaled by market.decimals
       const proof = Buffer.from("pyth-proof-placeholder");
 
       const tx = await program.methods
@@ -305,7 +354,7 @@ describe("solana_prediction", () => {
 
       // Verify market status
       const market = await program.account.market.fetch(marketPda);
-      assert.equal(market.status, 2); // Resolved
+      assert.deepEqual(market.status, { resolved: {} });
     });
   });
 